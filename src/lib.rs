@@ -1,2 +1,7 @@
+pub mod local_facade;
+#[cfg(feature = "testing")]
+pub mod mock_facade;
+pub mod retry;
 pub mod s3_facade;
 pub mod storage_facade;
+pub mod tiered_facade;
@@ -0,0 +1,641 @@
+// Provides a facade which stores objects as files beneath a root directory on the local filesystem
+//
+// Keys map onto paths relative to the root, with each `/` separated segment becoming a directory, so `a/b/c.txt` is stored at `{root}/a/b/c.txt`.
+// Behaviour follows the S3 facade wherever the two can agree, so code written against one runs against the other. This makes it a stand in for S3 in tests and local development, without AWS credentials.
+//
+// Directories only exist to hold files, much like prefixes in a bucket. They are created on write, and are never listed or returned themselves.
+use crate::storage_facade::{
+    Capabilities, DataReader, DataStoreId, DirectoryListing, ObjectEntry, ObjectMetadata,
+    StorageError, StorageFacade, StoreMetadata, as_directory, check_range, directory_prefix,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+/// Contains the root directory and metadata as fields
+pub struct LocalFacade {
+    root: PathBuf,
+    metadata: StoreMetadata,
+}
+
+impl LocalFacade {
+    /// Constructor with root exists logic
+    ///
+    /// This constructor returns the LocalFacade struct if `root` is an existing directory, and an error if not, so a mistyped root is caught here rather than on the first write.
+    /// The facade's id is `root` as given, and its name is the root directory's own name, EG `store` for `/tmp/store`.
+    pub async fn new(root: impl Into<PathBuf>, description: &str) -> Result<Self, Box<dyn Error>> {
+        let root = root.into();
+        if !tokio::fs::metadata(&root).await?.is_dir() {
+            return Err(format!("{} is not a directory", root.display()).into());
+        }
+
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(LocalFacade {
+            metadata: StoreMetadata {
+                id: DataStoreId::Local(root.clone()),
+                name,
+                description: description.to_string(),
+            },
+            root,
+        })
+    }
+
+    /// Returns the directory objects are stored beneath
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Maps a key onto its path beneath the root
+    ///
+    /// Keys which would resolve outside the root, EG `../escape.txt` or `/etc/passwd`, are refused with [`StorageError::InvalidKey`], as are keys with empty segments.
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        let invalid = |reason: &str| StorageError::InvalidKey {
+            key: key.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let mut path = self.root.clone();
+        for segment in key.split('/') {
+            match segment {
+                "" => return Err(invalid("keys can't have empty segments")),
+                "." | ".." => return Err(invalid("keys can't have relative segments")),
+                _ if segment.contains('\\') || Path::new(segment).has_root() => {
+                    return Err(invalid("segments can't contain path separators"));
+                }
+                _ => path.push(segment),
+            }
+        }
+        Ok(path)
+    }
+
+    /// Creates the directories a file at `path` needs, as S3 has no directories to create first
+    async fn create_parents(path: &Path) -> Result<(), StorageError> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        Ok(())
+    }
+
+    /// Copies `reader` into a temporary file in the same directory as `path`, then renames it over `path`
+    ///
+    /// The rename is atomic as both files are on the same filesystem, and the temporary file is synced first so the rename never exposes bytes still in flight. On failure the temporary file is removed, leaving any existing file at `path` untouched.
+    /// The temporary file is named `.{name}.{uuid}.tmp`, which listings skip with [`is_temp_name`], so a write in progress or a temporary file orphaned by a crash never shows up as an object.
+    async fn write_atomic<R>(path: &Path, mut reader: R) -> std::io::Result<()>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let temp = path.with_file_name(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4()));
+
+        let written = async {
+            let mut file = tokio::fs::File::create(&temp).await?;
+            tokio::io::copy(&mut reader, &mut file).await?;
+            file.sync_all().await?;
+            tokio::fs::rename(&temp, path).await
+        }
+        .await;
+        if written.is_err() {
+            let _cleanup = tokio::fs::remove_file(&temp).await;
+        }
+
+        written
+    }
+
+    /// Walks every file whose key starts with `prefix`, returning each key with its metadata in lexicographical order
+    ///
+    /// Prefixes match literally, as in a bucket, so `logs` also matches `logs-archive/...`. Only the directory holding the prefix's last segment is walked, rather than the whole store.
+    /// Symbolic links and files whose names aren't valid UTF-8 are skipped, as neither can be represented as a key, and so are the temporary files of writes in progress.
+    async fn walk(&self, prefix: &str) -> Result<Vec<(String, std::fs::Metadata)>, StorageError> {
+        let base = match prefix.rfind('/') {
+            Some(index) => &prefix[..=index],
+            None => "",
+        };
+        let start = match base.strip_suffix('/') {
+            Some(dir) => self.path(dir)?,
+            None => self.root.clone(),
+        };
+
+        let mut files = Vec::new();
+        let mut pending = vec![(start, base.to_string())];
+        while let Some((dir, dir_key)) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                // A prefix naming a directory which doesn't exist lists nothing, as a bucket would
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let Ok(name) = entry.file_name().into_string() else {
+                    continue;
+                };
+                if is_temp_name(&name) {
+                    continue;
+                }
+                let key = format!("{}{}", dir_key, name);
+                let file_type = entry.file_type().await?;
+
+                if file_type.is_dir() {
+                    // Descends only into directories which could hold keys matching the prefix
+                    let dir_prefix = format!("{}/", key);
+                    if dir_prefix.starts_with(prefix) || prefix.starts_with(&dir_prefix) {
+                        pending.push((entry.path(), dir_prefix));
+                    }
+                } else if file_type.is_file() && key.starts_with(prefix) {
+                    files.push((key, entry.metadata().await?));
+                }
+            }
+        }
+
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(files)
+    }
+}
+
+/// Checks whether a file name is one [`LocalFacade::write_atomic`] gives its temporary files, `.{name}.{uuid}.tmp`
+fn is_temp_name(name: &str) -> bool {
+    name.strip_prefix('.')
+        .and_then(|name| name.strip_suffix(".tmp"))
+        .and_then(|name| name.rsplit_once('.'))
+        .is_some_and(|(_, id)| uuid::Uuid::try_parse(id).is_ok())
+}
+
+/// Maps an IO error on a key's file to [`StorageError::NotFound`], [`StorageError::AccessDenied`] or [`StorageError::AlreadyExists`] where it's one of those, wrapping any other error as [`StorageError::Backend`]
+fn file_error(key: &str, error: std::io::Error) -> StorageError {
+    match error.kind() {
+        ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+        ErrorKind::PermissionDenied => StorageError::AccessDenied(key.to_string()),
+        ErrorKind::AlreadyExists => StorageError::AlreadyExists(key.to_string()),
+        _ => StorageError::Backend(Box::new(error)),
+    }
+}
+
+impl StorageFacade for LocalFacade {
+    /// Reads binary data from a file beneath the root
+    ///
+    /// # Remarks
+    /// As with S3, the whole file is read into memory, and `decrypt` is applied to the bytes before they are returned to the calling layer if given.
+    async fn read_data<F>(&self, path: &str, decrypt: Option<F>) -> Result<Vec<u8>, StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let bytes = tokio::fs::read(self.path(path)?)
+            .await
+            .map_err(|e| file_error(path, e))?;
+
+        if let Some(decrypt_fn) = decrypt {
+            return decrypt_fn(&bytes).map_err(StorageError::Encryption);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Opens a file beneath the root for streaming, returning its bytes as stored
+    async fn read_stream(&self, path: &str) -> Result<DataReader, StorageError> {
+        let file = tokio::fs::File::open(self.path(path)?)
+            .await
+            .map_err(|e| file_error(path, e))?;
+        Ok(Box::pin(file))
+    }
+
+    /// Reads a byte range of a file beneath the root, seeking to `start` rather than reading what comes before it
+    async fn read_range(
+        &self,
+        path: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<Vec<u8>, StorageError> {
+        let key = path;
+        let path = self.path(path)?;
+        if check_range(start, end)? {
+            return Ok(Vec::new());
+        }
+
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| file_error(key, e))?;
+        let len = file.metadata().await?.len();
+        if start >= len {
+            return Err(StorageError::InvalidRange(format!(
+                "range start {} is past the end of {}, which is {} bytes",
+                start,
+                path.display(),
+                len
+            )));
+        }
+
+        file.seek(SeekFrom::Start(start)).await?;
+        let mut data = Vec::new();
+        match end {
+            Some(end) => file.take(end - start).read_to_end(&mut data).await?,
+            None => file.read_to_end(&mut data).await?,
+        };
+        Ok(data)
+    }
+
+    /// Writes a byte-slice to a file beneath the root, creating any directories it needs
+    ///
+    /// `encrypt` is applied before anything touches the disk, so a failed encryption leaves any existing file as it was. Existing files are overwritten, as objects are in a bucket.
+    /// The bytes are written to a temporary file beside the destination and renamed over it, so readers see the old file or the new one and never a partial write, matching S3's all or nothing puts.
+    async fn write_data<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let key = path;
+        let path = self.path(path)?;
+        let data = match encrypt {
+            Some(encrypt_fn) => encrypt_fn(data).map_err(StorageError::Encryption)?,
+            None => data.to_vec(),
+        };
+
+        Self::create_parents(&path).await?;
+        Self::write_atomic(&path, data.as_slice())
+            .await
+            .map_err(|e| file_error(key, e))?;
+
+        Ok(())
+    }
+
+    /// Writes a byte-slice to a new file beneath the root, opening it with `create_new` so the filesystem refuses it if the file already exists
+    ///
+    /// If writing fails after the file is created, the partial file is removed again, so a failed write doesn't block a retry.
+    async fn write_data_if_absent<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let key = path;
+        let path = self.path(path)?;
+        let data = match encrypt {
+            Some(encrypt_fn) => encrypt_fn(data).map_err(StorageError::Encryption)?,
+            None => data.to_vec(),
+        };
+
+        Self::create_parents(&path).await?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+            .map_err(|e| file_error(key, e))?;
+        if let Err(e) = async {
+            file.write_all(&data).await?;
+            file.flush().await
+        }
+        .await
+        {
+            let _cleanup = tokio::fs::remove_file(&path).await;
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    /// Opens the file in append mode, so the bytes are added in place without reading what's already there
+    async fn append(&self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+        let key = path;
+        let path = self.path(path)?;
+
+        Self::create_parents(&path).await?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(|e| file_error(key, e))?;
+        file.write_all(data).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+
+    /// Copies everything a reader produces into a file beneath the root, creating any directories it needs
+    ///
+    /// As with [`StorageFacade::write_data`], the bytes go to a temporary file which is renamed over the destination once the reader is exhausted, so a stream that fails part way leaves any existing file as it was rather than half written.
+    async fn write_stream<R>(&self, path: &str, mut reader: R) -> Result<(), StorageError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let key = path;
+        let path = self.path(path)?;
+
+        Self::create_parents(&path).await?;
+        Self::write_atomic(&path, &mut reader)
+            .await
+            .map_err(|e| file_error(key, e))?;
+
+        Ok(())
+    }
+
+    /// Lists every file whose key starts with a prefix, at all depths, in lexicographical order
+    ///
+    /// Keys are returned relative to the root with `/` separators, matching the flat listing S3 returns for the same prefix.
+    async fn list_objects(&self, dir_path: &str) -> Result<Vec<String>, StorageError> {
+        Ok(self
+            .walk(dir_path)
+            .await?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect())
+    }
+
+    /// Lists files with the size and modified time already read while walking the directory tree
+    ///
+    /// Files have no ETag or storage class, so those are left as `None`.
+    async fn list_objects_detailed(
+        &self,
+        dir_path: &str,
+    ) -> Result<Vec<ObjectEntry>, StorageError> {
+        Ok(self
+            .walk(dir_path)
+            .await?
+            .into_iter()
+            .map(|(key, metadata)| ObjectEntry {
+                key,
+                size: metadata.len(),
+                last_modified: metadata.modified().ok(),
+                etag: None,
+                storage_class: None,
+            })
+            .collect())
+    }
+
+    /// Lists a single level of a directory by reading it, rather than walking the tree beneath it
+    ///
+    /// Symbolic links, entries whose names aren't valid UTF-8 and the temporary files of writes in progress are skipped, as they are by [`StorageFacade::list_objects`].
+    async fn list_directory(&self, dir_path: &str) -> Result<DirectoryListing, StorageError> {
+        let prefix = as_directory(dir_path);
+        let dir = match prefix.strip_suffix('/') {
+            Some(dir) => self.path(dir)?,
+            None => self.root.clone(),
+        };
+
+        let mut listing = DirectoryListing::default();
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(listing),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if is_temp_name(&name) {
+                continue;
+            }
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                listing.directories.push(format!("{}{}/", prefix, name));
+            } else if file_type.is_file() {
+                listing.files.push(format!("{}{}", prefix, name));
+            }
+        }
+
+        listing.files.sort();
+        listing.directories.sort();
+        Ok(listing)
+    }
+
+    /// Lists the single version of each file whose key starts with a path, as `{key}?versionId=null`
+    ///
+    /// The local filesystem keeps no versions, so this matches what S3 returns for a bucket without versioning enabled.
+    async fn list_object_versions(&self, file_path: &str) -> Result<Vec<String>, StorageError> {
+        Ok(self
+            .list_objects(file_path)
+            .await?
+            .into_iter()
+            .map(|key| format!("{}?versionId=null", key))
+            .collect())
+    }
+
+    /// Deletes a file beneath the root, succeeding if it's already gone as S3 does
+    ///
+    /// A directory isn't a file, so deleting one's path succeeds without touching it, as deleting a missing key does.
+    async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
+        let file = self.path(path)?;
+        let Err(error) = tokio::fs::remove_file(&file).await else {
+            return Ok(());
+        };
+        let is_dir = tokio::fs::symlink_metadata(&file)
+            .await
+            .is_ok_and(|m| m.is_dir());
+        if error.kind() == ErrorKind::NotFound || is_dir {
+            return Ok(());
+        }
+        Err(file_error(path, error))
+    }
+
+    /// Walks the directory until it finds a file, so a large directory is answered without reading all of it
+    ///
+    /// Directories left empty after their files were deleted are reported as `false`, as S3 would report the same prefix. Symbolic links, entries whose names aren't valid UTF-8 and the temporary files of writes in progress don't count, as they are never listed.
+    async fn prefix_exists(&self, dir_path: &str) -> Result<bool, StorageError> {
+        let prefix = as_directory(dir_path);
+        let mut pending = vec![match prefix.strip_suffix('/') {
+            Some(dir) => self.path(dir)?,
+            None => self.root.clone(),
+        }];
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                // A missing directory holds nothing, and neither does a path naming a file
+                Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) => {
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                if entry.file_name().to_str().is_none_or(is_temp_name) {
+                    continue;
+                }
+                let file_type = entry.file_type().await?;
+                if file_type.is_dir() {
+                    pending.push(entry.path());
+                } else if file_type.is_file() {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Removes the directory and everything in it with `remove_dir_all`
+    ///
+    /// A path naming a file rather than a directory holds nothing to delete, as with S3, so the file is left alone.
+    async fn delete_prefix(&self, dir_path: &str) -> Result<(), StorageError> {
+        let prefix = directory_prefix(dir_path)?;
+        let path = self.path(prefix.trim_end_matches('/'))?;
+        match tokio::fs::symlink_metadata(&path).await {
+            Ok(metadata) if metadata.is_dir() => Ok(tokio::fs::remove_dir_all(&path).await?),
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Moves a file with a rename, so the move is atomic when both paths are on the same filesystem
+    ///
+    /// Moving a file onto its own path does nothing. A source which isn't a regular file, EG a directory, fails with [`StorageError::NotFound`], as renaming it would move everything beneath it.
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        let key = from;
+        let from = self.path(from)?;
+        let to = self.path(to)?;
+        let metadata = tokio::fs::symlink_metadata(&from)
+            .await
+            .map_err(|e| file_error(key, e))?;
+        if !metadata.is_file() {
+            return Err(StorageError::NotFound(key.to_string()));
+        }
+        if from == to {
+            return Ok(());
+        }
+
+        Self::create_parents(&to).await?;
+        tokio::fs::rename(from, to)
+            .await
+            .map_err(|e| file_error(key, e))?;
+
+        Ok(())
+    }
+
+    /// Copies a file, doing nothing when copying a file onto its own path, as opening the destination would truncate the source
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        let key = from;
+        let from = self.path(from)?;
+        let to = self.path(to)?;
+        if from == to {
+            return Ok(());
+        }
+
+        Self::create_parents(&to).await?;
+        tokio::fs::copy(from, to)
+            .await
+            .map_err(|e| file_error(key, e))?;
+
+        Ok(())
+    }
+
+    /// Returns a file's size and modified time from the filesystem, leaving the ETag and storage class as `None`
+    async fn get_file_metadata(&self, path: &str) -> Result<ObjectMetadata, StorageError> {
+        let key = path;
+        let path = self.path(path)?;
+        let metadata = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| file_error(key, e))?;
+        if !metadata.is_file() {
+            // A directory holds files rather than being one, as a prefix in a bucket isn't an object
+            return Err(StorageError::NotFound(key.to_string()));
+        }
+
+        Ok(ObjectMetadata {
+            size: metadata.len(),
+            last_modified: metadata.modified().ok(),
+            etag: None,
+            storage_class: None,
+            content_type: None,
+            user_metadata: HashMap::new(),
+        })
+    }
+
+    /// Checks for a file at the path, reporting a directory as `false` as S3 reports a prefix, see [`StorageFacade::prefix_exists`]
+    async fn file_exists(&self, path: &str) -> Result<bool, StorageError> {
+        match tokio::fs::metadata(self.path(path)?).await {
+            Ok(metadata) => Ok(metadata.is_file()),
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) => {
+                Ok(false)
+            }
+            Err(e) => Err(file_error(path, e)),
+        }
+    }
+
+    /// Hashes a sorted manifest of every key under a prefix, with its size and modified time, into a SHA-256 hex digest
+    ///
+    /// # Remarks
+    /// Files have no ETag, so the modified time stands in as the version tag, and rewriting a file with identical content changes the fingerprint. Keys are hashed relative to the prefix, as S3 does.
+    async fn prefix_fingerprint(&self, prefix: &str) -> Result<String, StorageError> {
+        let mut hasher = Sha256::new();
+        for (key, metadata) in self.walk(prefix).await? {
+            let relative = key.strip_prefix(prefix).unwrap_or(&key);
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .unwrap_or_default();
+            // Lengths are included so no two manifests can serialise to the same bytes
+            hasher.update((relative.len() as u64).to_be_bytes());
+            hasher.update(relative.as_bytes());
+            hasher.update(metadata.len().to_be_bytes());
+            hasher.update(modified.as_nanos().to_be_bytes());
+            hasher.update(b"\n");
+        }
+
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect())
+    }
+
+    /// Checks the root is still a directory, and that a file can be created and removed in it
+    ///
+    /// The probe file is named `.health-check-` followed by a UUID, and is removed straight away, though a listing running at the same moment may catch it.
+    async fn health_check(&self) -> Result<(), StorageError> {
+        let root = self.root.display().to_string();
+        let check = |e: std::io::Error| match e.kind() {
+            ErrorKind::NotFound => StorageError::NotFound(root.clone()),
+            ErrorKind::PermissionDenied => StorageError::AccessDenied(root.clone()),
+            _ => StorageError::Backend(Box::new(e)),
+        };
+
+        if !tokio::fs::metadata(&self.root)
+            .await
+            .map_err(check)?
+            .is_dir()
+        {
+            return Err(StorageError::Backend(
+                format!("{} is not a directory", root).into(),
+            ));
+        }
+
+        let probe = self
+            .root
+            .join(format!(".health-check-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&probe, b"").await.map_err(check)?;
+        tokio::fs::remove_file(&probe).await.map_err(check)
+    }
+
+    fn metadata(&self) -> &StoreMetadata {
+        &self.metadata
+    }
+
+    /// Reports server side copies, as the filesystem copies files without them passing through the facade, and range reads
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            server_side_copy: true,
+            range_reads: true,
+            ..Capabilities::default()
+        }
+    }
+}
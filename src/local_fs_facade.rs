@@ -1,7 +1,7 @@
 // Provides abstractions for calling layers to interface with AWS s3
 // 
 // More to follow
-use crate::storage_facade::{StorageFacade, StoreMetadata};
+use crate::storage_facade::{ObjectMetadata, StorageFacade, StoreMetadata};
 use aws_sdk_s3 as s3;
 
 /// Contains the client and metadata as fields
@@ -43,7 +43,7 @@ impl StorageFacade for S3Facade {
         todo!()
     }
 
-    async fn get_file_metadata(&self, path: &str) -> Result<std::fs::Metadata, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_file_metadata(&self, path: &str) -> Result<ObjectMetadata, Box<dyn std::error::Error + Send + Sync>> {
         todo!()
     }
 
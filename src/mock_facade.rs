@@ -0,0 +1,282 @@
+// Provides a facade which keeps objects in memory, for testing code that's generic over StorageFacade
+//
+// Only built with the `testing` feature, so downstream crates can enable it as a dev dependency and inject a fake store into their own tests without AWS credentials or a scratch directory.
+// Behaviour follows the S3 and local facades wherever it's observable, EG missing files are reported as StorageError::NotFound and deleting a missing file succeeds, so tests against the mock hold against the real backends.
+//
+// Nothing is persisted, and the contents are gone once the last clone of the facade is dropped.
+use crate::storage_facade::{
+    Capabilities, DataReader, DataStoreId, ObjectMetadata, StorageError, StorageFacade,
+    StoreMetadata, check_range, directory_prefix,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Keeps objects in a map shared between clones, so one store can be handed to several tasks
+///
+/// Cloning is cheap, and every clone reads and writes the same objects.
+#[derive(Debug, Clone)]
+pub struct MockFacade {
+    objects: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    metadata: StoreMetadata,
+}
+
+impl MockFacade {
+    /// Creates an empty store
+    ///
+    /// There's no backend specific ID for an in-memory store, so the facade's id is `name` as a local path.
+    pub fn new(name: &str, description: &str) -> Self {
+        MockFacade {
+            objects: Arc::new(Mutex::new(HashMap::new())),
+            metadata: StoreMetadata {
+                id: DataStoreId::Local(PathBuf::from(name)),
+                name: name.to_string(),
+                description: description.to_string(),
+            },
+        }
+    }
+
+    /// Stores an object before the facade is used, EG to seed the data a test expects to find
+    pub fn with_object(self, path: &str, data: &[u8]) -> Self {
+        self.objects().insert(path.to_string(), data.to_vec());
+        self
+    }
+
+    /// Returns a copy of every stored object, so tests can assert on the store's contents directly
+    pub fn snapshot(&self) -> HashMap<String, Vec<u8>> {
+        self.objects().clone()
+    }
+
+    /// Locks the objects, carrying on past a poisoned lock as a panicking test shouldn't fail every other test sharing the store
+    fn objects(&self) -> MutexGuard<'_, HashMap<String, Vec<u8>>> {
+        self.objects
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns a copy of an object's bytes, or [`StorageError::NotFound`] if nothing is stored at the path
+    fn get(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        self.objects()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| StorageError::NotFound(path.to_string()))
+    }
+}
+
+/// Applies an encrypt or decrypt function, reporting its failure as [`StorageError::Encryption`] as the other facades do
+fn transform<F>(data: &[u8], function: Option<F>) -> Result<Vec<u8>, StorageError>
+where
+    F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>,
+{
+    match function {
+        Some(function) => function(data).map_err(StorageError::Encryption),
+        None => Ok(data.to_vec()),
+    }
+}
+
+impl StorageFacade for MockFacade {
+    async fn read_data<F>(&self, path: &str, decrypt: Option<F>) -> Result<Vec<u8>, StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        transform(&self.get(path)?, decrypt)
+    }
+
+    async fn read_stream(&self, path: &str) -> Result<DataReader, StorageError> {
+        Ok(Box::pin(std::io::Cursor::new(self.get(path)?)))
+    }
+
+    async fn read_range(
+        &self,
+        path: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<Vec<u8>, StorageError> {
+        let data = self.get(path)?;
+        if check_range(start, end)? {
+            return Ok(Vec::new());
+        }
+
+        let len = data.len() as u64;
+        if start >= len {
+            return Err(StorageError::InvalidRange(format!(
+                "range start {} is past the end of {}, which is {} bytes",
+                start, path, len
+            )));
+        }
+        let end = end.unwrap_or(len).min(len);
+        Ok(data[start as usize..end as usize].to_vec())
+    }
+
+    async fn write_data<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let data = transform(data, encrypt)?;
+        self.objects().insert(path.to_string(), data);
+        Ok(())
+    }
+
+    /// Checks and writes under a single lock, so of two concurrent writers only one succeeds
+    async fn write_data_if_absent<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let data = transform(data, encrypt)?;
+        match self.objects().entry(path.to_string()) {
+            Entry::Occupied(_) => Err(StorageError::AlreadyExists(path.to_string())),
+            Entry::Vacant(entry) => {
+                entry.insert(data);
+                Ok(())
+            }
+        }
+    }
+
+    /// Appends under the store's lock, so concurrent appends are applied one after another and never conflict
+    async fn append(&self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+        self.objects()
+            .entry(path.to_string())
+            .or_default()
+            .extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Reads the whole stream before storing it, so a reader failing part way leaves any existing object as it was
+    async fn write_stream<R>(&self, path: &str, mut reader: R) -> Result<(), StorageError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        self.objects().insert(path.to_string(), data);
+        Ok(())
+    }
+
+    /// Lists every key starting with `dir_path`, in lexicographical order
+    async fn list_objects(&self, dir_path: &str) -> Result<Vec<String>, StorageError> {
+        let mut keys: Vec<String> = self
+            .objects()
+            .keys()
+            .filter(|key| key.starts_with(dir_path))
+            .cloned()
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Lists each matching key as its only version, as the local facade does, since overwrites replace the previous bytes
+    async fn list_object_versions(&self, file_path: &str) -> Result<Vec<String>, StorageError> {
+        Ok(self
+            .list_objects(file_path)
+            .await?
+            .into_iter()
+            .map(|key| format!("{}?versionId=null", key))
+            .collect())
+    }
+
+    /// Removes the object, succeeding if it's already gone as S3 does
+    async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
+        self.objects().remove(path);
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, dir_path: &str) -> Result<(), StorageError> {
+        let prefix = directory_prefix(dir_path)?;
+        self.objects().retain(|key, _| !key.starts_with(&prefix));
+        Ok(())
+    }
+
+    /// Moves the object under a single lock, so no other task sees it at both paths or at neither
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        let mut objects = self.objects();
+        let data = objects
+            .remove(from)
+            .ok_or_else(|| StorageError::NotFound(from.to_string()))?;
+        objects.insert(to.to_string(), data);
+        Ok(())
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        let data = self.get(from)?;
+        self.objects().insert(to.to_string(), data);
+        Ok(())
+    }
+
+    /// Reports the object's size, with a SHA-256 of its bytes standing in for the ETag
+    ///
+    /// Nothing records when an object was written, so `last_modified` is always `None`.
+    async fn get_file_metadata(&self, path: &str) -> Result<ObjectMetadata, StorageError> {
+        let data = self.get(path)?;
+        Ok(ObjectMetadata {
+            size: data.len() as u64,
+            last_modified: None,
+            etag: Some(hex(&Sha256::digest(&data))),
+            storage_class: None,
+            content_type: None,
+            user_metadata: HashMap::new(),
+        })
+    }
+
+    async fn file_exists(&self, path: &str) -> Result<bool, StorageError> {
+        Ok(self.objects().contains_key(path))
+    }
+
+    /// Hashes each key under the prefix with a digest of its bytes, so rewriting an object with the same size still changes the fingerprint
+    async fn prefix_fingerprint(&self, prefix: &str) -> Result<String, StorageError> {
+        let objects = self.objects();
+        let mut keys: Vec<&String> = objects
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .collect();
+        keys.sort();
+
+        let mut hasher = Sha256::new();
+        for key in keys {
+            let relative = &key[prefix.len()..];
+            // Lengths are included so no two manifests can serialise to the same bytes
+            hasher.update((relative.len() as u64).to_be_bytes());
+            hasher.update(relative.as_bytes());
+            hasher.update(Sha256::digest(&objects[key]));
+            hasher.update(b"\n");
+        }
+
+        Ok(hex(&hasher.finalize()))
+    }
+
+    /// Always healthy, as there's nothing to reach
+    async fn health_check(&self) -> Result<(), StorageError> {
+        Ok(())
+    }
+
+    fn metadata(&self) -> &StoreMetadata {
+        &self.metadata
+    }
+
+    /// Supports range reads and copies without moving bytes through the caller, but keeps no versions, locks or tags
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            server_side_copy: true,
+            range_reads: true,
+            ..Capabilities::default()
+        }
+    }
+}
+
+/// Formats a digest as lowercase hex
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,88 @@
+// Retries whole facade calls which fail for transient reasons
+//
+// The SDK retries each request, and the S3 facade can retry writes and listings itself, but a long stretch of throttling can outlast both.
+// This wraps any facade call so an application can retry on top of them, backing off with full jitter so that many clients failing at once spread their retries out rather than arriving together.
+//
+// Only errors which could succeed on a second try are retried. Anything else, EG a missing object or refused permissions, is returned on the first failure.
+use crate::s3_facade;
+use crate::storage_facade::StorageError;
+use std::error::Error;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Longest wait before the first retry, doubled on each subsequent attempt
+const INITIAL_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Cap on the longest wait between attempts, however many have failed
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Runs an operation, retrying it with exponential backoff and full jitter while it fails with a retryable error
+///
+/// # Arguments
+/// * attempts: Most times the operation is run, including the first. 0 is treated as 1, so the operation always runs at least once.
+/// * op: Starts a fresh run of the operation each time it's called, EG `|| facade.read_data::<NoTransform>("key", None)`.
+///
+/// # Remarks
+/// Before retry `n`, counting from 0, the wait is picked at random between zero and 50ms doubled `n` times, capped at 10 seconds.
+/// Which errors are retried is decided by [`is_retryable`]. The first error that isn't, or the last error once every attempt has failed, is returned as the operation raised it.
+/// Retries stack with the SDK's and the facade's own, EG 3 attempts here around an SDK allowing 4 per request is up to 12 requests, so keep the count low.
+pub async fn with_retry<T, Fut>(attempts: usize, op: impl Fn() -> Fut) -> Result<T, StorageError>
+where
+    Fut: Future<Output = Result<T, StorageError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(e) if attempt + 1 < attempts && is_retryable(&e) => {
+                let wait = backoff(attempt);
+                tracing::warn!(attempt, ?wait, error = %e, "retrying failed operation");
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Decides whether a failed facade call is worth retrying
+///
+/// # Remarks
+/// Retries [`StorageError::TimedOut`], filesystem errors which are usually passing, EG an interrupted call or a reset connection, and S3 errors which are throttling, 5xx responses, timeouts or failed connections.
+/// [`StorageError::Backend`] is judged by the error it wraps, and every other [`StorageError`] variant, EG [`StorageError::NotFound`] or [`StorageError::AccessDenied`], is never retried.
+pub fn is_retryable(error: &StorageError) -> bool {
+    match error {
+        StorageError::TimedOut { .. } => true,
+        StorageError::Backend(e) => is_transient(e.as_ref()),
+        _ => false,
+    }
+}
+
+/// Checks whether an error wrapped in [`StorageError::Backend`] is a filesystem or S3 error which is usually passing
+fn is_transient(error: &(dyn Error + 'static)) -> bool {
+    if let Some(error) = error.downcast_ref::<io::Error>() {
+        return matches!(
+            error.kind(),
+            io::ErrorKind::Interrupted
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::WouldBlock
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+        );
+    }
+
+    s3_facade::is_transient(error)
+}
+
+/// Picks a wait at random between zero and the exponential backoff for the attempt
+fn backoff(attempt: usize) -> Duration {
+    let exponent = u32::try_from(attempt).unwrap_or(u32::MAX);
+    let ceiling = INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(exponent))
+        .min(MAX_BACKOFF);
+
+    // Taken from the random leading bits of a v4 UUID, as uuid is already a dependency
+    let fraction = f64::from(Uuid::new_v4().as_fields().0) / f64::from(u32::MAX);
+    ceiling.mul_f64(fraction)
+}
@@ -11,73 +11,4901 @@
 // The idea being that if we use an S3 specific feature, it should be part of a process that can be considered agnostic to all structs which implement the StorageFacade trait.
 // for example, methods checking storage class of a file, and potentially triggering a move from deep archive to instant access, should be called as part of a process within a public method.
 // This way, callers don't need to care about or work with the platform specific features of each data store, but can implement high level instructions which will take advantage of them if required.
-use crate::storage_facade::{DataStoreId, StorageFacade, StoreMetadata};
+use crate::storage_facade::{
+    Capabilities, CaseMode, Clock, DEFAULT_CONCURRENCY, DataReader, DataStoreId, DirectoryListing,
+    KeyLimits, KeyPolicy, ObjectEntry, ObjectMetadata, Pipeline, ReadOutcome, ReadPrecondition,
+    StorageError, StorageFacade, StoreMetadata, SystemClock, WriteOutput, as_directory,
+    check_range, directory_prefix,
+};
 use aws_config as aws;
 use aws_sdk_s3::{
     self as s3,
-    error::SdkError,
+    config::{
+        Intercept, SharedInterceptor,
+        retry::{RetryConfig, RetryMode},
+        timeout::TimeoutConfig,
+    },
+    error::{DisplayErrorContext, ProvideErrorMetadata, SdkError},
     operation::{
+        copy_object::CopyObjectError,
+        delete_object::DeleteObjectError,
+        get_object::{GetObjectError, GetObjectOutput},
         head_object::{HeadObjectError, HeadObjectOutput},
         list_objects_v2::{ListObjectsV2Error, ListObjectsV2Output},
+        put_object::PutObjectError,
+    },
+    presigning::PresigningConfig,
+    primitives::{ByteStream, ByteStreamError, DateTime, DateTimeFormat},
+    types::{
+        self as s3_types, BucketVersioningStatus, ChecksumMode, ChecksumType,
+        CompletedMultipartUpload, CompletedPart, Delete, GlacierJobParameters, MetadataDirective,
+        Object, ObjectIdentifier, ObjectLockLegalHold, ObjectLockLegalHoldStatus,
+        ObjectLockRetention, ObjectLockRetentionMode, RestoreRequest, StorageClass,
+        TaggingDirective, Tier,
     },
-    primitives::ByteStream,
 };
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use bytes::Bytes;
+use futures::Stream;
+use futures::channel::oneshot;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use globset::GlobBuilder;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
+use std::future::Future;
+use std::io;
+use std::ops::{ControlFlow, Range};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio_util::io::StreamReader;
+
+/// Stand-in closure type for calls which don't parse in an encrypt or decrypt function
+type NoTransform = fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+
+/// Default size of each part in a streamed multipart upload, see [`S3Facade::with_part_size`]
+const MULTIPART_PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// The most keys S3 accepts in a single DeleteObjects request
+const DELETE_BATCH_SIZE: usize = 1000;
+
+/// Largest object a facade reads into memory unless told otherwise, see [`S3Facade::with_max_read_bytes`]
+pub const DEFAULT_MAX_READ_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Prefix soft deleted objects are moved under, see [`S3Facade::soft_delete`]
+const TRASH_PREFIX: &str = ".trash/";
+
+/// Tag holding the RFC 3339 time after which a soft deleted object may be purged
+const PURGE_AFTER_TAG: &str = "fallible-purge-after";
+
+/// The smallest part S3 accepts for any but the last part of a multipart upload
+const MIN_MULTIPART_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// The longest a presigned URL can last, as S3 refuses SigV4 signatures valid for more than seven days
+const MAX_PRESIGNED_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How many days a copy restored from an archive class stays readable, see [`S3Facade::ensure_retrievable`]
+const RESTORE_DAYS: i32 = 7;
+
+/// The largest part S3 accepts in a multipart upload
+const MAX_MULTIPART_PART_SIZE: usize = 5 * 1024 * 1024 * 1024;
+
+/// The most tags S3 allows on a single object
+const MAX_OBJECT_TAGS: usize = 10;
+
+/// The longest tag key S3 accepts, in Unicode characters
+const MAX_TAG_KEY_CHARS: usize = 128;
+
+/// The longest tag value S3 accepts, in Unicode characters
+const MAX_TAG_VALUE_CHARS: usize = 256;
+
+/// Retries applied to failed writes, see [`S3Facade::with_write_retries`]
+///
+/// Which errors are retried is decided by the facade's retry classifier, see [`S3Facade::with_retry_classifier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteRetries {
+    /// How many times a failed write is retried before the error is returned
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled on each subsequent attempt
+    pub initial_backoff: Duration,
+}
+
+/// Retry and timeout settings for the SDK clients behind a facade, see [`S3Facade::with_client_config`]
+///
+/// These are applied by the SDK to every request, beneath the facade's own [`WriteRetries`] and [`OperationTimeouts`]. The default allows 3 retries in standard mode and 30 seconds per operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientConfig {
+    /// Attempts per request including the first, so 1 turns the SDK's retries off
+    pub max_attempts: u32,
+    /// `Standard` retries with exponential backoff, while `Adaptive` also slows the client down for a while once S3 throttles it
+    pub retry_mode: RetryMode,
+    /// Limit on a whole operation, including its retries and transferring the body, or `None` for no limit
+    pub operation_timeout: Option<Duration>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            max_attempts: 4,
+            retry_mode: RetryMode::Standard,
+            operation_timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+/// Builder for an [`S3Facade`], for when the bucket needs more than a name to reach, see [`S3Facade::builder`]
+///
+/// Anything not set keeps the default [`S3Facade::new`] uses, with credentials and other client settings loaded from the environment as usual. Settings which don't affect how the bucket is reached, EG [`S3Facade::with_pipeline`], are still applied to the built facade with its `with_*` methods.
+#[derive(Debug, Clone)]
+pub struct S3FacadeBuilder {
+    name: String,
+    description: String,
+    region: Option<String>,
+    endpoint_url: Option<String>,
+    force_path_style: bool,
+    operation_timeout: Option<Duration>,
+    max_read_bytes: u64,
+    skip_bucket_check: bool,
+    sdk_config: Option<aws::SdkConfig>,
+}
+
+impl S3FacadeBuilder {
+    /// Sets the region requests are signed for and sent to, replacing the one configured in the environment
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_string());
+        self
+    }
+
+    /// Sends every request to `endpoint_url` rather than AWS, EG `http://localhost:9000` for a local MinIO server
+    ///
+    /// Most S3-compatible stores still need a region for signing even if they ignore it, see [`S3FacadeBuilder::region`].
+    pub fn endpoint_url(mut self, endpoint_url: &str) -> Self {
+        self.endpoint_url = Some(endpoint_url.to_string());
+        self
+    }
+
+    /// Addresses buckets as `{endpoint}/{bucket}/{key}` rather than `{bucket}.{endpoint}/{key}`, which most self-hosted gateways require as they don't have a DNS entry per bucket
+    pub fn force_path_style(mut self, enabled: bool) -> Self {
+        self.force_path_style = enabled;
+        self
+    }
+
+    /// Sets the SDK's limit on a whole operation, including its retries and transferring the body, see [`ClientConfig::operation_timeout`]
+    ///
+    /// Other timeouts configured in the environment are kept. Use [`S3Facade::with_client_config`] on the built facade to change the retries as well.
+    pub fn operation_timeout(mut self, limit: Duration) -> Self {
+        self.operation_timeout = Some(limit);
+        self
+    }
+
+    /// Sets the largest object the facade will read into memory, see [`S3Facade::with_max_read_bytes`]
+    pub fn max_read_bytes(mut self, limit: u64) -> Self {
+        self.max_read_bytes = limit;
+        self
+    }
+
+    /// Skips the head_bucket() existence check, so building sends no requests
+    ///
+    /// As with [`S3Facade::from_client`], a mistyped name is then only caught on the first request, and the ARN is built from the name.
+    pub fn skip_bucket_check(mut self, skip: bool) -> Self {
+        self.skip_bucket_check = skip;
+        self
+    }
+
+    /// Builds the client from an already loaded configuration, rather than loading the environment's again, see [`S3Facade::new_with_shared_config`]
+    ///
+    /// The builder's other settings are applied on top of `config`, which is left as it was, so one configuration can be shared by facades with different settings.
+    pub fn sdk_config(mut self, config: &aws::SdkConfig) -> Self {
+        self.sdk_config = Some(config.clone());
+        self
+    }
+
+    /// Loads the environment's configuration, unless one was given with [`S3FacadeBuilder::sdk_config`], applies the builder's settings to it, and checks the bucket exists unless told not to
+    ///
+    /// See [`S3Facade::new`] for the names accepted and the targets the existence check is always skipped for.
+    pub async fn build(self) -> Result<S3Facade, Box<dyn Error>> {
+        let name = self.name.as_str();
+        let description = self.description.as_str();
+
+        let config = match self.sdk_config {
+            Some(config) => config,
+            None => aws::load_defaults(aws::BehaviorVersion::v2026_01_12()).await,
+        };
+        let mut builder =
+            s3::config::Builder::from(&config).force_path_style(self.force_path_style);
+        if let Some(endpoint_url) = self.endpoint_url {
+            builder = builder.endpoint_url(endpoint_url);
+        }
+        if let Some(region) = self.region {
+            builder = builder.region(s3::config::Region::new(region));
+        }
+        if let Some(limit) = self.operation_timeout {
+            let timeout = config
+                .timeout_config()
+                .map(TimeoutConfig::to_builder)
+                .unwrap_or_default()
+                .operation_timeout(limit);
+            builder = builder.timeout_config(timeout.build());
+        }
+
+        if is_directory_bucket_name(name) {
+            let client =
+                s3::Client::from_conf(builder.disable_s3_express_session_auth(false).build());
+            return Ok(S3Facade::from_client(client, name, description)
+                .with_max_read_bytes(self.max_read_bytes));
+        }
+
+        let client = s3::Client::from_conf(builder.build());
+
+        if self.skip_bucket_check
+            || is_object_lambda_arn(name)
+            || is_multi_region_access_point_arn(name)
+        {
+            return Ok(S3Facade::from_client(client, name, description)
+                .with_max_read_bytes(self.max_read_bytes));
+        }
+
+        let request = client.head_bucket().bucket(name).send().await;
+
+        match request {
+            Err(e) => {
+                tracing::error!(bucket = name, error = %DisplayErrorContext(&e), "bucket existence check failed");
+                Err(e.into())
+            }
+            Ok(result) => {
+                tracing::debug!(bucket = name, "bucket existence check succeeded");
+                let arn = result
+                    .bucket_arn()
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("arn:aws:s3:::{}", name));
+
+                Ok(S3Facade::with_client(client, arn, name, description)
+                    .with_max_read_bytes(self.max_read_bytes))
+            }
+        }
+    }
+}
+
+/// Pacing applied to paginated listings, for buckets sitting behind request rate limits
+///
+/// Listing a huge prefix back to back can trip S3's per-prefix request limits, at which point a page comes back as `SlowDown` or a 503.
+/// With this set, a listing pauses between pages and retries throttled pages with exponential backoff, rather than failing partway through.
+/// Which errors are retried can be changed with [`S3Facade::with_retry_classifier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListingBackoff {
+    /// Pause between consecutive page requests
+    pub page_delay: Duration,
+    /// How many times a throttled page request is retried before the error is returned
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled on each subsequent attempt
+    pub initial_backoff: Duration,
+}
+
+impl Default for ListingBackoff {
+    fn default() -> Self {
+        ListingBackoff {
+            page_delay: Duration::ZERO,
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Optional S3 specific behaviour for [`S3Facade::copy_file_with_options`]
+///
+/// The default copies the object exactly as [`StorageFacade::copy_file`] does, keeping the source's content type, user metadata, storage class and encryption.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CopyOptions {
+    /// Whether the destination keeps the source's tags (`Copy`) or takes `tags` instead (`Replace`). S3 copies tags when unset.
+    pub tagging_directive: Option<TaggingDirective>,
+    /// Tags applied to the destination when `tagging_directive` is `Replace`
+    pub tags: HashMap<String, String>,
+    /// Storage class of the destination. The source's class is kept when unset.
+    pub storage_class: Option<StorageClass>,
+    /// Whether the destination keeps the source's content type and user metadata (`Copy`) or takes `content_type` and `metadata` instead (`Replace`). S3 copies them when unset.
+    pub metadata_directive: Option<MetadataDirective>,
+    /// Content type of the destination when `metadata_directive` is `Replace`. S3 falls back to `binary/octet-stream` when unset.
+    pub content_type: Option<String>,
+    /// User metadata of the destination when `metadata_directive` is `Replace`
+    pub metadata: HashMap<String, String>,
+}
+
+/// Optional S3 specific behaviour for [`S3Facade::write_data_with_options`]
+///
+/// The default writes the object exactly as [`StorageFacade::write_data`] does, leaving S3 to serve it as `binary/octet-stream` unless the facade guesses content types, see [`S3Facade::with_content_type_guessing`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WriteOptions {
+    /// Content type the object is served with, EG `application/json`, including through presigned URLs
+    pub content_type: Option<String>,
+    /// User metadata stored alongside the object, sent as `x-amz-meta-*` headers. Keys are lowercased by S3.
+    pub metadata: HashMap<String, String>,
+    /// Storage class of the object. S3 uses `STANDARD` when unset.
+    pub storage_class: Option<StorageClass>,
+    /// Content encoding the object is served with, EG `gzip`, so HTTP clients reading it through a presigned URL know to decompress it
+    pub content_encoding: Option<String>,
+}
+
+/// Moves objects older than a given age to a storage class, as applied by [`S3Facade::apply_tiering`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TieringRule {
+    /// How long since an object was last modified before the rule applies
+    pub older_than: Duration,
+    /// Storage class matching objects are moved to
+    pub to_class: StorageClass,
+}
+
+/// Outcome of [`S3Facade::apply_tiering`]
+///
+/// # Parameters:
+/// * transitioned: Key paired with the storage class it was moved to, for each object moved.
+/// * unchanged: Number of objects no rule applied to, or already in the class their rule asks for.
+/// * failed: Key paired with the error message for each object that couldn't be moved.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TieringReport {
+    pub transitioned: Vec<(String, StorageClass)>,
+    pub unchanged: usize,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Bucket configuration a service expects to be in place, see [`S3Facade::verify_bucket_policy`]
+///
+/// Each field is `Some(true)` if the setting must be on, `Some(false)` if it must be off, or `None` if the caller doesn't mind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BucketExpectations {
+    /// Object versioning is enabled, rather than suspended or never configured
+    pub versioning: Option<bool>,
+    /// A default server side encryption rule is configured
+    pub default_encryption: Option<bool>,
+    /// All four public access block settings are turned on
+    pub public_access_blocked: Option<bool>,
+}
+
+/// Cross region replication state of an object, from its `x-amz-replication-status` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplicationStatus {
+    /// The object is waiting to be replicated
+    Pending,
+    /// The object has been replicated to every destination
+    Completed,
+    /// Replication failed, and S3 will not retry it
+    Failed,
+    /// The object is itself a replica, written by replication from another bucket
+    Replica,
+}
+
+impl ReplicationStatus {
+    /// Maps the SDK's status, returning `None` for statuses this crate doesn't know about
+    fn from_sdk(status: &s3_types::ReplicationStatus) -> Option<Self> {
+        match status {
+            s3_types::ReplicationStatus::Pending => Some(ReplicationStatus::Pending),
+            s3_types::ReplicationStatus::Complete | s3_types::ReplicationStatus::Completed => {
+                Some(ReplicationStatus::Completed)
+            }
+            s3_types::ReplicationStatus::Failed => Some(ReplicationStatus::Failed),
+            s3_types::ReplicationStatus::Replica => Some(ReplicationStatus::Replica),
+            _ => None,
+        }
+    }
+}
+
+/// Versioning state of a bucket, see [`S3Facade::versioning_status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersioningStatus {
+    /// Overwrites and deletes keep the previous versions
+    Enabled,
+    /// Versioning was enabled and then turned off, so new writes replace the `null` version while older versions are kept
+    Suspended,
+    /// Versioning has never been enabled, so each key only ever has its current bytes
+    Disabled,
+}
+
+/// Outcome of [`S3Facade::upload_dir`]
+///
+/// # Parameters:
+/// * uploaded: Keys written successfully.
+/// * failed: Key, or local path if no key could be derived, paired with the error message for each file that couldn't be uploaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UploadReport {
+    pub uploaded: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Outcome of [`S3Facade::download_prefix`]
+///
+/// # Parameters:
+/// * downloaded: Keys written to the local filesystem, including directory markers.
+/// * skipped: Keys left alone because a local file already existed and overwriting was off.
+/// * failed: Key paired with the error message for each object that couldn't be downloaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DownloadReport {
+    pub downloaded: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Outcome of [`S3Facade::scan_and_quarantine`]
+///
+/// # Parameters:
+/// * scanned: Number of objects read and validated.
+/// * quarantined: Original keys of the objects which failed validation and were moved.
+/// * failed: Key paired with the error message for each object that couldn't be read or moved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanReport {
+    pub scanned: usize,
+    pub quarantined: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Outcome of [`S3Facade::prune`]
+///
+/// # Parameters:
+/// * scanned: Number of objects listed and checked against the predicate.
+/// * deleted: Keys of the objects which matched and were deleted.
+/// * failed: Key paired with the error message for each matching object S3 couldn't delete.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub scanned: usize,
+    pub deleted: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Outcome of [`S3Facade::reorganize`]
+///
+/// # Parameters:
+/// * reorganized: Source key paired with the destination key it was copied to, for each object copied.
+/// * unchanged: Number of objects whose key mapped onto itself, which are left alone.
+/// * failed: Source key paired with the error message for each object that couldn't be copied, or deleted after copying.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReorgReport {
+    pub reorganized: Vec<(String, String)>,
+    pub unchanged: usize,
+    pub failed: Vec<(String, String)>,
+}
+
+/// One page of a listing, as returned by [`S3Facade::list_pages`]
+///
+/// # Parameters:
+/// * keys: The objects on the page, with logical keys, in the order S3 listed them.
+/// * is_truncated: Whether S3 has more pages after this one.
+/// * next_token: The continuation token S3 gave for the next page, `None` on the last page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingPage {
+    pub keys: Vec<ObjectEntry>,
+    pub is_truncated: bool,
+    pub next_token: Option<String>,
+}
+
+/// A multipart upload which has been started but not yet completed or aborted, as reported by [`S3Facade::list_in_progress_uploads`]
+///
+/// # Parameters:
+/// * key: The key the upload will create once completed.
+/// * upload_id: The id S3 assigned the upload, needed to resume or abort it.
+/// * initiated: When the upload was started, if S3 reported it.
+/// * parts_completed: How many parts have been uploaded so far.
+/// * bytes_uploaded: The combined size of those parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartUploadInfo {
+    pub key: String,
+    pub upload_id: String,
+    pub initiated: Option<SystemTime>,
+    pub parts_completed: usize,
+    pub bytes_uploaded: u64,
+}
+
+/// Totals for the objects sharing a top level segment, as reported by [`S3Facade::group_by_top_prefix`]
+///
+/// # Parameters:
+/// * objects: Number of objects in the group.
+/// * bytes: Combined size of those objects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefixStats {
+    pub objects: u64,
+    pub bytes: u64,
+}
+
+/// Monthly storage rates by storage class, for [`S3Facade::estimate_storage_cost`]
+///
+/// Rates are per GB-month, in whatever currency the caller works in, where a GB is 2^30 bytes as AWS bills it.
+/// Classes missing from the table are still totalled in the estimate, but aren't costed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StoragePricing {
+    pub per_gb_month: HashMap<StorageClass, f64>,
+}
+
+/// Bytes held in one storage class, and what they cost per month
+///
+/// # Parameters:
+/// * objects: Number of objects in the class.
+/// * bytes: Combined size of those objects.
+/// * monthly_cost: Estimated cost per month, or `None` if the pricing table has no rate for the class.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClassCost {
+    pub objects: u64,
+    pub bytes: u64,
+    pub monthly_cost: Option<f64>,
+}
+
+/// Outcome of [`S3Facade::estimate_storage_cost`]
+///
+/// # Parameters:
+/// * by_class: Totals for each storage class objects were found in.
+/// * total: Sum of the costed classes' monthly costs. Classes without a rate are left out, so check `by_class` for gaps.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostEstimate {
+    pub by_class: HashMap<StorageClass, ClassCost>,
+    pub total: f64,
+}
+
+/// Time limits for each kind of operation, so slow operations can be allowed more time than quick ones
+///
+/// Each limit covers the whole operation as the caller sees it, including any retries, EG every page of a listing. Leaving a limit as `None` means that kind of operation never times out.
+/// An operation which overruns fails with [`StorageError::TimedOut`].
+///
+/// # Parameters:
+/// * read: Limit for reading an object's body.
+/// * write: Limit for writing an object.
+/// * list: Limit for listing a prefix.
+/// * delete: Limit for deleting an object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationTimeouts {
+    pub read: Option<Duration>,
+    pub write: Option<Duration>,
+    pub list: Option<Duration>,
+    pub delete: Option<Duration>,
+}
+
+/// A lightweight reference to one object, returned by [`S3Facade::object`]
+///
+/// Creating a handle sends no requests. Each method makes its request fresh, so a handle never holds stale data, and can outlive the object it refers to, in which case its methods return errors.
+pub struct ObjectHandle<'a> {
+    facade: &'a S3Facade,
+    path: String,
+}
+
+impl ObjectHandle<'_> {
+    /// Returns the path the handle refers to, as it was given to [`S3Facade::object`]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the size of the object as stored, in bytes
+    pub async fn size(&self) -> Result<u64, StorageError> {
+        Ok(self.metadata().await?.size)
+    }
+
+    /// Returns the object's metadata, without downloading its body
+    pub async fn metadata(&self) -> Result<ObjectMetadata, StorageError> {
+        self.facade.get_file_metadata(&self.path).await
+    }
+
+    /// Reads the whole object through [`StorageFacade::read_data`], so the facade's pipeline is applied
+    pub async fn read(&self) -> Result<Vec<u8>, StorageError> {
+        self.facade.read_data::<NoTransform>(&self.path, None).await
+    }
+
+    /// Reads a byte range of the object as stored, without the facade's pipeline applied
+    ///
+    /// A range running past the end of the object is cut short at the end, while a range starting past it is an error. An empty range returns no bytes without sending a request.
+    pub async fn read_range(&self, range: Range<u64>) -> Result<Vec<u8>, StorageError> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.facade
+            .read_range(&self.path, range.start, Some(range.end))
+            .await
+    }
+
+    /// Deletes the object
+    pub async fn delete(&self) -> Result<(), StorageError> {
+        self.facade.delete_file(&self.path).await
+    }
+}
 
 /// Contains the client and metadata as fields
 pub struct S3Facade {
     client: s3::Client,
+    read_client: s3::Client,
     metadata: StoreMetadata,
+    case_sensitivity: CaseMode,
+    listing_backoff: Option<ListingBackoff>,
+    pipeline: Option<Pipeline>,
+    clock: Arc<dyn Clock>,
+    hashed_partitions: bool,
+    treat_prefix_as_directory: bool,
+    retry_classifier: Option<RetryClassifier>,
+    timeouts: OperationTimeouts,
+    key_policy: KeyPolicy,
+    key_limits: KeyLimits,
+    write_retries: Option<WriteRetries>,
+    coalesce_writes: bool,
+    in_flight_writes: InFlightWrites,
+    directory_bucket: bool,
+    read_rate_limit: Option<Arc<ReadRateLimiter>>,
+    part_size: usize,
+    max_read_bytes: u64,
+    guess_content_type: bool,
+}
+
+/// Paces the object bodies a facade downloads so they never arrive faster than a byte rate, see [`S3Facade::with_read_rate_limit`]
+///
+/// Each chunk is paid for as it arrives, and held back until the rate allows it. An idle facade doesn't save up allowance, so there is no burst above the rate after a quiet spell.
+#[derive(Debug)]
+struct ReadRateLimiter {
+    bytes_per_second: u64,
+    /// When every byte handed out so far will have been paid for
+    paid_until: Mutex<tokio::time::Instant>,
+}
+
+impl ReadRateLimiter {
+    /// Waits until `bytes` more can be handed out without going over the rate
+    async fn take(&self, bytes: usize) {
+        let until = {
+            let mut paid_until = self.paid_until.lock().unwrap();
+            let cost = Duration::from_secs_f64(bytes as f64 / self.bytes_per_second as f64);
+            *paid_until = (*paid_until).max(tokio::time::Instant::now()) + cost;
+            *paid_until
+        };
+        tokio::time::sleep_until(until).await;
+    }
+}
+
+/// Builds the presigning config for a URL lasting `expires_in`, refusing anything longer than S3 allows
+fn presigning_config(expires_in: Duration) -> Result<PresigningConfig, StorageError> {
+    if expires_in > MAX_PRESIGNED_EXPIRY {
+        return Err(format!(
+            "presigned URLs can last at most seven days, not {:?}",
+            expires_in
+        )
+        .into());
+    }
+    PresigningConfig::expires_in(expires_in).map_err(|e| StorageError::Backend(Box::new(e)))
+}
+
+/// Turns an object body into a stream of its chunks, each paced by the facade's read rate limit if it has one
+fn body_chunks(
+    body: ByteStream,
+    limiter: Option<Arc<ReadRateLimiter>>,
+) -> impl Stream<Item = Result<Bytes, StorageError>> + Send + 'static {
+    stream::try_unfold(body, move |mut body| {
+        let limiter = limiter.clone();
+        async move {
+            let chunk = body.try_next().await?;
+            if let (Some(limiter), Some(chunk)) = (&limiter, &chunk) {
+                limiter.take(chunk.len()).await;
+            }
+            Ok::<_, StorageError>(chunk.map(|chunk| (chunk, body)))
+        }
+    })
+}
+
+/// Writes currently uploading, keyed by key and a SHA-256 of the bytes, each with the callers waiting on its result
+type InFlightWrites = Mutex<HashMap<(String, [u8; 32]), Vec<oneshot::Sender<Result<(), String>>>>>;
+
+/// Uploads a coalesced write on behalf of every caller which joined it, see [`S3Facade::with_write_coalescing`]
+///
+/// Dropping it unfinished, EG when the leading caller is cancelled, forgets the write, so the callers waiting on it fail rather than hang.
+struct WriteLeader<'a> {
+    in_flight: &'a InFlightWrites,
+    id: Option<(String, [u8; 32])>,
+}
+
+impl WriteLeader<'_> {
+    /// Forgets the write and hands its result to every caller waiting on it
+    fn finish(mut self, result: &Result<(), StorageError>) {
+        let Some(id) = self.id.take() else {
+            return;
+        };
+        let waiters = self.in_flight.lock().unwrap().remove(&id);
+
+        for waiter in waiters.unwrap_or_default() {
+            let shared = result.as_ref().map(|_| ()).map_err(|e| e.to_string());
+            let _ = waiter.send(shared);
+        }
+    }
+}
+
+impl Drop for WriteLeader<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            self.in_flight.lock().unwrap().remove(&id);
+        }
+    }
+}
+
+/// Checks tags against S3's limits, so a bad set is refused with the limit it breaks rather than an opaque 400 from S3
+fn check_tags(tags: &HashMap<String, String>) -> Result<(), StorageError> {
+    if tags.len() > MAX_OBJECT_TAGS {
+        return Err(format!(
+            "an object can have at most {} tags, not {}",
+            MAX_OBJECT_TAGS,
+            tags.len()
+        )
+        .into());
+    }
+
+    for (key, value) in tags {
+        if key.is_empty() {
+            return Err("tag keys can't be empty".into());
+        }
+        if key.chars().count() > MAX_TAG_KEY_CHARS {
+            return Err(format!(
+                "tag key {} is over the {} character limit",
+                key, MAX_TAG_KEY_CHARS
+            )
+            .into());
+        }
+        if value.chars().count() > MAX_TAG_VALUE_CHARS {
+            return Err(format!(
+                "the value of tag {} is over the {} character limit",
+                key, MAX_TAG_VALUE_CHARS
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes tags as the URL query string S3 expects in tagging headers
+fn encode_tags(tags: &HashMap<String, String>) -> String {
+    tags.iter()
+        .map(|(key, value)| {
+            format!(
+                "{}={}",
+                urlencoding::encode(key),
+                urlencoding::encode(value)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// Builds the `CopySource` for an object, percent-encoding its key so spaces and other special characters survive the header
+///
+/// Slashes separate the key's segments, so they're kept as they are rather than encoded.
+fn copy_source(bucket: &str, key: &str) -> String {
+    let key = key
+        .split('/')
+        .map(|segment| urlencoding::encode(segment))
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("{}/{}", bucket, key)
+}
+
+/// Reads up to one multipart part from a reader, returning fewer bytes only once the reader is exhausted
+async fn read_part(
+    reader: &mut (impl AsyncRead + Unpin),
+    part_size: usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut part = Vec::with_capacity(part_size);
+    reader.take(part_size as u64).read_to_end(&mut part).await?;
+    Ok(part)
+}
+
+/// Computes the ETag S3 gives an object uploaded in parts of `part_size` bytes
+///
+/// # Remarks
+/// A multipart ETag is the MD5 of the concatenated MD5s of each part, followed by a dash and the number of parts, so it depends on the part size as well as the content.
+/// This matches the ETags written by [`S3Facade::write_with_part_size`], quotes included, as long as the bucket doesn't encrypt objects with SSE-KMS, which changes how ETags are computed.
+/// Empty data is counted as a single empty part, as that is how it is uploaded. A `part_size` of zero is treated as one.
+pub fn multipart_etag(data: &[u8], part_size: usize) -> String {
+    let mut digests = Md5::new();
+    let mut parts = 0;
+
+    for part in data.chunks(part_size.max(1)) {
+        digests.update(Md5::digest(part));
+        parts += 1;
+    }
+    if parts == 0 {
+        digests.update(Md5::digest(b""));
+        parts = 1;
+    }
+
+    let digest = digests.finalize();
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("\"{}-{}\"", hex, parts)
+}
+
+/// Whether a facade target is an S3 Object Lambda access point rather than a bucket
+fn is_object_lambda_arn(name: &str) -> bool {
+    name.starts_with("arn:") && name.split(':').nth(2) == Some("s3-object-lambda")
+}
+
+/// Whether a facade target is a Multi-Region Access Point, whose ARNs have no region and name an `.mrap` alias
+fn is_multi_region_access_point_arn(name: &str) -> bool {
+    let parts: Vec<&str> = name.splitn(6, ':').collect();
+
+    matches!(
+        parts.as_slice(),
+        ["arn", _, "s3", "", _, resource]
+            if resource.starts_with("accesspoint/") && resource.ends_with(".mrap")
+    )
+}
+
+/// Guesses a MIME type from the extension of a key, for [`S3Facade::with_content_type_guessing`]
+fn guess_content_type(key: &str) -> Option<&'static str> {
+    let name = key.rsplit('/').next().unwrap_or(key);
+    let (stem, extension) = name.rsplit_once('.')?;
+    if stem.is_empty() {
+        return None;
+    }
+
+    let content_type = match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "wasm" => "application/wasm",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        _ => return None,
+    };
+    Some(content_type)
+}
+
+/// Whether a facade target is an S3 Express One Zone directory bucket, whose names take the form `{base}--{zone id}--x-s3`
+fn is_directory_bucket_name(name: &str) -> bool {
+    name.strip_suffix("--x-s3")
+        .and_then(|rest| rest.rsplit_once("--"))
+        .is_some_and(|(base, zone)| !base.is_empty() && !zone.is_empty())
+}
+
+/// Recovers a logical key from a partitioned one, returning `None` for keys outside any partition
+fn strip_partition(stored_key: &str) -> Option<&str> {
+    let mut segments = stored_key.splitn(3, '/');
+    let is_partition =
+        |segment: &str| segment.len() == 2 && segment.bytes().all(|b| b.is_ascii_hexdigit());
+
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some(first), Some(second), Some(logical))
+            if is_partition(first) && is_partition(second) =>
+        {
+            Some(logical)
+        }
+        _ => None,
+    }
+}
+
+/// Converts an object from a listing response into the backend agnostic entry
+fn object_entry(object: &Object) -> ObjectEntry {
+    ObjectEntry {
+        key: object.key().unwrap_or_default().to_string(),
+        size: object.size().unwrap_or_default() as u64,
+        last_modified: object
+            .last_modified()
+            .and_then(|t| SystemTime::try_from(*t).ok()),
+        etag: object.e_tag().map(String::from),
+        storage_class: object.storage_class().map(|c| c.as_str().to_string()),
+    }
+}
+
+/// Recursively lists every file beneath a directory
+async fn walk_files(root: &Path) -> Result<Vec<PathBuf>, StorageError> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                pending.push(entry.path());
+            } else {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Joins the components of a relative path with forward slashes, returning `None` if any aren't valid unicode
+fn relative_key(relative: &Path) -> Option<String> {
+    let components: Option<Vec<&str>> = relative
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect();
+
+    components.map(|components| components.join("/"))
+}
+
+/// Maps a slash separated relative key onto a path beneath `root`, refusing anything that would escape it
+fn local_path_for(root: &Path, relative: &str) -> Option<PathBuf> {
+    let mut path = root.to_path_buf();
+    for segment in relative.split('/').filter(|segment| !segment.is_empty()) {
+        let segment = Path::new(segment);
+        let is_plain = segment.components().count() == 1
+            && matches!(
+                segment.components().next(),
+                Some(std::path::Component::Normal(_))
+            );
+        if !is_plain {
+            return None;
+        }
+        path.push(segment);
+    }
+
+    Some(path)
+}
+
+/// Describes a bucket setting which isn't in the expected state
+fn expectation_failure(setting: &str, expected: bool) -> String {
+    if expected {
+        format!("{} is expected to be enabled, but is not", setting)
+    } else {
+        format!("{} is expected to be disabled, but is enabled", setting)
+    }
+}
+
+/// Compresses bytes with gzip at the default level
+#[cfg(feature = "compression")]
+fn gzip(data: &[u8]) -> Result<Vec<u8>, StorageError> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompresses gzip bytes, refusing to inflate past `limit` so a small object can't expand to exhaust memory
+#[cfg(feature = "compression")]
+fn gunzip(key: &str, data: &[u8], limit: u64) -> Result<Vec<u8>, StorageError> {
+    use std::io::Read;
+
+    let mut decompressed = Vec::new();
+    flate2::read::GzDecoder::new(data)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut decompressed)
+        .map_err(|e| StorageError::Decode(format!("{} is not valid gzip: {}", key, e)))?;
+
+    let size = decompressed.len() as u64;
+    if size > limit {
+        return Err(StorageError::TooLarge {
+            key: key.to_string(),
+            size,
+            limit,
+        });
+    }
+    Ok(decompressed)
+}
+
+/// Builds the header block(s) for a tar entry, treating names ending in a slash as directories
+#[cfg(feature = "tar")]
+fn tar_header(name: &str, size: u64, mtime: u64) -> Bytes {
+    let mut blocks = Vec::with_capacity(512);
+
+    // Names which don't fit the 100 byte header field are carried in a preceding GNU long name entry
+    if name.len() > 100 {
+        let mut long_name = tar::Header::new_gnu();
+        long_name.as_old_mut().name[..13].copy_from_slice(b"././@LongLink");
+        long_name.set_entry_type(tar::EntryType::GNULongName);
+        long_name.set_mode(0o644);
+        long_name.set_size(name.len() as u64 + 1);
+        long_name.set_cksum();
+        blocks.extend_from_slice(long_name.as_bytes());
+        blocks.extend_from_slice(name.as_bytes());
+        blocks.push(0);
+        blocks.resize(blocks.len().next_multiple_of(512), 0);
+    }
+
+    let mut header = tar::Header::new_gnu();
+    let truncated = &name.as_bytes()[..name.len().min(100)];
+    header.as_old_mut().name[..truncated.len()].copy_from_slice(truncated);
+    if name.ends_with('/') {
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_mode(0o755);
+    } else {
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_mode(0o644);
+    }
+    header.set_size(size);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    blocks.extend_from_slice(header.as_bytes());
+
+    Bytes::from(blocks)
+}
+
+/// Wraps an SDK error as [`StorageError::Backend`], for requests which aren't on a single object, EG listings or bucket configuration
+///
+/// Errors on a single object go through `object_error` instead, which picks out missing keys and refused access.
+impl<E, R> From<SdkError<E, R>> for StorageError
+where
+    E: Error + Send + Sync + 'static,
+    R: std::fmt::Debug + Send + Sync + 'static,
+{
+    fn from(error: SdkError<E, R>) -> Self {
+        StorageError::Backend(Box::new(error))
+    }
+}
+
+/// Wraps a builder error as [`StorageError::Backend`], raised when a request is missing a required field
+impl From<s3::error::BuildError> for StorageError {
+    fn from(error: s3::error::BuildError) -> Self {
+        StorageError::Backend(Box::new(error))
+    }
+}
+
+/// Wraps an error reading an object's body as [`StorageError::Backend`], EG the connection dropping mid download
+impl From<ByteStreamError> for StorageError {
+    fn from(error: ByteStreamError) -> Self {
+        StorageError::Backend(Box::new(error))
+    }
+}
+
+/// Decides whether a failed request should be retried, see [`S3Facade::with_retry_classifier`]
+pub type RetryClassifier = Arc<dyn Fn(&StorageError) -> bool + Send + Sync>;
+
+/// The facade's built in retry classification, which retries S3 asking us to slow down and nothing else
+///
+/// Public so custom classifiers can extend it rather than replace it, EG `|e| default_retry_classifier(e) || is_quirky_backend_error(e)`.
+pub fn default_retry_classifier(error: &StorageError) -> bool {
+    match error {
+        StorageError::Backend(e) => {
+            e.downcast_ref::<SdkError<ListObjectsV2Error>>()
+                .is_some_and(is_throttled)
+                || e.downcast_ref::<SdkError<PutObjectError>>()
+                    .is_some_and(is_throttled)
+                || e.downcast_ref::<SdkError<GetObjectError>>()
+                    .is_some_and(is_throttled)
+        }
+        _ => false,
+    }
 }
 
-impl S3Facade {
-    /// Constructor with bucket exists logic
+/// Maps an SDK error from a request on a single object to [`StorageError::NotFound`] or [`StorageError::AccessDenied`] where it's one of those, returning any other error as it is
+///
+/// HEAD responses have no body to carry an error code, so the status is checked as well. A 404 for a missing bucket is left alone, as it says nothing about the key.
+/// Missing keys are logged at debug level, and every other failure at error level with the full context the SDK gives.
+fn object_error<E>(key: &str, error: SdkError<E>) -> StorageError
+where
+    E: ProvideErrorMetadata + Error + Send + Sync + 'static,
+{
+    let status = error.raw_response().map(|r| r.status().as_u16());
+
+    let access_denied = match (status, error.code()) {
+        (_, Some("NoSuchBucket")) => false,
+        (_, Some("NoSuchKey" | "NotFound")) | (Some(404), _) => {
+            // A missing key is often expected, EG when probing for one, so it isn't logged as an error
+            tracing::debug!(key, "object not found");
+            return StorageError::NotFound(key.to_string());
+        }
+        (_, Some("AccessDenied")) | (Some(403), _) => true,
+        _ => false,
+    };
+
+    tracing::error!(key, ?status, error = %DisplayErrorContext(&error), "S3 request failed");
+    if access_denied {
+        StorageError::AccessDenied(key.to_string())
+    } else {
+        StorageError::Backend(Box::new(error))
+    }
+}
+
+/// Maps a failed precondition on an object, a 412 for an `If-Match` header, to [`StorageError::Conflict`], and any other error as [`object_error`] does
+fn conflict_error<E>(key: &str, expected_etag: &str, error: SdkError<E>) -> StorageError
+where
+    E: ProvideErrorMetadata + Error + Send + Sync + 'static,
+{
+    if error.raw_response().map(|r| r.status().as_u16()) == Some(412) {
+        return StorageError::Conflict {
+            key: key.to_string(),
+            expected_etag: expected_etag.to_string(),
+        };
+    }
+    object_error(key, error)
+}
+
+/// Checks whether an SDK error is S3 asking us to slow down
+fn is_throttled<E: ProvideErrorMetadata>(error: &SdkError<E>) -> bool {
+    let status = error.raw_response().map(|r| r.status().as_u16());
+
+    matches!(status, Some(429) | Some(503))
+        || matches!(
+            error.code(),
+            Some("SlowDown" | "Throttling" | "ThrottlingException" | "RequestLimitExceeded")
+        )
+}
+
+/// Checks whether an error is an SDK error from a common object operation that's worth retrying, see [`crate::retry::is_retryable`]
+///
+/// Throttling, 5xx responses, timeouts and failed connections count as transient. An SDK error can only be inspected once its operation is known, so errors from operations other than get, put, head, list, copy and delete are never treated as transient.
+pub(crate) fn is_transient(error: &(dyn Error + 'static)) -> bool {
+    fn transient<E>(error: &(dyn Error + 'static)) -> bool
+    where
+        E: ProvideErrorMetadata + Error + 'static,
+    {
+        error
+            .downcast_ref::<SdkError<E>>()
+            .is_some_and(|error| match error {
+                SdkError::TimeoutError(_) => true,
+                SdkError::DispatchFailure(failure) => failure.is_timeout() || failure.is_io(),
+                _ => {
+                    is_throttled(error)
+                        || error
+                            .raw_response()
+                            .is_some_and(|r| r.status().is_server_error())
+                }
+            })
+    }
+
+    transient::<GetObjectError>(error)
+        || transient::<PutObjectError>(error)
+        || transient::<HeadObjectError>(error)
+        || transient::<ListObjectsV2Error>(error)
+        || transient::<CopyObjectError>(error)
+        || transient::<DeleteObjectError>(error)
+}
+
+impl S3Facade {
+    /// Constructor with bucket exists logic
+    ///
+    /// This constructor returns the S3Facade struct if the name argument matches the name of an existing s3 bucket.
+    /// We use head_bucket() as a lightweight call to check for bucket existence.
+    /// If the bucket exists, we return an S3Facade struct containing metadata useful to the calling layer. If not, we return an error, and will log specifics from here.
+    /// In all cases, we want to return an arn for the bucket as part of the metadata, even if one is not provided by the sdk. This is because AWS is known to use bucket names and arns for different purposes, and we want to cover all bases.
+    /// If the SDK is unable to return the ARN automatically, we construct it using String::format();
+    ///
+    /// # Object Lambda access points
+    /// `name` may also be an S3 Object Lambda access point ARN, EG `arn:aws:s3-object-lambda:eu-west-2:123456789012:accesspoint/redact`, in which case reads return the transformed object.
+    /// Object Lambda access points don't support head_bucket(), so the existence check is skipped and the ARN is used as given. This has some limitations:
+    /// * A mistyped ARN is not caught here, only on the first request.
+    /// * head_object() is only supported if the access point's Lambda handles it, so [`StorageFacade::file_exists`] falls back to a one byte ranged GET.
+    /// * Writes, copies, deletes and listings are rejected by AWS, as Object Lambda only transforms reads.
+    ///
+    /// # Multi-Region Access Points
+    /// `name` may also be a Multi-Region Access Point ARN, EG `arn:aws:s3::123456789012:accesspoint/mfzwi23gnjvgw.mrap`, in which case requests are routed by AWS to the nearest bucket behind it.
+    /// These requests must be signed with SigV4A against the `*` region, which the SDK selects automatically for MRAP ARNs whatever region the client is configured for, via the SDK's `sigv4a` feature.
+    /// As with Object Lambda, head_bucket() isn't supported, so the existence check is skipped, and bucket level calls such as [`S3Facade::verify_bucket_policy`] won't work.
+    ///
+    /// # Directory buckets
+    /// `name` may also be an S3 Express One Zone directory bucket, EG `analytics--use1-az4--x-s3`, which holds its objects in a single availability zone for lower latency.
+    /// Requests to a directory bucket are authorised with short lived session credentials from `CreateSession`, rather than signed with SigV4 one by one. The client is set up for session auth whatever `AWS_S3_DISABLE_EXPRESS_SESSION_AUTH` says, and the SDK creates, caches and refreshes sessions itself, so callers need `s3express:CreateSession` on the bucket.
+    /// The existence check is skipped, saving a session at construction, so a mistyped name is only caught on the first request. The facade's id is the bucket's `s3express` ARN, with the account left empty as it isn't known without a request.
+    /// Directory buckets behave differently from general purpose buckets in ways the facade can't hide:
+    /// * There are no object versions, so [`StorageFacade::list_object_versions`], [`S3Facade::has_any_version`] and [`S3Facade::snapshot_at`] fail with [`StorageError::Unsupported`] without sending a request.
+    /// * Listings aren't returned in lexicographical order, and only prefixes ending in a `/` are supported.
+    /// * Tags, object lock and storage classes other than `EXPRESS_ONEZONE` are rejected by AWS, so methods relying on them fail with the error S3 returns.
+    pub async fn new(name: &str, description: &str) -> Result<Self, Box<dyn Error>> {
+        Self::builder(name, description).build().await
+    }
+
+    /// Starts building a facade, for targets which need a region, endpoint or client settings beyond what the environment configures, see [`S3FacadeBuilder`]
+    ///
+    /// EG for a local MinIO server, `S3Facade::builder("media", "Local MinIO").endpoint_url("http://localhost:9000").region("us-east-1").force_path_style(true).build().await`.
+    pub fn builder(name: &str, description: &str) -> S3FacadeBuilder {
+        S3FacadeBuilder {
+            name: name.to_string(),
+            description: description.to_string(),
+            region: None,
+            endpoint_url: None,
+            force_path_style: false,
+            operation_timeout: None,
+            max_read_bytes: DEFAULT_MAX_READ_BYTES,
+            skip_bucket_check: false,
+            sdk_config: None,
+        }
+    }
+
+    /// Constructor with the bucket exists logic of [`S3Facade::new`], building the client from a configuration the caller has already loaded
+    ///
+    /// # Remarks
+    /// [`S3Facade::new`] loads the environment's configuration every time, so a service with a facade per bucket resolves credentials once per bucket. Load the configuration once with `aws_config::load_defaults` and pass it to each facade instead, so they share its credentials provider and cached credentials, and its HTTP client and connection pool where the configuration sets one.
+    /// Each facade still builds its own lightweight `s3::Client` from the configuration. To share one client outright, clone it into [`S3Facade::from_client`] for each bucket, as cloning a client shares everything behind it, though that skips the existence check.
+    /// EG `let config = aws_config::load_defaults(BehaviorVersion::latest()).await;` and then `S3Facade::new_with_shared_config(&config, "invoices", "Invoices").await?` for each bucket.
+    pub async fn new_with_shared_config(
+        config: &aws::SdkConfig,
+        name: &str,
+        description: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::builder(name, description)
+            .sdk_config(config)
+            .build()
+            .await
+    }
+
+    /// Constructor for S3-compatible stores, EG MinIO, Wasabi or Cloudflare R2, with the same bucket exists logic as [`S3Facade::new`]
+    ///
+    /// # Remarks
+    /// Credentials and anything not overridden here are loaded from the environment as usual, so a self-hosted store's access keys go in `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`.
+    /// `endpoint_url` replaces the AWS endpoint for every request, and `region` replaces the configured region, which most S3-compatible stores still need for signing even if they ignore it. Either left as `None` keeps the default.
+    /// With `force_path_style` on, buckets are addressed as `{endpoint}/{bucket}/{key}` rather than `{bucket}.{endpoint}/{key}`, which most self-hosted gateways require as they don't have a DNS entry per bucket.
+    /// EG for a local MinIO server, `S3Facade::new_with_config("media", "Local MinIO", Some("http://localhost:9000".to_string()), Some("us-east-1".to_string()), true)`.
+    /// Stores which don't return an ARN from head_bucket() are given one built from the bucket name, as with AWS. [`S3Facade::builder`] offers the same settings and more.
+    pub async fn new_with_config(
+        name: &str,
+        description: &str,
+        endpoint_url: Option<String>,
+        region: Option<String>,
+        force_path_style: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut builder = Self::builder(name, description).force_path_style(force_path_style);
+        if let Some(endpoint_url) = endpoint_url {
+            builder = builder.endpoint_url(&endpoint_url);
+        }
+        if let Some(region) = region {
+            builder = builder.region(&region);
+        }
+
+        builder.build().await
+    }
+
+    /// Constructor around an already configured client, without the bucket exists logic
+    ///
+    /// # Remarks
+    /// No requests are sent, so this suits unit tests, where `client` can be configured against a local mock or stub server, EG with `aws-smithy-mocks`, and exercised in CI without a real bucket or credentials.
+    /// A mistyped name is only caught on the first request. As head_bucket() hasn't run, the ARN is built from the name as `arn:aws:s3:::{name}`, except for the access point ARNs and directory buckets [`S3Facade::new`] accepts, which are given the same ids they get there.
+    /// The client is used as given for every request, so a client for a directory bucket should already be set up for session auth.
+    pub fn from_client(client: s3::Client, name: &str, description: &str) -> Self {
+        let arn = if is_directory_bucket_name(name) {
+            let region = client
+                .config()
+                .region()
+                .map(|r| r.to_string())
+                .unwrap_or_default();
+            format!("arn:aws:s3express:{}::bucket/{}", region, name)
+        } else if is_object_lambda_arn(name) || is_multi_region_access_point_arn(name) {
+            name.to_string()
+        } else {
+            format!("arn:aws:s3:::{}", name)
+        };
+
+        Self::with_client(client, arn, name, description)
+    }
+
+    /// Builds the facade with default settings once the target has been resolved
+    fn with_client(client: s3::Client, arn: String, name: &str, description: &str) -> Self {
+        S3Facade {
+            read_client: client.clone(),
+            client,
+            metadata: StoreMetadata {
+                id: DataStoreId::S3(arn),
+                name: name.to_string(),
+                description: description.to_string(),
+            },
+            case_sensitivity: CaseMode::default(),
+            listing_backoff: None,
+            pipeline: None,
+            clock: Arc::new(SystemClock),
+            hashed_partitions: false,
+            treat_prefix_as_directory: false,
+            retry_classifier: None,
+            timeouts: OperationTimeouts::default(),
+            key_policy: KeyPolicy::default(),
+            key_limits: KeyLimits::default(),
+            write_retries: None,
+            coalesce_writes: false,
+            in_flight_writes: Mutex::default(),
+            directory_bucket: is_directory_bucket_name(name),
+            read_rate_limit: None,
+            part_size: MULTIPART_PART_SIZE,
+            max_read_bytes: DEFAULT_MAX_READ_BYTES,
+            guess_content_type: false,
+        }
+    }
+
+    /// Sets how keys are normalised before they are sent to the bucket
+    ///
+    /// Defaults to [`CaseMode::Sensitive`]. See [`CaseMode`] for the risks of changing this on a bucket which already holds data.
+    pub fn with_case_sensitivity(mut self, mode: CaseMode) -> Self {
+        self.case_sensitivity = mode;
+        self
+    }
+
+    /// Paces listings and retries throttled pages, see [`ListingBackoff`]
+    ///
+    /// Without this, listings request pages as fast as S3 returns them and fail on the first throttled page.
+    pub fn with_listing_backoff(mut self, backoff: ListingBackoff) -> Self {
+        self.listing_backoff = Some(backoff);
+        self
+    }
+
+    /// Applies a [`Pipeline`] to every read and write which isn't given its own encrypt or decrypt function
+    pub fn with_pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.pipeline = Some(pipeline);
+        self
+    }
+
+    /// Replaces the [`SystemClock`] used wherever the facade needs to know the time
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Spreads objects across hash derived prefixes, for write heavy workloads which S3 would otherwise throttle
+    ///
+    /// # Remarks
+    /// S3 scales request rates per prefix, so hammering a single prefix with writes gets throttled long before the bucket as a whole would be.
+    /// With this on, every object is stored under two short hash derived segments, EG `logs/today.txt` is stored as `3f/a2/logs/today.txt`; see [`S3Facade::hashed_key`].
+    /// Reads, writes, copies and deletes map keys transparently, and listings map stored keys back, so callers keep working with the logical key throughout.
+    ///
+    /// The tradeoffs:
+    /// * The bucket is no longer browsable by a human in the console, as related objects are scattered across partitions.
+    /// * Listing a prefix has to list the whole bucket and filter, as a logical prefix doesn't map to any one stored prefix.
+    /// * Objects written before this was turned on are not visible through the facade, and vice versa, so choose this when a bucket is created.
+    pub fn with_hashed_partitions(mut self, enabled: bool) -> Self {
+        self.hashed_partitions = enabled;
+        self
+    }
+
+    /// Replaces the built in classification of which failed requests are worth retrying
+    ///
+    /// # Remarks
+    /// S3-compatible backends don't all report transient failures the way AWS does, so errors a backend only returns under load can be marked retryable here.
+    /// The classifier's answer is final, so to extend rather than replace the built in behaviour, call [`default_retry_classifier`] from within it.
+    /// Errors reach the classifier as [`StorageError::Backend`], wrapping the SDK error. Retries still follow the attempts and backoff of the facade's [`ListingBackoff`] and [`WriteRetries`], and no requests are retried without them.
+    pub fn with_retry_classifier(
+        mut self,
+        classifier: impl Fn(&StorageError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// Sets time limits per kind of operation, see [`OperationTimeouts`]
+    ///
+    /// Reads, writes, listings and deletes made through the trait methods, and the public methods built on them, are all covered. Defaults to no timeouts.
+    pub fn with_timeouts(mut self, timeouts: OperationTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Refuses operations on keys outside a [`KeyPolicy`], returning [`StorageError::KeyNotAllowed`] without sending a request
+    ///
+    /// # Remarks
+    /// Every method which maps a path to a key checks it first, including the source and destination of copies and moves. This includes [`StorageFacade::file_exists`], which returns the refusal as an error rather than reporting the key as missing.
+    /// Listings can't check a prefix up front, as a prefix isn't a key, so they leave out any objects the policy doesn't allow instead. Defaults to allowing every key.
+    pub fn with_key_policy(mut self, policy: KeyPolicy) -> Self {
+        self.key_policy = policy;
+        self
+    }
+
+    /// Refuses keys which break a [`KeyLimits`], returning [`StorageError::InvalidKey`] without sending a request
+    ///
+    /// Like [`S3Facade::with_key_policy`], every method which maps a path to a key checks it first, so reads and deletes are refused as well as writes. Defaults to no limits.
+    pub fn with_key_limits(mut self, limits: KeyLimits) -> Self {
+        self.key_limits = limits;
+        self
+    }
+
+    /// Retries writes made through [`StorageFacade::write_data`] which fail with an error the retry classifier deems retryable
+    ///
+    /// # Remarks
+    /// The encrypt function, or the facade's pipeline, runs exactly once per call however many attempts are made, and every attempt uploads the same encoded bytes.
+    /// This matters for encrypt functions which are expensive, or which aren't deterministic, EG those generating a fresh nonce, where a second run would produce different bytes.
+    /// A write timeout from [`S3Facade::with_timeouts`] covers every attempt together rather than each one. Without this, writes are attempted once.
+    pub fn with_write_retries(mut self, retries: WriteRetries) -> Self {
+        self.write_retries = Some(retries);
+        self
+    }
+
+    /// Sends reads and writes to different endpoints, EG reading through a CDN in front of the bucket while writing to S3 itself
+    ///
+    /// # Remarks
+    /// GETs, HEADs and listings of objects and their versions go to `read_endpoint`. Everything else goes to `write_endpoint`, including copies, deletes, tagging, bucket configuration and the listings used to manage multipart uploads.
+    /// Both endpoints are signed with SigV4 using the same credentials and region. SigV4 signs the Host header, so a proxy in front of S3 must forward requests with the host they were signed for, or strip the signature and sign them again itself, EG CloudFront with origin access control, otherwise S3 rejects reads with `SignatureDoesNotMatch`.
+    /// Presigned GET URLs point at `read_endpoint` too. A cached read may be stale, so methods which write then read back, EG [`S3Facade::write_data_verified`], are only as consistent as the cache in front of `read_endpoint`.
+    pub fn with_split_endpoints(mut self, read_endpoint: &str, write_endpoint: &str) -> Self {
+        let config = self.client.config().to_builder();
+        self.read_client =
+            s3::Client::from_conf(config.clone().endpoint_url(read_endpoint).build());
+        self.client = s3::Client::from_conf(config.endpoint_url(write_endpoint).build());
+        self
+    }
+
+    /// Sets the SDK's retries and operation timeout for every request the facade sends, see [`ClientConfig`]
+    ///
+    /// # Remarks
+    /// Without this the clients keep whatever the environment configures, which for the SDK defaults is 3 attempts and no operation timeout.
+    /// **Warning:** the operation timeout covers transferring the body, so a timeout tight enough for small objects will abort a large [`StorageFacade::read_data`] or upload part way through. Give latency sensitive paths their own facade rather than tightening the one used for bulk transfers, or use [`StorageFacade::read_stream`], whose body is read after the operation completes.
+    /// Retries here are per request and invisible to the facade, so they stack with [`S3Facade::with_write_retries`], EG 4 attempts per write retried 3 times by the facade is up to 16 requests.
+    pub fn with_client_config(mut self, client_config: ClientConfig) -> Self {
+        // Built on each client's existing settings, so backoff and connect timeouts the environment set are kept
+        let configured = |client: &s3::Client| {
+            let config = client.config();
+            let retry = config
+                .retry_config()
+                .cloned()
+                .unwrap_or_else(RetryConfig::standard)
+                .with_retry_mode(client_config.retry_mode)
+                .with_max_attempts(client_config.max_attempts.max(1));
+            let timeout = config
+                .timeout_config()
+                .map(TimeoutConfig::to_builder)
+                .unwrap_or_default();
+            let timeout = match client_config.operation_timeout {
+                Some(limit) => timeout.operation_timeout(limit),
+                None => timeout.disable_operation_timeout(),
+            };
+
+            let config = config
+                .to_builder()
+                .retry_config(retry)
+                .timeout_config(timeout.build());
+            s3::Client::from_conf(config.build())
+        };
+
+        self.read_client = configured(&self.read_client);
+        self.client = configured(&self.client);
+        self
+    }
+
+    /// Adds an SDK interceptor to every request the facade sends, whichever endpoint it goes to, EG to record metrics
+    pub fn with_interceptor(mut self, interceptor: impl Intercept + 'static) -> Self {
+        let interceptor = SharedInterceptor::new(interceptor);
+        let intercepted = |client: &s3::Client| {
+            let mut config = client.config().to_builder();
+            config.push_interceptor(interceptor.clone());
+            s3::Client::from_conf(config.build())
+        };
+
+        self.read_client = intercepted(&self.read_client);
+        self.client = intercepted(&self.client);
+        self
+    }
+
+    /// Coalesces concurrent writes of the same bytes to the same key made through [`StorageFacade::write_data`] into a single upload
+    ///
+    /// # Remarks
+    /// Writes are matched on the key and a SHA-256 of the bytes as they would be uploaded, after encryption or the facade's pipeline. The first write uploads, and any identical write arriving before it finishes waits and shares its result rather than sending a PUT of its own.
+    /// Callers sharing a failed upload get an error carrying its message, and if the uploading caller is cancelled, every caller waiting on it fails too.
+    /// An encrypt function which isn't deterministic produces different bytes per call, so its writes are never coalesced. Defaults to off.
+    pub fn with_write_coalescing(mut self, enabled: bool) -> Self {
+        self.coalesce_writes = enabled;
+        self
+    }
+
+    /// Guesses each object's content type from the extension of its key when the write doesn't give one, EG `text/html` for `index.html`
+    ///
+    /// # Remarks
+    /// So objects are served with a useful type through presigned URLs rather than S3's `binary/octet-stream`. Only a short list of common web, document, image and archive extensions is recognised, matched case-insensitively, and keys with any other extension or none are written without a type as before.
+    /// A content type given explicitly, EG through [`S3Facade::write_data_with_options`], always wins over the guess. Defaults to off.
+    pub fn with_content_type_guessing(mut self, enabled: bool) -> Self {
+        self.guess_content_type = enabled;
+        self
+    }
+
+    /// Caps how fast the facade downloads object bodies, in bytes per second, so large downloads don't starve other services on a shared host
+    ///
+    /// # Remarks
+    /// The cap is shared by every read the facade makes, so concurrent downloads split it between them rather than each getting the full rate. It covers [`StorageFacade::read_data`] and every other method which downloads an object's body, including streams, which are paced as they're consumed.
+    /// Chunks are held back once they've arrived, so a single chunk of up to the SDK's buffer size can land ahead of the rate, but the total never runs ahead of it. Listings, heads and uploads aren't limited. A limit of zero is treated as one byte per second.
+    pub fn with_read_rate_limit(mut self, bytes_per_second: u64) -> Self {
+        self.read_rate_limit = Some(Arc::new(ReadRateLimiter {
+            bytes_per_second: bytes_per_second.max(1),
+            paid_until: Mutex::new(tokio::time::Instant::now()),
+        }));
+        self
+    }
+
+    /// Sets the size of each part in a streamed multipart upload, defaulting to 8 MiB
+    ///
+    /// # Remarks
+    /// This is also the threshold below which [`S3Facade::write_from_reader`] and [`StorageFacade::write_stream`] upload in a single PUT. At most one part is held in memory per upload, so larger parts trade memory for fewer requests.
+    /// S3 allows at most 10,000 parts per upload, so the default caps a streamed object at around 78 GiB, and larger objects need a larger part size. Sizes outside the 5 MiB to 5 GiB S3 accepts are clamped into that range.
+    pub fn with_part_size(mut self, part_size: usize) -> Self {
+        self.part_size = part_size.clamp(MIN_MULTIPART_PART_SIZE, MAX_MULTIPART_PART_SIZE);
+        self
+    }
+
+    /// Sets the largest object the facade will read into memory, defaulting to [`DEFAULT_MAX_READ_BYTES`]
+    ///
+    /// # Remarks
+    /// Applies to [`StorageFacade::read_data`] and the other methods which buffer a whole object, EG [`S3Facade::read_conditional`], [`S3Facade::read_matching`] and [`S3Facade::read_and_hash`], which fail with [`StorageError::TooLarge`] for anything larger rather than risk running the process out of memory. Streams and range reads aren't limited.
+    /// The size is taken from the response headers, so an object over the limit is refused before any of its body is downloaded. Hosts with memory to spare can raise this, up to `u64::MAX` to turn the check off.
+    pub fn with_max_read_bytes(mut self, limit: u64) -> Self {
+        self.max_read_bytes = limit;
+        self
+    }
+
+    /// Makes listings treat a prefix as a directory name rather than a literal string match
+    ///
+    /// # Remarks
+    /// S3 matches prefixes literally, so by default listing `logs` returns both `logs/today.txt` and `logs-archive/today.txt`.
+    /// With this on, a slash is appended to any prefix which doesn't already end in one, so listing `logs` only returns objects under `logs/`, as it would on a filesystem.
+    /// An empty prefix still lists the whole bucket. This applies to every method which lists a prefix, not just [`StorageFacade::list_objects`].
+    pub fn with_prefix_as_directory(mut self, enabled: bool) -> Self {
+        self.treat_prefix_as_directory = enabled;
+        self
+    }
+
+    /// Returns a handle to the object at a path, for code which reads better working with one object at a time
+    ///
+    /// No requests are sent until one of the handle's methods is called, see [`ObjectHandle`].
+    pub fn object(&self, path: &str) -> ObjectHandle<'_> {
+        ObjectHandle {
+            facade: self,
+            path: path.to_string(),
+        }
+    }
+
+    /// Whether the facade targets an S3 Express One Zone directory bucket, and so authorises requests with session credentials, see [`S3Facade::new`]
+    pub fn is_directory_bucket(&self) -> bool {
+        self.directory_bucket
+    }
+
+    /// Returns the partitioned key an object is stored under when hashed partitions are on
+    ///
+    /// The partition is two segments of two hex characters each, taken from a stable hash of the key, so the same logical key always lands in the same partition across processes and releases.
+    pub fn hashed_key(&self, logical_key: &str) -> String {
+        let key = self.case_sensitivity.apply(logical_key);
+
+        // FNV-1a, chosen because it is stable, unlike the standard library's hasher
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        let partition = (hash >> 48) as u16;
+
+        format!("{:02x}/{:02x}/{}", partition >> 8, partition & 0xff, key)
+    }
+
+    /// Returns the key the bucket should see for a caller supplied path, or an error if the facade's [`KeyPolicy`] or [`KeyLimits`] refuse it
+    fn key(&self, path: &str) -> Result<String, StorageError> {
+        let normalised = self.case_sensitivity.apply(path);
+        self.key_limits.check(&normalised)?;
+        self.key_policy.check(&normalised)?;
+
+        if self.hashed_partitions {
+            Ok(self.hashed_key(path))
+        } else {
+            Ok(self.case_sensitivity.apply(path))
+        }
+    }
+
+    /// Returns the prefix the bucket should see for a caller supplied listing prefix
+    ///
+    /// Unlike [`S3Facade::key`], prefixes are never partitioned, so this is also the form keys take once listings have mapped them back.
+    fn prefix_key(&self, dir_path: &str) -> String {
+        self.case_sensitivity.apply(dir_path)
+    }
+
+    /// Copies the given keys out of this bucket and into another facade, ready for a batch job to read
+    ///
+    /// Objects are written to the destination under the same key, with up to `concurrency` transfers in flight at once.
+    /// Bytes are copied as stored, bypassing this facade's pipeline, so encrypted objects remain encrypted in the destination.
+    /// Returns the number of objects warmed, or the first error encountered, in which case some keys may already have been copied.
+    pub async fn prefetch(
+        &self,
+        keys: &[String],
+        into: &impl StorageFacade,
+        concurrency: usize,
+    ) -> Result<usize, StorageError> {
+        stream::iter(keys)
+            .map(|key| async move {
+                let data = self.read_raw(key).await?;
+                into.write_data::<NoTransform>(key, &data, None).await
+            })
+            .buffer_unordered(concurrency.max(1))
+            .try_fold(0, |warmed, _| async move { Ok(warmed + 1) })
+            .await
+    }
+
+    /// Heads each of the given keys, returning their metadata keyed by path
+    ///
+    /// Up to `concurrency` requests are in flight at once. Keys which don't exist map to `None`, while any other failure, such as a permissions error, is returned as the error for the whole call.
+    pub async fn stat_many(
+        &self,
+        keys: &[String],
+        concurrency: usize,
+    ) -> Result<HashMap<String, Option<ObjectMetadata>>, StorageError> {
+        stream::iter(keys)
+            .map(|key| async move {
+                match self.get_object_head(&self.key(key)?).await {
+                    Ok(head) => Ok((key.clone(), Some(head_metadata(&head)))),
+                    Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => {
+                        Ok((key.clone(), None))
+                    }
+                    Err(e) => Err(object_error(key, e)),
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .try_collect()
+            .await
+    }
+
+    /// Reads an object only if it differs from the caller's cached copy
+    ///
+    /// # Remarks
+    /// Sends the precondition as `If-None-Match` and `If-Modified-Since` headers, so an unchanged object costs a round trip but no bytes.
+    /// S3 responds with a 304 when the object hasn't changed, which is returned as [`ReadOutcome::NotModified`] rather than as an error.
+    ///
+    /// # Arguments
+    /// * `path` - the path of the file to read, using forward slash "/" separators
+    /// * `precondition` - the ETag and / or timestamp of the caller's cached copy
+    pub async fn read_conditional(
+        &self,
+        path: &str,
+        precondition: ReadPrecondition,
+    ) -> Result<ReadOutcome, StorageError> {
+        let key = self.key(path)?;
+        let request = self
+            .read_client
+            .get_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .set_if_none_match(precondition.etag)
+            .set_if_modified_since(precondition.modified_since.map(DateTime::from))
+            .send()
+            .await;
+
+        let data = match request {
+            Ok(data) => data,
+            Err(e) if e.raw_response().map(|r| r.status().as_u16()) == Some(304) => {
+                return Ok(ReadOutcome::NotModified);
+            }
+            Err(e) => return Err(object_error(&key, e)),
+        };
+        self.check_read_limit(&key, &data)?;
+
+        let metadata = ObjectMetadata {
+            size: data.content_length().unwrap_or_default() as u64,
+            last_modified: data
+                .last_modified()
+                .and_then(|t| SystemTime::try_from(*t).ok()),
+            etag: data.e_tag().map(String::from),
+            storage_class: data.storage_class().map(|c| c.as_str().to_string()),
+            content_type: data.content_type().map(String::from),
+            user_metadata: data.metadata().cloned().unwrap_or_default(),
+        };
+        let bytes = self.collect_body(data.body).await?;
+
+        Ok(ReadOutcome::Modified(bytes, metadata))
+    }
+
+    /// Reads an object only if it's still the version the caller expects
+    ///
+    /// # Remarks
+    /// Sends `expected_etag` as an `If-Match` header, so an object which changed since the caller last saw it, EG between a listing and the read, fails with [`StorageError::Conflict`] rather than returning newer bytes.
+    /// An object which has been deleted fails with [`StorageError::NotFound`], as it would from [`StorageFacade::read_data`].
+    ///
+    /// # Arguments
+    /// * `path` - the path of the file to read, using forward slash "/" separators
+    /// * `expected_etag` - the ETag of the version to read, with or without its surrounding quotes
+    pub async fn read_matching(
+        &self,
+        path: &str,
+        expected_etag: &str,
+    ) -> Result<Vec<u8>, StorageError> {
+        let key = self.key(path)?;
+        let request = self
+            .read_client
+            .get_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .if_match(expected_etag)
+            .send()
+            .await;
+
+        let data = request.map_err(|e| conflict_error(&key, expected_etag, e))?;
+        self.check_read_limit(&key, &data)?;
+
+        self.collect_body(data.body).await
+    }
+
+    /// Reads an object along with its ETag, for a read-modify-write finished with [`S3Facade::write_data_if_match`]
+    ///
+    /// # Remarks
+    /// The bytes and the ETag come from the same response, so the ETag is always that of the version read, unlike fetching it separately with [`StorageFacade::get_file_metadata`].
+    /// Otherwise this behaves as [`StorageFacade::read_data`] without a decrypt function: the facade's pipeline is applied, and the read timeout and read limit apply.
+    pub async fn read_with_etag(&self, path: &str) -> Result<(Vec<u8>, String), StorageError> {
+        let (stored, etag) = self
+            .within(self.timeouts.read, "read", async {
+                let object = self.get_object_within_limit(path).await?;
+                let etag = object.e_tag().unwrap_or_default().to_string();
+                let stored = self.collect_body(object.body).await?;
+                Ok::<_, StorageError>((stored, etag))
+            })
+            .await?;
+
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.decode(&stored)?,
+            None => stored,
+        };
+        Ok((data, etag))
+    }
+
+    /// Writes an object only if it's still the version the caller expects, completing a compare and swap
+    ///
+    /// # Remarks
+    /// Sends `expected_etag` as an `If-Match` header, so S3 itself refuses the write if another writer changed the object since the caller read it. That's returned as [`StorageError::Conflict`], in which case the caller should read the object again, reapply its change and retry.
+    /// A missing object can't match, and is returned as [`StorageError::NotFound`]. Use [`StorageFacade::write_data_if_absent`] to create one.
+    /// The data is encoded as [`StorageFacade::write_data`] encodes it without an encrypt function, and the write timeout applies. The write is never retried or coalesced, as a retry after a lost response would find the object it had itself written and report a conflict.
+    ///
+    /// # Arguments
+    /// * `path` - the path of the file to write, using forward slash "/" separators
+    /// * `data` - the object's new contents
+    /// * `expected_etag` - the ETag of the version the change was made to, EG from [`S3Facade::read_with_etag`], with or without its surrounding quotes
+    pub async fn write_data_if_match(
+        &self,
+        path: &str,
+        data: &[u8],
+        expected_etag: &str,
+    ) -> Result<(), StorageError> {
+        let key = self.key(path)?;
+        let data = match &self.pipeline {
+            Some(pipeline) => pipeline.encode(data)?,
+            None => data.to_vec(),
+        };
+
+        let request = async {
+            match self
+                .client
+                .put_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .set_content_type(self.content_type_for(&key, None))
+                .if_match(expected_etag)
+                .body(ByteStream::from(data))
+                .send()
+                .await
+            {
+                Ok(_) => Ok(()),
+                // 409 is S3 reporting a concurrent conditional write which beat this one
+                Err(e)
+                    if matches!(
+                        e.raw_response().map(|r| r.status().as_u16()),
+                        Some(409 | 412)
+                    ) =>
+                {
+                    Err(StorageError::Conflict {
+                        key: key.clone(),
+                        expected_etag: expected_etag.to_string(),
+                    })
+                }
+                Err(e) => Err(object_error(&key, e)),
+            }
+        };
+        self.within(self.timeouts.write, "write", request).await
+    }
+
+    /// Reads an object, retrying failed attempts until one succeeds or `deadline` would be passed
+    ///
+    /// # Remarks
+    /// Failures the retry classifier deems retryable are retried, waiting 50ms before the first retry and doubling each time, with no limit on attempts besides the deadline, see [`S3Facade::with_retry_classifier`].
+    /// The deadline, each attempt's cut off and the waits between attempts are all measured with tokio's clock, so they agree with each other, and a test pausing tokio's time can drive them. This deliberately bypasses the facade's injectable [`Clock`], which can't drive tokio's timers, so mixing the two would let the deadline and the waits drift apart. An attempt still running when the deadline passes is cut off, and no backoff is started once the deadline would pass before it ends, so the call returns [`StorageError::TimedOut`] by the deadline rather than after it.
+    /// The read timeout from [`S3Facade::with_timeouts`] still bounds each attempt, and the bytes are decoded by the facade's pipeline as [`StorageFacade::read_data`] would.
+    pub async fn read_data_deadline(
+        &self,
+        path: &str,
+        deadline: tokio::time::Instant,
+    ) -> Result<Vec<u8>, StorageError> {
+        let start = tokio::time::Instant::now();
+        let timed_out = || StorageError::TimedOut {
+            operation: "read".to_string(),
+            after: deadline.saturating_duration_since(start),
+        };
+        let mut wait = Duration::from_millis(50);
+
+        loop {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(timed_out());
+            }
+            let error = match tokio::time::timeout_at(deadline, self.read_raw(path)).await {
+                Ok(Ok(bytes)) => {
+                    return match &self.pipeline {
+                        Some(pipeline) => Ok(pipeline.decode(&bytes)?),
+                        None => Ok(bytes),
+                    };
+                }
+                Ok(Err(e)) => e,
+                Err(_) => return Err(timed_out()),
+            };
+            if !self.is_retryable(&error) {
+                return Err(error);
+            }
+
+            if tokio::time::Instant::now() + wait >= deadline {
+                return Err(timed_out());
+            }
+            tokio::time::sleep(wait).await;
+            wait *= 2;
+        }
+    }
+
+    /// Moves every object under one prefix to the same relative key under another, EG renaming `old-name/` to `new-name/`, returning how many objects were moved
+    ///
+    /// # Remarks
+    /// The prefix is listed up front and every object is copied server side, up to [`DEFAULT_CONCURRENCY`] at a time, before any source is deleted. If any copy fails, its error is returned and every source is left in place, so nothing is lost, though copies already made stay at the destination. Running the move again once the cause is fixed overwrites them.
+    /// Once every copy has succeeded, the sources are deleted with DeleteObjects in batches of 1000. Sources which can't be deleted are left in place alongside their copies, and an error naming each is returned.
+    /// Both prefixes are treated as directories, so `old-name` moves `old-name/` rather than also catching `old-name-2/`. An empty `from_prefix` is refused, as are prefixes which contain one another once the facade's [`CaseMode`] is applied, as a copy could then overwrite a source before it had been moved.
+    /// This isn't atomic: readers can see objects at both prefixes while it runs, and objects written under `from_prefix` once it's been listed aren't moved.
+    pub async fn move_prefix(
+        &self,
+        from_prefix: &str,
+        to_prefix: &str,
+    ) -> Result<usize, StorageError> {
+        // Listed keys come back with the case mode applied, so the prefixes are compared and stripped in the same form
+        let from_prefix = self.prefix_key(&directory_prefix(from_prefix)?);
+        let to_prefix = self.prefix_key(&as_directory(to_prefix));
+        if from_prefix.starts_with(&to_prefix) || to_prefix.starts_with(&from_prefix) {
+            return Err(StorageError::InvalidKey {
+                key: to_prefix.clone(),
+                reason: format!("overlaps the prefix being moved, {}", from_prefix),
+            });
+        }
+
+        let keys = self.list_objects(&from_prefix).await?;
+        stream::iter(&keys)
+            .map(|key| {
+                let destination = key
+                    .strip_prefix(&from_prefix)
+                    .map(|relative| format!("{}{}", to_prefix, relative))
+                    .ok_or_else(|| StorageError::InvalidKey {
+                        key: key.clone(),
+                        reason: format!("was listed but isn't under {}", from_prefix),
+                    });
+                async move { self.copy_file(key, &destination?).await }
+            })
+            .buffer_unordered(DEFAULT_CONCURRENCY)
+            .try_collect::<Vec<()>>()
+            .await?;
+
+        let (_, failed) = self.delete_batched(&keys).await?;
+        if !failed.is_empty() {
+            let failures: Vec<String> = failed
+                .iter()
+                .map(|(key, message)| format!("{}: {}", key, message))
+                .collect();
+            return Err(StorageError::Backend(
+                format!(
+                    "{} of {} objects were copied but couldn't be deleted: {}",
+                    failed.len(),
+                    keys.len(),
+                    failures.join("; ")
+                )
+                .into(),
+            ));
+        }
+
+        Ok(keys.len())
+    }
+
+    /// Writes a JSON manifest of everything under a prefix, for consumers who want to know a batch is complete
+    ///
+    /// # Remarks
+    /// Lists the prefix and writes `{prefix}_MANIFEST.json`, containing each object's key, size in bytes, and ETag as a checksum.
+    /// Call this once every object in a batch has been written, EG after filling a date partition in a data lake.
+    /// Any previous manifest under the prefix is left out of the new one and overwritten.
+    /// Note that ETags of multipart uploads are not an MD5 of the content, so consumers should only compare them against other ETags.
+    ///
+    /// Returns the key of the manifest object.
+    pub async fn finalize_prefix(&self, prefix: &str) -> Result<String, StorageError> {
+        let manifest_key = format!("{}_MANIFEST.json", prefix);
+        let stored_manifest_key = self.prefix_key(&manifest_key);
+
+        let mut objects = self.list_all_objects(prefix).await?;
+        objects.retain(|object| object.key() != Some(stored_manifest_key.as_str()));
+        objects.sort_by(|a, b| a.key().cmp(&b.key()));
+
+        let entries: Vec<serde_json::Value> = objects
+            .iter()
+            .map(|object| {
+                serde_json::json!({
+                    "key": object.key(),
+                    "size": object.size(),
+                    "etag": object.e_tag(),
+                })
+            })
+            .collect();
+        let manifest = serde_json::json!({
+            "prefix": prefix,
+            "objects": entries,
+        });
+
+        self.write_data::<NoTransform>(
+            &manifest_key,
+            &serde_json::to_vec_pretty(&manifest)
+                .map_err(|e| StorageError::Backend(Box::new(e)))?,
+            None,
+        )
+        .await?;
+
+        Ok(manifest_key)
+    }
+
+    /// Checks whether a key has any versions or delete markers at all
+    ///
+    /// [`StorageFacade::list_object_versions`] returns an empty list both for keys which never existed and for keys whose history has been purged, so callers don't have to tell the two apart.
+    /// Use this when all you need to know is whether anything is left, as it only requests a single entry.
+    pub async fn has_any_version(&self, path: &str) -> Result<bool, StorageError> {
+        self.check_versions_supported()?;
+        let key = self.key(path)?;
+        let request = self
+            .read_client
+            .list_object_versions()
+            .bucket(&self.metadata.name)
+            .prefix(&key)
+            .max_keys(1)
+            .send()
+            .await?;
+
+        // Entries are returned in key order, so an exact match is always listed before longer keys sharing the prefix
+        let has_version = request.versions().iter().any(|v| v.key() == Some(&key));
+        let has_marker = request
+            .delete_markers()
+            .iter()
+            .any(|m| m.key() == Some(&key));
+
+        Ok(has_version || has_marker)
+    }
+
+    /// Reads a specific version of an object, EG one returned by [`StorageFacade::list_object_versions`]
+    ///
+    /// The facade's pipeline is applied as [`StorageFacade::read_data`] applies it, so the version must have been written through the same pipeline. Fails with [`StorageError::Unsupported`] if versioning has never been enabled on the bucket, and with [`StorageError::TooLarge`] if the version is over [`S3Facade::with_max_read_bytes`].
+    pub async fn read_version(
+        &self,
+        path: &str,
+        version_id: &str,
+    ) -> Result<Vec<u8>, StorageError> {
+        self.check_versioning_configured().await?;
+        let key = self.key(path)?;
+        let bytes = self
+            .within(self.timeouts.read, "read", async {
+                let data = self
+                    .read_client
+                    .get_object()
+                    .bucket(&self.metadata.name)
+                    .key(&key)
+                    .version_id(version_id)
+                    .send()
+                    .await
+                    .map_err(|e| object_error(&key, e))?;
+                self.check_read_limit(&key, &data)?;
+                self.collect_body(data.body).await
+            })
+            .await?;
+
+        match &self.pipeline {
+            Some(pipeline) => Ok(pipeline.decode(&bytes)?),
+            None => Ok(bytes),
+        }
+    }
+
+    /// Permanently deletes a single version of an object, EG a bad write, leaving the rest of its history alone
+    ///
+    /// # Remarks
+    /// Unlike [`StorageFacade::delete_file`], no delete marker is added. Deleting the current version makes the next most recent one current, and deleting a delete marker brings the object back.
+    /// Fails with [`StorageError::Unsupported`] if versioning has never been enabled on the bucket, as the only version would then be the object itself.
+    pub async fn delete_version(&self, path: &str, version_id: &str) -> Result<(), StorageError> {
+        self.check_versioning_configured().await?;
+        let key = self.key(path)?;
+        let _request = self
+            .client
+            .delete_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .version_id(version_id)
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?;
+
+        Ok(())
+    }
+
+    /// Makes an older version of an object current again, EG to recover from an accidental overwrite
+    ///
+    /// # Remarks
+    /// The version is copied server side onto its own key as a new version, so the history is kept, including the version being replaced. Copying follows [`S3Facade::copy_file_with_options`], so the restored version keeps its content type, metadata and storage class.
+    /// Fails with [`StorageError::Unsupported`] if versioning has never been enabled on the bucket.
+    pub async fn restore_version(&self, path: &str, version_id: &str) -> Result<(), StorageError> {
+        self.check_versioning_configured().await?;
+        let key = self.key(path)?;
+        let head = self
+            .read_client
+            .head_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .version_id(version_id)
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?;
+
+        let _request = self
+            .client
+            .copy_object()
+            .copy_source(format!(
+                "{}?versionId={}",
+                copy_source(&self.metadata.name, &key),
+                urlencoding::encode(version_id)
+            ))
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .set_storage_class(head.storage_class().cloned())
+            .set_server_side_encryption(head.server_side_encryption().cloned())
+            .set_ssekms_key_id(head.ssekms_key_id().map(str::to_string))
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?;
+
+        Ok(())
+    }
+
+    /// Reconstructs which version of each key under a prefix was current at a point in time
+    ///
+    /// # Remarks
+    /// Returns `(key, version_id)` pairs in key order, for the latest version of each key written at or before `at`. Keys which didn't exist yet, or whose latest action by then was a delete, are left out.
+    /// This needs versioning to have been enabled on the bucket for the whole period, as S3 keeps no history otherwise. Objects written before versioning was enabled have a `null` version id, which S3 accepts when reading them back.
+    /// S3 timestamps versions to the second, so events within the same second as `at` count as having happened before it, and two events on one key within the same second can't be reliably ordered.
+    pub async fn snapshot_at(
+        &self,
+        prefix: &str,
+        at: SystemTime,
+    ) -> Result<Vec<(String, String)>, StorageError> {
+        // Latest event per key at or before `at`, as its time and the version id, or `None` for a delete marker
+        let mut latest: BTreeMap<String, (SystemTime, Option<String>)> = BTreeMap::new();
+        self.for_each_version(prefix, |key, version_id, modified, is_delete_marker| {
+            let Some(modified) = modified.filter(|modified| *modified <= at) else {
+                return;
+            };
+            // Events within the same second can't be ordered by time, so the first one seen is kept
+            if latest.get(key).is_some_and(|(seen, _)| *seen >= modified) {
+                return;
+            }
+            let version = (!is_delete_marker).then(|| version_id.to_string());
+            latest.insert(key.to_string(), (modified, version));
+        })
+        .await?;
+
+        Ok(latest
+            .into_iter()
+            .filter_map(|(key, (_, version))| version.map(|version| (key, version)))
+            .collect())
+    }
+
+    /// Copies a file within the same bucket, with explicit control over S3 specific behaviour
+    ///
+    /// # Remarks
+    /// Without a tagging directive, `copy_object` copies tags for some requests and drops them for others depending on what else is being replaced, so callers who care about tags should say what they want.
+    /// With `TaggingDirective::Replace`, the destination ends up with exactly the tags in `options.tags`, and the source is left untouched.
+    /// S3 writes a copy as `STANDARD` under the bucket's default encryption unless told otherwise, which would quietly move EG a Glacier object into a pricier class during a move. To avoid that the source is headed first and its storage class and server side encryption are carried over, at the cost of one extra request.
+    /// Objects encrypted with a customer provided key (SSE-C) can't be copied this way, as the facade never holds the key.
+    ///
+    /// # Arguments
+    /// * `from` - the path of the file to copy, including the filename
+    /// * `to` - the path of the new file, including the filename
+    /// * `options` - directives to apply to the copy, see [`CopyOptions`]
+    pub async fn copy_file_with_options(
+        &self,
+        from: &str,
+        to: &str,
+        options: CopyOptions,
+    ) -> Result<(), StorageError> {
+        let tagging = (options.tagging_directive == Some(TaggingDirective::Replace))
+            .then(|| encode_tags(&options.tags));
+
+        let replace_metadata = options.metadata_directive == Some(MetadataDirective::Replace);
+        let content_type = options.content_type.filter(|_| replace_metadata);
+        let metadata = replace_metadata.then_some(options.metadata);
+
+        let source = self.key(from)?;
+        let head = self
+            .get_object_head(&source)
+            .await
+            .map_err(|e| object_error(&source, e))?;
+        let storage_class = options
+            .storage_class
+            .or_else(|| head.storage_class().cloned());
+
+        let _request = self
+            .client
+            .copy_object()
+            .copy_source(copy_source(&self.metadata.name, &source))
+            .bucket(&self.metadata.name)
+            .key(self.key(to)?)
+            .set_tagging_directive(options.tagging_directive)
+            .set_tagging(tagging)
+            .set_storage_class(storage_class)
+            .set_metadata_directive(options.metadata_directive)
+            .set_content_type(content_type)
+            .set_metadata(metadata)
+            .set_server_side_encryption(head.server_side_encryption().cloned())
+            .set_ssekms_key_id(head.ssekms_key_id().map(str::to_string))
+            .send()
+            .await
+            .map_err(|e| object_error(&source, e))?;
+
+        Ok(())
+    }
+
+    /// Copies an object from this bucket into another bucket, as a server side copy so no bytes pass through the client
+    ///
+    /// # Remarks
+    /// The facade's client must be able to write to `dest_bucket`, and both buckets must be reachable from the client's region, as S3 performs the copy itself.
+    /// `from` is mapped through the facade's key settings as usual, while `to` is used as given, as the settings describe this bucket's keys rather than the destination's.
+    /// Objects up to 5 GiB can be copied this way, the most a single CopyObject request allows. To copy between backends, or objects larger than that, use [`crate::storage_facade::migrate`].
+    pub async fn copy_to_bucket(
+        &self,
+        from: &str,
+        dest_bucket: &str,
+        to: &str,
+    ) -> Result<(), StorageError> {
+        let source = self.key(from)?;
+        let _request = self
+            .client
+            .copy_object()
+            .copy_source(copy_source(&self.metadata.name, &source))
+            .bucket(dest_bucket)
+            .key(to)
+            .send()
+            .await
+            .map_err(|e| object_error(&source, e))?;
+
+        Ok(())
+    }
+
+    /// Moves an object into the trash instead of deleting it, where it can be restored until `retention` has passed
+    ///
+    /// # Remarks
+    /// The object is moved to `.trash/` followed by its key, and tagged with the time it may be purged after, measured against the facade's [`Clock`]. Nothing is purged until [`S3Facade::empty_trash`] runs.
+    /// The object's own tags travel with it, so S3's limit of 10 tags per object means an object already holding 10 can't be soft deleted.
+    /// Soft deleting a key already in the trash replaces the trashed copy, and its purge time with it. The trash sits in the same bucket, so a [`KeyPolicy`] must allow `.trash/` keys too.
+    pub async fn soft_delete(&self, path: &str, retention: Duration) -> Result<(), StorageError> {
+        let purge_after = DateTime::from(self.clock.now() + retention)
+            .fmt(DateTimeFormat::DateTime)
+            .map_err(|e| StorageError::Backend(Box::new(e)))?;
+        let mut tags = self.get_tags(path).await?;
+        tags.insert(PURGE_AFTER_TAG.to_string(), purge_after);
+
+        let trashed = format!("{}{}", TRASH_PREFIX, path);
+        self.copy_file_with_options(
+            path,
+            &trashed,
+            CopyOptions {
+                tagging_directive: Some(TaggingDirective::Replace),
+                tags,
+                ..CopyOptions::default()
+            },
+        )
+        .await?;
+        self.delete_file(path).await
+    }
+
+    /// Moves a soft deleted object back out of the trash to its original key, see [`S3Facade::soft_delete`]
+    ///
+    /// The purge time tag is removed, leaving the object with the tags it had before it was deleted. An object written to the original key since is overwritten.
+    pub async fn restore(&self, original_key: &str) -> Result<(), StorageError> {
+        let trashed = format!("{}{}", TRASH_PREFIX, original_key);
+        let mut tags = self.get_tags(&trashed).await?;
+        tags.remove(PURGE_AFTER_TAG);
+
+        self.copy_file_with_options(
+            &trashed,
+            original_key,
+            CopyOptions {
+                tagging_directive: Some(TaggingDirective::Replace),
+                tags,
+                ..CopyOptions::default()
+            },
+        )
+        .await?;
+        self.delete_file(&trashed).await
+    }
+
+    /// Permanently deletes every object in the trash whose retention has passed, returning their original keys
+    ///
+    /// # Remarks
+    /// Intended to run on a schedule as a sweeper. Purge times are compared against the facade's [`Clock`], and each trashed object costs a tagging request to check.
+    /// Objects in the trash without a readable purge time, EG ones put there by something other than [`S3Facade::soft_delete`], are left alone.
+    pub async fn empty_trash(&self) -> Result<Vec<String>, StorageError> {
+        let now = self.clock.now();
+        let mut purged = Vec::new();
+
+        for trashed in self.list_objects(TRASH_PREFIX).await? {
+            let tags = self.get_tags(&trashed).await?;
+            let purge_after = tags
+                .get(PURGE_AFTER_TAG)
+                .and_then(|time| DateTime::from_str(time, DateTimeFormat::DateTime).ok())
+                .and_then(|time| SystemTime::try_from(time).ok());
+            if purge_after.is_none_or(|purge_after| purge_after > now) {
+                continue;
+            }
+
+            self.delete_file(&trashed).await?;
+            purged.push(trashed[TRASH_PREFIX.len()..].to_string());
+        }
+
+        Ok(purged)
+    }
+
+    /// Fetches an object's tags
+    ///
+    /// # Remarks
+    /// An object without tags returns an empty map, while a missing object is reported as [`StorageError::NotFound`].
+    ///
+    /// # Arguments
+    /// * `path` - the path of the file, including the filename
+    pub async fn get_tags(&self, path: &str) -> Result<HashMap<String, String>, StorageError> {
+        let key = self.key(path)?;
+        let tagging = self
+            .client
+            .get_object_tagging()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?;
+
+        Ok(tagging
+            .tag_set()
+            .iter()
+            .map(|tag| (tag.key().to_string(), tag.value().to_string()))
+            .collect())
+    }
+
+    /// Replaces an object's tags, EG to drive lifecycle rules or cost allocation
+    ///
+    /// # Remarks
+    /// S3 keeps one tag set per object, so any tags not in `tags` are removed, and an empty map clears them all.
+    /// The tags are checked against S3's limits before anything is sent: at most 10 tags, keys of 1 to 128 characters and values of up to 256 characters.
+    ///
+    /// # Arguments
+    /// * `path` - the path of the file, including the filename
+    /// * `tags` - the object's new tags
+    pub async fn set_tags(
+        &self,
+        path: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<(), StorageError> {
+        check_tags(&tags)?;
+        let key = self.key(path)?;
+
+        let tag_set = tags
+            .into_iter()
+            .map(|(key, value)| s3_types::Tag::builder().key(key).value(value).build())
+            .collect::<Result<Vec<_>, _>>()?;
+        let tagging = s3_types::Tagging::builder()
+            .set_tag_set(Some(tag_set))
+            .build()?;
+
+        self.client
+            .put_object_tagging()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .tagging(tagging)
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?;
+
+        Ok(())
+    }
+
+    /// Returns whether versioning is enabled, suspended or has never been turned on for the bucket
+    ///
+    /// # Remarks
+    /// Makes a `GetBucketVersioning` request on every call rather than caching the answer, as the bucket's configuration can change while the facade is in use. Callers checking before each versioned operation pay for an extra round trip each time, so hold on to the result for as long as a stale answer is acceptable, EG while rendering one page of version history.
+    /// Errors, EG a lack of the `s3:GetBucketVersioning` permission, are returned as [`StorageError::Backend`].
+    ///
+    /// EG `if facade.versioning_status().await? == VersioningStatus::Enabled { show_history(&versions) }`
+    pub async fn versioning_status(&self) -> Result<VersioningStatus, StorageError> {
+        let versioning = self
+            .client
+            .get_bucket_versioning()
+            .bucket(&self.metadata.name)
+            .send()
+            .await
+            .map_err(|e| StorageError::Backend(e.into()))?;
+        Ok(match versioning.status() {
+            Some(BucketVersioningStatus::Enabled) => VersioningStatus::Enabled,
+            Some(BucketVersioningStatus::Suspended) => VersioningStatus::Suspended,
+            _ => VersioningStatus::Disabled,
+        })
+    }
+
+    /// Checks the bucket's configuration against a set of expectations, so services can fail fast at startup
+    ///
+    /// # Remarks
+    /// Fetches the versioning, default encryption and public access block configuration, skipping any the caller has no expectation for.
+    /// A bucket with no encryption or public access block configuration at all is treated as having the setting off, rather than as an error.
+    /// Every expectation is checked before returning, so a [`StorageError::ExpectationsNotMet`] lists all the failures rather than the first one found.
+    /// Errors fetching a configuration, EG a lack of IAM permissions, are returned as [`StorageError::Backend`].
+    pub async fn verify_bucket_policy(
+        &self,
+        expect: BucketExpectations,
+    ) -> Result<(), StorageError> {
+        let mut failures = Vec::new();
+
+        if let Some(expected) = expect.versioning {
+            let versioning = self
+                .client
+                .get_bucket_versioning()
+                .bucket(&self.metadata.name)
+                .send()
+                .await
+                .map_err(|e| StorageError::Backend(e.into()))?;
+            let enabled = versioning.status() == Some(&BucketVersioningStatus::Enabled);
+            if enabled != expected {
+                failures.push(expectation_failure("versioning", expected));
+            }
+        }
+
+        if let Some(expected) = expect.default_encryption {
+            let encryption = self
+                .client
+                .get_bucket_encryption()
+                .bucket(&self.metadata.name)
+                .send()
+                .await;
+            let enabled = match encryption {
+                Ok(output) => output
+                    .server_side_encryption_configuration()
+                    .is_some_and(|config| !config.rules().is_empty()),
+                Err(e) if e.code() == Some("ServerSideEncryptionConfigurationNotFoundError") => {
+                    false
+                }
+                Err(e) => return Err(StorageError::Backend(e.into())),
+            };
+            if enabled != expected {
+                failures.push(expectation_failure("default encryption", expected));
+            }
+        }
+
+        if let Some(expected) = expect.public_access_blocked {
+            let access_block = self
+                .client
+                .get_public_access_block()
+                .bucket(&self.metadata.name)
+                .send()
+                .await;
+            let blocked = match access_block {
+                Ok(output) => output
+                    .public_access_block_configuration()
+                    .is_some_and(|config| {
+                        config.block_public_acls() == Some(true)
+                            && config.ignore_public_acls() == Some(true)
+                            && config.block_public_policy() == Some(true)
+                            && config.restrict_public_buckets() == Some(true)
+                    }),
+                Err(e) if e.code() == Some("NoSuchPublicAccessBlockConfiguration") => false,
+                Err(e) => return Err(StorageError::Backend(e.into())),
+            };
+            if blocked != expected {
+                failures.push(expectation_failure("public access block", expected));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(StorageError::ExpectationsNotMet(failures))
+        }
+    }
+
+    /// Streams every object under a prefix as a single tar archive
+    ///
+    /// # Remarks
+    /// Designed for export endpoints, so a download handler can pipe the stream straight to a client without the archive ever existing on disk or in memory.
+    /// The prefix is listed up front, then each object is fetched lazily as the stream reaches it, in lexicographical key order.
+    /// Entries are named by their key relative to `prefix`, with names over 100 bytes written using the GNU long name extension.
+    /// Bytes are archived as stored, so encrypted objects remain encrypted inside the archive.
+    /// An error fetching any object ends the stream with that error, and the archive should be considered incomplete.
+    ///
+    /// Requires the `tar` feature.
+    #[cfg(feature = "tar")]
+    pub async fn stream_prefix_as_tar(
+        &self,
+        prefix: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes, StorageError>> + Send + '_, StorageError> {
+        let stored_prefix = self.prefix_key(prefix);
+        let mut keys: Vec<String> = self
+            .list_all_objects(prefix)
+            .await?
+            .iter()
+            .filter_map(|object| object.key().map(String::from))
+            .filter(|key| *key != stored_prefix)
+            .collect();
+        keys.sort();
+
+        let entries = stream::iter(keys)
+            .then(move |key| {
+                let stored_prefix = stored_prefix.clone();
+                async move { self.tar_entry(&key, &stored_prefix).await }
+            })
+            .try_flatten();
+        // A tar archive ends with two empty 512 byte blocks
+        let end_of_archive = stream::once(async { Ok(Bytes::from_static(&[0; 1024])) });
+
+        Ok(entries.chain(end_of_archive))
+    }
+
+    /// Fetches an object and streams it as a tar entry: header, body, then padding to the next 512 byte block
+    #[cfg(feature = "tar")]
+    async fn tar_entry(
+        &self,
+        key: &str,
+        prefix: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes, StorageError>> + Send + use<>, StorageError> {
+        let stored_key = self.key(key)?;
+        let object = self
+            .read_client
+            .get_object()
+            .bucket(&self.metadata.name)
+            .key(&stored_key)
+            .send()
+            .await
+            .map_err(|e| object_error(&stored_key, e))?;
+
+        let size = object.content_length().unwrap_or_default() as u64;
+        let mtime = object.last_modified().map(|t| t.secs()).unwrap_or_default();
+        let name = key.strip_prefix(prefix).unwrap_or(key);
+        let header = tar_header(name, size, mtime.max(0) as u64);
+        let padding = vec![0; ((512 - size % 512) % 512) as usize];
+
+        let body = body_chunks(object.body, self.read_rate_limit.clone());
+
+        Ok(stream::once(async move { Ok(header) })
+            .chain(body)
+            .chain(stream::once(async move { Ok(Bytes::from(padding)) })))
+    }
+
+    /// Generates presigned GET URLs for a batch of keys, returning a map of key to URL
+    ///
+    /// # Remarks
+    /// Presigning happens locally using the client's credentials, so no requests are sent to S3, and URLs are generated concurrently.
+    /// Useful for pages which render many objects at once, EG an image gallery.
+    /// URLs are only as good as the credentials which signed them, so URLs signed with temporary credentials stop working when those credentials expire, regardless of `expires_in`.
+    /// S3 caps `expires_in` at seven days, and a longer duration returns an error without signing anything.
+    pub async fn presign_get_many(
+        &self,
+        keys: &[String],
+        expires_in: Duration,
+    ) -> Result<HashMap<String, String>, StorageError> {
+        let urls = futures::future::try_join_all(keys.iter().map(|key| async move {
+            let url = self.presign_get(key, expires_in).await?;
+            Ok::<_, StorageError>((key.clone(), url))
+        }))
+        .await?;
+
+        Ok(urls.into_iter().collect())
+    }
+
+    /// Generates a presigned GET URL, so a browser can download an object directly from S3 without the bytes passing through the caller
+    ///
+    /// # Remarks
+    /// Presigning happens locally using the client's credentials, so no requests are sent to S3, and the URL works whether or not the object exists yet.
+    /// URLs are only as good as the credentials which signed them, so URLs signed with temporary credentials stop working when those credentials expire, regardless of `expires_in`.
+    /// S3 caps `expires_in` at seven days, and a longer duration returns an error rather than a URL expiring sooner than asked. The download is the object as stored, without the facade's pipeline applied.
+    pub async fn presign_get(
+        &self,
+        path: &str,
+        expires_in: Duration,
+    ) -> Result<String, StorageError> {
+        let request = self
+            .read_client
+            .get_object()
+            .bucket(&self.metadata.name)
+            .key(self.key(path)?)
+            .presigned(presigning_config(expires_in)?)
+            .await?;
+
+        Ok(request.uri().to_string())
+    }
+
+    /// Generates a presigned PUT URL, so a browser can upload an object directly to S3 without the bytes passing through the caller
+    ///
+    /// # Remarks
+    /// Signed as for [`S3Facade::presign_get`], with the same seven day cap on `expires_in`. Anyone holding the URL can write any content to the key until it expires, overwriting what's there.
+    /// The upload is stored exactly as sent, so the facade's pipeline isn't applied, and objects which should be encrypted must be encrypted by the uploader.
+    pub async fn presign_put(
+        &self,
+        path: &str,
+        expires_in: Duration,
+    ) -> Result<String, StorageError> {
+        let request = self
+            .client
+            .put_object()
+            .bucket(&self.metadata.name)
+            .key(self.key(path)?)
+            .presigned(presigning_config(expires_in)?)
+            .await?;
+
+        Ok(request.uri().to_string())
+    }
+
+    /// Reports whether an object has been replicated, for checking disaster recovery readiness
+    ///
+    /// Heads the object and reads its replication status.
+    /// Returns `None` when replication isn't configured for the object, as S3 only sets the status on objects covered by a replication rule.
+    pub async fn replication_status(
+        &self,
+        path: &str,
+    ) -> Result<Option<ReplicationStatus>, StorageError> {
+        let key = self.key(path)?;
+        let head = self
+            .get_object_head(&key)
+            .await
+            .map_err(|e| object_error(&key, e))?;
+
+        Ok(head
+            .replication_status()
+            .and_then(ReplicationStatus::from_sdk))
+    }
+
+    /// Lists up to `limit` keys under a prefix, along with whether there were more beyond them
+    ///
+    /// # Remarks
+    /// Paging stops as soon as the limit is passed, so a huge prefix costs only the pages needed to fill it, rather than a listing of everything.
+    /// Keys are returned in lexicographical order, the order S3 lists them in, so the same call returns the same keys until the prefix changes.
+    /// With hashed partitions on, keys come back in the order of their partitions instead, so the keys returned are an arbitrary but stable selection.
+    pub async fn list_objects_limited(
+        &self,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool), StorageError> {
+        let mut keys = Vec::new();
+        let mut truncated = false;
+
+        self.walk_objects(prefix, None, |object| {
+            if keys.len() == limit {
+                truncated = true;
+                return ControlFlow::Break(());
+            }
+            keys.extend(object.key().map(String::from));
+            ControlFlow::Continue(())
+        })
+        .await?;
+
+        Ok((keys, truncated))
+    }
+
+    /// Counts the objects under a prefix whose keys match a glob, EG `*.parquet`
+    ///
+    /// # Remarks
+    /// The glob is matched against each key with the prefix removed, where `*` doesn't cross a `/`, so `*.parquet` counts only objects directly under the prefix and `**/*.parquet` counts them at any depth.
+    /// Keys are matched page by page as the listing arrives and never collected, so counting a huge prefix costs its listing but not the memory to hold it. An invalid glob fails before anything is listed.
+    pub async fn count_matching(&self, prefix: &str, glob: &str) -> Result<u64, StorageError> {
+        let matcher = GlobBuilder::new(glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| StorageError::Backend(Box::new(e)))?
+            .compile_matcher();
+        let prefix_key = self.prefix_key(prefix);
+        let mut count = 0;
+
+        self.walk_objects(prefix, None, |object| {
+            let relative = object
+                .key()
+                .unwrap_or_default()
+                .strip_prefix(prefix_key.as_str())
+                .unwrap_or_default()
+                .trim_start_matches('/');
+            if matcher.is_match(relative) {
+                count += 1;
+            }
+            ControlFlow::Continue(())
+        })
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Streams a listing one page at a time, for callers which process and discard each page along with S3's pagination details
+    ///
+    /// # Remarks
+    /// Yields one [`ListingPage`] per `list_objects_v2` response, requesting the next page only once the stream is polled past the current one. `page_size` caps the keys S3 returns per page, and defaults to S3's limit of 1000.
+    /// The caller sets the pace, so the list timeout from [`S3Facade::with_timeouts`] bounds each page rather than the whole listing. A [`ListingBackoff`] still pauses between pages and retries throttled ones.
+    /// With hashed partitions on or a [`KeyPolicy`] set, keys are filtered after S3 pages them, so a page can hold fewer keys than `page_size`, or none at all.
+    pub fn list_pages(
+        &self,
+        prefix: &str,
+        page_size: Option<usize>,
+    ) -> impl Stream<Item = Result<ListingPage, StorageError>> + Send + '_ {
+        let (logical_prefix, prefix) = self.listing_prefixes(prefix);
+        let max_keys = page_size.map(|size| size.min(i32::MAX as usize) as i32);
+
+        // The state is the token of the next page to fetch, or `None` once the last page has been yielded
+        stream::try_unfold(Some(None), move |next: Option<Option<String>>| {
+            let (logical_prefix, prefix) = (logical_prefix.clone(), prefix.clone());
+            async move {
+                let Some(continuation_token) = next else {
+                    return Ok(None);
+                };
+                if continuation_token.is_some() {
+                    self.pause_between_pages().await;
+                }
+
+                let page = self
+                    .within(
+                        self.timeouts.list,
+                        "list",
+                        self.list_objects_page(&prefix, continuation_token, max_keys, None),
+                    )
+                    .await?;
+                let next_token = page.next_continuation_token().map(String::from);
+                let listing = ListingPage {
+                    is_truncated: page.is_truncated().unwrap_or_default(),
+                    keys: page
+                        .contents
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|object| self.logical_object(object, &logical_prefix))
+                        .map(|object| object_entry(&object))
+                        .collect(),
+                    next_token: next_token.clone(),
+                };
+
+                Ok(Some((listing, next_token.map(Some))))
+            }
+        })
+    }
+
+    /// Streams the keys under a prefix as their pages arrive, for listings too large to hold in memory at once
+    ///
+    /// # Remarks
+    /// Built on [`S3Facade::list_pages`], so only one page of up to 1000 keys is held at a time, the next page is only requested once the stream is polled past the current one, and the list timeout bounds each page.
+    /// Keys are yielded in the order S3 lists them rather than sorted as [`StorageFacade::list_objects`] sorts them, which is lexicographical for general purpose buckets, but not for directory buckets or with hashed partitions on. Stop polling the stream to stop listing.
+    pub fn list_objects_stream(
+        &self,
+        dir_path: &str,
+    ) -> impl Stream<Item = Result<String, StorageError>> + Send + '_ {
+        self.list_pages(dir_path, None)
+            .map_ok(|page| stream::iter(page.keys.into_iter().map(|entry| Ok(entry.key))))
+            .try_flatten()
+    }
+
+    /// Lists the objects under a prefix which were last modified more than `age` ago
+    ///
+    /// # Remarks
+    /// Intended for retention audits, EG proving no object under a prefix is older than a retention window.
+    /// "Now" comes from the facade's [`Clock`], and entries are returned in lexicographical key order.
+    /// Objects S3 reports without a last modified time can't be aged, so they are left out rather than guessed at.
+    pub async fn find_older_than(
+        &self,
+        prefix: &str,
+        age: Duration,
+    ) -> Result<Vec<ObjectEntry>, StorageError> {
+        let now = self.clock.now();
+
+        let mut stale: Vec<ObjectEntry> = self
+            .list_all_objects(prefix)
+            .await?
+            .iter()
+            .map(object_entry)
+            .filter(|entry| {
+                entry
+                    .last_modified
+                    .and_then(|modified| now.duration_since(modified).ok())
+                    .is_some_and(|entry_age| entry_age > age)
+            })
+            .collect();
+        stale.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Ok(stale)
+    }
+
+    /// Lists the multipart uploads under a prefix which have been started but not completed or aborted
+    ///
+    /// # Remarks
+    /// Covers uploads started by any client, not just this facade, so stragglers left behind by crashed processes show up too.
+    /// Each upload's parts are listed to report its progress, so this costs one request per upload on top of the listing itself.
+    /// Uploads are returned in key order, with uploads to the same key in the order they were initiated.
+    pub async fn list_in_progress_uploads(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<MultipartUploadInfo>, StorageError> {
+        let logical_prefix = self.prefix_key(prefix);
+        let mut uploads = Vec::new();
+        let mut key_marker = None;
+        let mut upload_id_marker = None;
+
+        loop {
+            let page = self
+                .client
+                .list_multipart_uploads()
+                .bucket(&self.metadata.name)
+                .prefix(if self.hashed_partitions {
+                    ""
+                } else {
+                    &logical_prefix
+                })
+                .set_key_marker(key_marker.take())
+                .set_upload_id_marker(upload_id_marker.take())
+                .send()
+                .await?;
+
+            for upload in page.uploads() {
+                let (Some(stored_key), Some(upload_id)) = (upload.key(), upload.upload_id()) else {
+                    continue;
+                };
+                let key = if self.hashed_partitions {
+                    match strip_partition(stored_key) {
+                        Some(logical) if logical.starts_with(&logical_prefix) => logical,
+                        _ => continue,
+                    }
+                } else {
+                    stored_key
+                };
+                if !self.key_policy.allows(key) {
+                    continue;
+                }
+
+                let (parts_completed, bytes_uploaded) =
+                    self.uploaded_parts(stored_key, upload_id).await?;
+
+                uploads.push(MultipartUploadInfo {
+                    key: key.to_string(),
+                    upload_id: upload_id.to_string(),
+                    initiated: upload
+                        .initiated()
+                        .and_then(|t| SystemTime::try_from(*t).ok()),
+                    parts_completed,
+                    bytes_uploaded,
+                });
+            }
+
+            if page.is_truncated() != Some(true) {
+                break;
+            }
+            key_marker = page.next_key_marker().map(String::from);
+            upload_id_marker = page.next_upload_id_marker().map(String::from);
+        }
+
+        Ok(uploads)
+    }
+
+    /// Counts the parts uploaded so far to a multipart upload, and their combined size
+    async fn uploaded_parts(
+        &self,
+        stored_key: &str,
+        upload_id: &str,
+    ) -> Result<(usize, u64), StorageError> {
+        let mut parts_completed = 0;
+        let mut bytes_uploaded = 0;
+        let mut part_number_marker = None;
+
+        loop {
+            let page = self
+                .client
+                .list_parts()
+                .bucket(&self.metadata.name)
+                .key(stored_key)
+                .upload_id(upload_id)
+                .set_part_number_marker(part_number_marker.take())
+                .send()
+                .await?;
+
+            parts_completed += page.parts().len();
+            bytes_uploaded += page
+                .parts()
+                .iter()
+                .map(|part| part.size().unwrap_or_default() as u64)
+                .sum::<u64>();
+
+            if page.is_truncated() != Some(true) {
+                break;
+            }
+            part_number_marker = page.next_part_number_marker().map(String::from);
+        }
+
+        Ok((parts_completed, bytes_uploaded))
+    }
+
+    /// Totals the objects under a prefix, grouped by the first path segment after it
+    ///
+    /// # Remarks
+    /// Built for cost reporting on buckets laid out as `{prefix}{tenant}/...`, so one listing replaces a listing per tenant.
+    /// The listing is aggregated a page at a time, so memory is bounded by the number of groups rather than the number of objects.
+    /// Objects directly under the prefix, with no further slash in their key, are totalled under the empty string.
+    pub async fn group_by_top_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<HashMap<String, PrefixStats>, StorageError> {
+        let logical_prefix = self.prefix_key(prefix);
+        let mut groups: HashMap<String, PrefixStats> = HashMap::new();
+
+        self.for_each_object(prefix, |object| {
+            let key = object.key().unwrap_or_default();
+            let relative = key.strip_prefix(&logical_prefix).unwrap_or(key);
+            let group = match relative.split_once('/') {
+                Some((segment, _)) => segment,
+                None => "",
+            };
+
+            let stats = groups.entry(group.to_string()).or_default();
+            stats.objects += 1;
+            stats.bytes += object.size().unwrap_or_default() as u64;
+        })
+        .await?;
+
+        Ok(groups)
+    }
+
+    /// Estimates the monthly cost of storing every object under a prefix, broken down by storage class
+    ///
+    /// # Remarks
+    /// Sizes are totalled per storage class from a single listing, aggregated a page at a time, then multiplied by the caller's rates.
+    /// This only covers storage itself. Requests, retrieval fees, minimum storage durations and per object overheads for archive classes aren't included, so treat the estimate as a lower bound.
+    /// Objects listed without a storage class are counted as `STANDARD`, as some S3-compatible backends leave it out for them.
+    pub async fn estimate_storage_cost(
+        &self,
+        prefix: &str,
+        pricing: &StoragePricing,
+    ) -> Result<CostEstimate, StorageError> {
+        let mut by_class: HashMap<StorageClass, ClassCost> = HashMap::new();
+        self.for_each_object(prefix, |object| {
+            let class = match object.storage_class() {
+                Some(class) => StorageClass::from(class.as_str()),
+                None => StorageClass::Standard,
+            };
+            let cost = by_class.entry(class).or_default();
+            cost.objects += 1;
+            cost.bytes += object.size().unwrap_or_default() as u64;
+        })
+        .await?;
+
+        let mut total = 0.0;
+        for (class, cost) in by_class.iter_mut() {
+            cost.monthly_cost = pricing
+                .per_gb_month
+                .get(class)
+                .map(|rate| cost.bytes as f64 / (1024.0 * 1024.0 * 1024.0) * rate);
+            total += cost.monthly_cost.unwrap_or_default();
+        }
+
+        Ok(CostEstimate { by_class, total })
+    }
+
+    /// Moves each object under a prefix to the storage class its age calls for
+    ///
+    /// # Remarks
+    /// An object matches every rule it's older than, and the most specific of those, the rule with the longest `older_than`, decides its class. Ages are measured against the facade's [`Clock`].
+    /// Objects are moved in place with a server side copy onto their own key, which keeps their tags and user metadata but gives them a new last modified time, so later passes judge them by when they were moved.
+    /// Objects already in the class their rule asks for, or which no rule matches, are left alone. Objects S3 reports without a last modified time can't be aged, and are left alone too.
+    /// A failed move is recorded in the report rather than aborting the pass, while errors listing the prefix are returned as an error.
+    /// Objects in archive classes such as `GLACIER` can't be copied until restored, so rules moving objects out of them will fail for those objects.
+    pub async fn apply_tiering(
+        &self,
+        prefix: &str,
+        rules: &[TieringRule],
+    ) -> Result<TieringReport, StorageError> {
+        let now = self.clock.now();
+        let mut entries: Vec<ObjectEntry> = self
+            .list_all_objects(prefix)
+            .await?
+            .iter()
+            .map(object_entry)
+            .collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let mut report = TieringReport::default();
+        for entry in entries {
+            let age = entry
+                .last_modified
+                .and_then(|modified| now.duration_since(modified).ok());
+            let rule = age.and_then(|age| {
+                rules
+                    .iter()
+                    .filter(|rule| age > rule.older_than)
+                    .max_by_key(|rule| rule.older_than)
+            });
+
+            // Listings leave the class out for STANDARD objects on some S3-compatible backends
+            let current_class = entry.storage_class.as_deref().unwrap_or("STANDARD");
+            let Some(rule) = rule.filter(|rule| rule.to_class.as_str() != current_class) else {
+                report.unchanged += 1;
+                continue;
+            };
+
+            let options = CopyOptions {
+                storage_class: Some(rule.to_class.clone()),
+                ..CopyOptions::default()
+            };
+            match self
+                .copy_file_with_options(&entry.key, &entry.key, options)
+                .await
+            {
+                Ok(()) => report.transitioned.push((entry.key, rule.to_class.clone())),
+                Err(e) => report.failed.push((entry.key, e.to_string())),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Uploads every file under a local directory to a prefix, keeping their relative paths as keys
+    ///
+    /// # Remarks
+    /// Walks `local_root` recursively and writes each file to `dest_prefix` followed by its relative path, with forward slash "/" separators whatever the host platform uses.
+    /// A slash is added between the prefix and relative path if the prefix doesn't already end in one. Empty directories aren't represented, as buckets have no directories.
+    /// Up to `concurrency` uploads are in flight at once, and each goes through [`StorageFacade::write_data`], so the facade's pipeline is applied if it has one.
+    /// A failed file is recorded in the report rather than aborting the rest, so check [`UploadReport::failed`] before assuming the backup is complete.
+    /// Errors walking the directory itself are returned as an error, as the set of files to upload is then unknown.
+    /// Files are read into memory whole, so this is best suited to trees of small to medium sized files.
+    pub async fn upload_dir(
+        &self,
+        local_root: &Path,
+        dest_prefix: &str,
+        concurrency: usize,
+    ) -> Result<UploadReport, StorageError> {
+        let files = walk_files(local_root).await?;
+        let prefix = match dest_prefix {
+            "" => String::new(),
+            p if p.ends_with('/') => p.to_string(),
+            p => format!("{}/", p),
+        };
+
+        let results: Vec<Result<String, (String, String)>> = stream::iter(files)
+            .map(|file| {
+                let prefix = &prefix;
+                async move {
+                    let key = match file.strip_prefix(local_root).map(relative_key) {
+                        Ok(Some(relative)) => format!("{}{}", prefix, relative),
+                        _ => {
+                            let path = file.display().to_string();
+                            return Err((path, "path is not valid unicode".to_string()));
+                        }
+                    };
+
+                    let upload = async {
+                        let data = tokio::fs::read(&file).await?;
+                        self.write_data::<NoTransform>(&key, &data, None).await
+                    };
+                    match upload.await {
+                        Ok(()) => Ok(key),
+                        Err(e) => Err((key, e.to_string())),
+                    }
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut report = UploadReport::default();
+        for result in results {
+            match result {
+                Ok(key) => report.uploaded.push(key),
+                Err(failure) => report.failed.push(failure),
+            }
+        }
+        report.uploaded.sort();
+
+        Ok(report)
+    }
+
+    /// Deletes every object under a prefix matching a predicate, EG temporary files older than a day
+    ///
+    /// # Remarks
+    /// The prefix is listed a page at a time, with only the keys of matching objects kept, then those are deleted with DeleteObjects in batches of 1000, the most S3 accepts per request.
+    /// `should_delete` sees each object's logical key along with its size, ETag and last modified time, so age checks cost nothing beyond the listing.
+    /// Objects S3 refuses to delete are recorded in the report rather than aborting the pass, while errors listing the prefix or sending a batch are returned as an error, in which case earlier batches will already have been deleted.
+    pub async fn prune(
+        &self,
+        prefix: &str,
+        should_delete: impl Fn(&ObjectEntry) -> bool,
+    ) -> Result<PruneReport, StorageError> {
+        let mut report = PruneReport::default();
+        let mut doomed = Vec::new();
+        self.for_each_object(prefix, |object| {
+            report.scanned += 1;
+            let entry = object_entry(&object);
+            if should_delete(&entry) {
+                doomed.push(entry.key);
+            }
+        })
+        .await?;
+
+        (report.deleted, report.failed) = self.delete_batched(&doomed).await?;
+
+        Ok(report)
+    }
+
+    /// Validates every object under a prefix, moving those which fail under a quarantine prefix
+    ///
+    /// # Remarks
+    /// Each object is read through [`StorageFacade::read_data`], so `validate` sees the bytes with the facade's pipeline applied, and up to `concurrency` objects are checked at once.
+    /// An object failing validation is moved to `quarantine_prefix` followed by its key relative to `prefix`, EG scanning `uploads/` with a quarantine of `quarantine/` moves `uploads/a/b.csv` to `quarantine/a/b.csv`.
+    /// Objects already under `quarantine_prefix` are skipped, so the quarantine can safely sit inside the scanned prefix.
+    /// Objects which can't be read or moved are recorded as failures rather than aborting the scan, while errors listing the prefix are returned as an error.
+    /// Each object is read into memory whole, so this is best suited to prefixes of small to medium sized objects.
+    pub async fn scan_and_quarantine(
+        &self,
+        prefix: &str,
+        quarantine_prefix: &str,
+        validate: impl Fn(&[u8]) -> bool + Sync,
+        concurrency: usize,
+    ) -> Result<ScanReport, StorageError> {
+        let logical_prefix = self.prefix_key(prefix);
+        let logical_quarantine = self.prefix_key(quarantine_prefix);
+        let keys: Vec<String> = self
+            .list_all_objects(prefix)
+            .await?
+            .iter()
+            .filter_map(|object| object.key().map(String::from))
+            .filter(|key| logical_quarantine.is_empty() || !key.starts_with(&logical_quarantine))
+            .collect();
+
+        let validate = &validate;
+        let results: Vec<Result<Option<String>, (String, String)>> = stream::iter(keys)
+            .map(|key| {
+                let (logical_prefix, logical_quarantine) = (&logical_prefix, &logical_quarantine);
+                async move {
+                    let data = match self.read_data::<NoTransform>(&key, None).await {
+                        Ok(data) => data,
+                        Err(e) => return Err((key, e.to_string())),
+                    };
+                    if validate(&data) {
+                        return Ok(None);
+                    }
+
+                    let relative = key.strip_prefix(logical_prefix.as_str()).unwrap_or(&key);
+                    let destination = format!("{}{}", logical_quarantine, relative);
+                    match self.move_file(&key, &destination).await {
+                        Ok(()) => Ok(Some(key)),
+                        Err(e) => Err((key, e.to_string())),
+                    }
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut report = ScanReport::default();
+        for result in results {
+            match result {
+                Ok(quarantined) => {
+                    report.scanned += 1;
+                    report.quarantined.extend(quarantined);
+                }
+                Err(failure) => report.failed.push(failure),
+            }
+        }
+        report.quarantined.sort();
+
+        Ok(report)
+    }
+
+    /// Copies every object under a prefix to a new key computed by `map_key`, for changing a bucket's layout, EG adding a date partition
+    ///
+    /// # Remarks
+    /// The prefix is listed up front, so objects copied to keys under the same prefix aren't picked up again. `map_key` sees each object's full logical key and returns the full key to copy it to.
+    /// Copies are server side, with up to `concurrency` in flight at once. With `delete_source` on, each source is deleted once its copy lands, as [`StorageFacade::move_file`] does.
+    /// Keys which map onto themselves are left alone. If two keys map to the same destination, whichever copy lands last wins, so make sure the mapping is one to one.
+    /// A failed object is recorded in the report rather than aborting the rest. A failed delete leaves the copy in place, so the object exists under both keys until the run is repeated.
+    pub async fn reorganize(
+        &self,
+        src_prefix: &str,
+        map_key: impl Fn(&str) -> String,
+        delete_source: bool,
+        concurrency: usize,
+    ) -> Result<ReorgReport, StorageError> {
+        let mut report = ReorgReport::default();
+        let mut moves = Vec::new();
+        for key in self.list_objects(src_prefix).await? {
+            let destination = map_key(&key);
+            if destination == key {
+                report.unchanged += 1;
+            } else {
+                moves.push((key, destination));
+            }
+        }
+
+        let results: Vec<Result<(String, String), (String, String)>> = stream::iter(moves)
+            .map(|(key, destination)| async move {
+                let copied = if delete_source {
+                    self.move_file(&key, &destination).await
+                } else {
+                    self.copy_file(&key, &destination).await
+                };
+                match copied {
+                    Ok(()) => Ok((key, destination)),
+                    Err(e) => Err((key, e.to_string())),
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        for result in results {
+            match result {
+                Ok(reorganized) => report.reorganized.push(reorganized),
+                Err(failure) => report.failed.push(failure),
+            }
+        }
+        report.reorganized.sort();
+
+        Ok(report)
+    }
+
+    /// Downloads every object under a prefix into a local directory, recreating the key structure as directories
+    ///
+    /// # Remarks
+    /// The inverse of [`S3Facade::upload_dir`]. Each object is written to `local_root` joined with its key relative to `prefix`, creating intermediate directories as needed.
+    /// Keys ending in a slash are treated as directory markers, and create an empty directory rather than a file.
+    /// When `overwrite` is false, objects whose local path already exists are skipped and reported as such.
+    /// Keys which would resolve outside `local_root`, EG containing `..` segments, are refused and reported as failures.
+    /// Up to `concurrency` downloads are in flight at once, each through [`StorageFacade::read_data`], so the facade's pipeline is applied if it has one.
+    /// A failed object is recorded in the report rather than aborting the rest, while errors listing the prefix are returned as an error.
+    pub async fn download_prefix(
+        &self,
+        prefix: &str,
+        local_root: &Path,
+        concurrency: usize,
+        overwrite: bool,
+    ) -> Result<DownloadReport, StorageError> {
+        let stored_prefix = self.prefix_key(prefix);
+        let keys: Vec<String> = self
+            .list_all_objects(prefix)
+            .await?
+            .iter()
+            .filter_map(|object| object.key().map(String::from))
+            .collect();
+
+        enum Outcome {
+            Downloaded(String),
+            Skipped(String),
+            Failed(String, String),
+        }
+
+        let outcomes: Vec<Outcome> = stream::iter(keys)
+            .map(|key| {
+                let stored_prefix = &stored_prefix;
+                async move {
+                    let relative = key.strip_prefix(stored_prefix.as_str()).unwrap_or(&key);
+                    let Some(local_path) = local_path_for(local_root, relative) else {
+                        let reason = "key would resolve outside the local root".to_string();
+                        return Outcome::Failed(key, reason);
+                    };
+
+                    if !overwrite && tokio::fs::try_exists(&local_path).await.unwrap_or(false) {
+                        return Outcome::Skipped(key);
+                    }
+
+                    let download = async {
+                        if key.ends_with('/') {
+                            tokio::fs::create_dir_all(&local_path).await?;
+                            return Ok(());
+                        }
+                        let data = self.read_data::<NoTransform>(&key, None).await?;
+                        if let Some(parent) = local_path.parent() {
+                            tokio::fs::create_dir_all(parent).await?;
+                        }
+                        tokio::fs::write(&local_path, data).await?;
+                        Ok::<_, StorageError>(())
+                    };
+                    match download.await {
+                        Ok(()) => Outcome::Downloaded(key),
+                        Err(e) => Outcome::Failed(key, e.to_string()),
+                    }
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut report = DownloadReport::default();
+        for outcome in outcomes {
+            match outcome {
+                Outcome::Downloaded(key) => report.downloaded.push(key),
+                Outcome::Skipped(key) => report.skipped.push(key),
+                Outcome::Failed(key, reason) => report.failed.push((key, reason)),
+            }
+        }
+        report.downloaded.sort();
+        report.skipped.sort();
+
+        Ok(report)
+    }
+
+    /// Downloads an object to a local file, picking up where an earlier, interrupted download left off
+    ///
+    /// # Remarks
+    /// Whatever is already in `dest` is assumed to be the start of the object, so only the remaining bytes are fetched, with a ranged GET, and appended as they arrive.
+    /// The object's ETag is recorded beside the file while the download is in progress, in `dest` with `.etag` appended. If the object has changed since, or the file is longer than the object, the download restarts from the beginning.
+    /// A partial file without a recorded ETag, EG one left by another tool, is trusted as is. Once complete, the file's size is checked against the object's and the recorded ETag is removed.
+    /// Bytes are written as stored, without the facade's pipeline applied, as a transform can't be applied to part of an object.
+    pub async fn download_resumable(&self, path: &str, dest: &Path) -> Result<(), StorageError> {
+        let key = self.key(path)?;
+        let head = self
+            .get_object_head(&key)
+            .await
+            .map_err(|e| object_error(&key, e))?;
+        let size = head.content_length().unwrap_or_default() as u64;
+        let etag = head.e_tag().unwrap_or_default().to_string();
+
+        let mut marker = dest.as_os_str().to_owned();
+        marker.push(".etag");
+        let marker = PathBuf::from(marker);
+
+        let recorded = tokio::fs::read_to_string(&marker).await.ok();
+        let mut offset = match tokio::fs::metadata(dest).await {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        if recorded.is_some_and(|recorded| recorded != etag) || offset > size {
+            offset = 0;
+        }
+        tokio::fs::write(&marker, &etag).await?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dest)
+            .await?;
+        file.set_len(offset).await?;
+
+        if offset < size {
+            // If-Match stops a change between the head and the GET being spliced onto the old bytes
+            let mut body = self
+                .read_client
+                .get_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .range(format!("bytes={}-", offset))
+                .if_match(&etag)
+                .send()
+                .await
+                .map_err(|e| conflict_error(&key, &etag, e))?
+                .body;
+            while let Some(chunk) = body.try_next().await? {
+                self.pace_read(chunk.len()).await;
+                file.write_all(&chunk).await?;
+            }
+        }
+        file.flush().await?;
+
+        let written = file.metadata().await?.len();
+        if written != size {
+            return Err(format!(
+                "downloaded {} bytes of {}, but the object is {} bytes",
+                written, path, size
+            )
+            .into());
+        }
+        tokio::fs::remove_file(&marker).await?;
+
+        Ok(())
+    }
+
+    /// Downloads an object into a local file, streaming the body to disk rather than holding it in memory
+    ///
+    /// # Remarks
+    /// Any missing parent directories of `dest` are created, and an existing file at `dest` is replaced. If the download fails part way, the partial file is removed.
+    /// Encrypt functions and the facade's [`Pipeline`] work on whole objects, so with `decrypt` given, or a pipeline set, the object is read into memory through [`StorageFacade::read_data`] and decoded before being written. Otherwise bytes are written as stored, a chunk at a time.
+    pub async fn download_to<F>(
+        &self,
+        path: &str,
+        dest: &Path,
+        decrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        if decrypt.is_some() || self.pipeline.is_some() {
+            let data = self.read_data(path, decrypt).await?;
+            tokio::fs::write(dest, data).await?;
+            return Ok(());
+        }
+
+        let mut reader = self.read_stream(path).await?;
+        let download = async {
+            let mut file = tokio::fs::File::create(dest).await?;
+            tokio::io::copy(&mut reader, &mut file).await?;
+            file.flush().await
+        };
+        if let Err(e) = download.await {
+            let _cleanup = tokio::fs::remove_file(dest).await;
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    /// Uploads a local file to an object, streaming it from disk rather than reading it into memory
+    ///
+    /// # Remarks
+    /// Files smaller than the facade's part size, 8 MiB by default, are uploaded in a single PUT, and larger ones as a multipart upload, as [`S3Facade::write_from_reader`] does.
+    /// The facade's [`Pipeline`] works on whole objects, so with one set the file is read into memory and written through [`StorageFacade::write_data`] instead, keeping uploads readable by [`S3Facade::download_to`].
+    pub async fn upload_from(&self, path: &str, src: &Path) -> Result<(), StorageError> {
+        if self.pipeline.is_some() {
+            let data = tokio::fs::read(src).await?;
+            return self.write_data::<NoTransform>(path, &data, None).await;
+        }
+
+        let file = tokio::fs::File::open(src).await?;
+        let len = file.metadata().await?.len();
+        self.write_from_reader(path, file, Some(len)).await
+    }
+
+    /// Writes an object so that readers only ever see the previous version or the complete new one
+    ///
+    /// # Remarks
+    /// Uploads `data` to `{path}.tmp.{uuid}`, server side copies it over `path`, then deletes the temporary object.
+    /// A single S3 PUT is already all or nothing, so the benefit here is for slow or retried uploads: `path` is only touched by the copy, which S3 performs in one step once the upload has fully landed.
+    /// The window in which `path` changes is therefore just the copy, during which readers see either the old object or the new one, never a mix.
+    /// The temporary object is visible to listings while the upload is in progress, and is left behind if the process dies before the delete, so sweep stray `.tmp.` keys if you rely on listings.
+    /// If the copy fails, the temporary object is deleted and `path` is left as it was.
+    pub async fn publish(&self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+        let temp_path = format!("{}.tmp.{}", path, uuid::Uuid::new_v4());
+
+        self.write_data::<NoTransform>(&temp_path, data, None)
+            .await?;
+
+        let copied = self.copy_file(&temp_path, path).await;
+        let cleaned_up = self.delete_file(&temp_path).await;
+
+        copied?;
+        cleaned_up
+    }
+
+    /// Reads an object into a buffer the caller provides, so a loop reading many objects can reuse one allocation
+    ///
+    /// # Remarks
+    /// `buf` is cleared first, not appended to, so on success it holds exactly the object's bytes, and on failure its contents are unspecified. Its capacity is kept, and only grows when an object is larger than any read into it before.
+    /// There's no decrypt function, as one returns a fresh `Vec` anyway. The facade's [`Pipeline`] is applied if it has one, which decodes into a temporary and copies the result back, so the saving is only the download buffer.
+    /// Otherwise this behaves as [`StorageFacade::read_data`]: the read timeout and rate limit apply, and objects over the facade's read limit fail with [`StorageError::TooLarge`] before any of the body is downloaded.
+    ///
+    /// # Arguments
+    /// * `path` - the path of the file to read, using forward slash "/" separators
+    /// * `buf` - the buffer to read the object into
+    pub async fn read_into(&self, path: &str, buf: &mut Vec<u8>) -> Result<(), StorageError> {
+        buf.clear();
+        self.within(self.timeouts.read, "read", async {
+            let object = self.get_object_within_limit(path).await?;
+            buf.reserve(object.content_length().unwrap_or_default() as usize);
+
+            let mut chunks = std::pin::pin!(body_chunks(object.body, self.read_rate_limit.clone()));
+            while let Some(chunk) = chunks.try_next().await? {
+                buf.extend_from_slice(&chunk);
+            }
+            Ok::<_, StorageError>(())
+        })
+        .await?;
+
+        if let Some(pipeline) = &self.pipeline {
+            let decoded = pipeline.decode(buf)?;
+            buf.clear();
+            buf.extend_from_slice(&decoded);
+        }
+        tracing::debug!(bytes = buf.len(), "read object");
+
+        Ok(())
+    }
+
+    /// Reads an object as text, detecting its character encoding
+    ///
+    /// # Remarks
+    /// A byte order mark decides the encoding when present, which is the only way UTF-16 is recognised. Otherwise valid UTF-8 is read as UTF-8, and anything else is handed to `chardetng` to guess, EG Latin-1 text is usually detected as windows-1252.
+    /// The mark itself is not included in the returned string. Bytes which aren't valid in the detected encoding return [`StorageError::Decode`] rather than being replaced, so nothing is silently mangled.
+    /// The object is read through [`StorageFacade::read_data`], so the facade's pipeline is applied before decoding.
+    ///
+    /// Requires the `text` feature.
+    #[cfg(feature = "text")]
+    pub async fn read_text(&self, path: &str) -> Result<String, StorageError> {
+        let data = self.read_data::<NoTransform>(path, None).await?;
+
+        let (encoding, body) = match encoding_rs::Encoding::for_bom(&data) {
+            Some((encoding, bom_length)) => (encoding, &data[bom_length..]),
+            None if std::str::from_utf8(&data).is_ok() => (encoding_rs::UTF_8, &data[..]),
+            None => {
+                let mut detector = chardetng::EncodingDetector::new();
+                detector.feed(&data, true);
+                (detector.guess(None, true), &data[..])
+            }
+        };
+
+        match encoding.decode_without_bom_handling_and_without_replacement(body) {
+            Some(text) => Ok(text.into_owned()),
+            None => Err(StorageError::Decode(format!(
+                "{} is not valid {}",
+                path,
+                encoding.name()
+            ))),
+        }
+    }
+
+    /// Writes an object compressed with gzip, EG for large text which compresses well
+    ///
+    /// # Remarks
+    /// The data is compressed first and then encrypted, as encrypted bytes look random and don't compress. Read it back with [`S3Facade::read_compressed`], parsing in the matching decrypt function.
+    /// When the stored bytes are the gzip stream itself, i.e. there's no encrypt function or pipeline, the object is also given `Content-Encoding: gzip` so HTTP clients fetching it through a presigned URL decompress it transparently. Otherwise the header is left off, as it would tell those clients to decompress bytes which aren't gzip.
+    /// Otherwise this behaves as [`StorageFacade::write_data`], see [`S3Facade::write_data_with_options`].
+    ///
+    /// Requires the `compression` feature.
+    #[cfg(feature = "compression")]
+    pub async fn write_compressed<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let stored_as_gzip = encrypt.is_none() && self.pipeline.is_none();
+        let options = WriteOptions {
+            content_encoding: stored_as_gzip.then(|| "gzip".to_string()),
+            ..WriteOptions::default()
+        };
+
+        self.write_data_with_options(path, &gzip(data)?, encrypt, options)
+            .await
+    }
+
+    /// Reads an object written by [`S3Facade::write_compressed`], decrypting and then decompressing it
+    ///
+    /// # Remarks
+    /// The decrypt function, or the facade's pipeline if there isn't one, is applied before decompressing, undoing [`S3Facade::write_compressed`] in reverse order.
+    /// Both the stored object and its decompressed bytes are held to the facade's read limit, see [`S3Facade::with_max_read_bytes`], so a small object which inflates past it fails with [`StorageError::TooLarge`]. Bytes which aren't gzip return [`StorageError::Decode`].
+    ///
+    /// Requires the `compression` feature.
+    #[cfg(feature = "compression")]
+    pub async fn read_compressed<F>(
+        &self,
+        path: &str,
+        decrypt: Option<F>,
+    ) -> Result<Vec<u8>, StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let data = self.read_data(path, decrypt).await?;
+        gunzip(path, &data, self.max_read_bytes)
+    }
+
+    /// Places or lifts an object lock legal hold on an object
+    ///
+    /// # Remarks
+    /// A legal hold stops an object version being deleted or overwritten until the hold is lifted, independently of any retention period, and has no expiry of its own.
+    /// Holds are applied to the current version of the object. The bucket must have been created with object lock enabled, otherwise this fails with [`StorageError::ExpectationsNotMet`].
+    pub async fn set_legal_hold(&self, path: &str, on: bool) -> Result<(), StorageError> {
+        let status = if on {
+            ObjectLockLegalHoldStatus::On
+        } else {
+            ObjectLockLegalHoldStatus::Off
+        };
+
+        self.client
+            .put_object_legal_hold()
+            .bucket(&self.metadata.name)
+            .key(self.key(path)?)
+            .legal_hold(ObjectLockLegalHold::builder().status(status).build())
+            .send()
+            .await
+            .map_err(|e| self.object_lock_error(e))?;
+
+        Ok(())
+    }
+
+    /// Checks whether an object is under an object lock legal hold
+    ///
+    /// An object which has never had a hold placed on it reports false. As with [`S3Facade::set_legal_hold`], a bucket without object lock fails with [`StorageError::ExpectationsNotMet`].
+    pub async fn get_legal_hold(&self, path: &str) -> Result<bool, StorageError> {
+        let hold = self
+            .client
+            .get_object_legal_hold()
+            .bucket(&self.metadata.name)
+            .key(self.key(path)?)
+            .send()
+            .await;
+
+        match hold {
+            Ok(output) => Ok(output
+                .legal_hold()
+                .and_then(|hold| hold.status())
+                .is_some_and(|status| *status == ObjectLockLegalHoldStatus::On)),
+            Err(e) if e.code() == Some("NoSuchObjectLockConfiguration") => Ok(false),
+            Err(e) => Err(self.object_lock_error(e)),
+        }
+    }
+
+    /// Sets how long an object lock keeps the current version of an object from being deleted or overwritten
+    ///
+    /// # Remarks
+    /// In `GOVERNANCE` mode the retention can be shortened or removed by a user with the `s3:BypassGovernanceRetention` permission, which this method doesn't use, so it can only keep or extend an existing retention.
+    /// In `COMPLIANCE` mode the retention can't be shortened or removed by anyone until `until` has passed, not even the root account, and the object can't be deleted before then. Double check the date before using it on real data.
+    /// The bucket must have been created with object lock enabled, otherwise this fails with [`StorageError::ExpectationsNotMet`].
+    ///
+    /// # Arguments
+    /// * `path` - the path of the file, including the filename
+    /// * `mode` - `GOVERNANCE` or `COMPLIANCE`
+    /// * `until` - when the retention ends, which must be in the future
+    pub async fn set_retention(
+        &self,
+        path: &str,
+        mode: ObjectLockRetentionMode,
+        until: SystemTime,
+    ) -> Result<(), StorageError> {
+        self.client
+            .put_object_retention()
+            .bucket(&self.metadata.name)
+            .key(self.key(path)?)
+            .retention(
+                ObjectLockRetention::builder()
+                    .mode(mode)
+                    .retain_until_date(DateTime::from(until))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| self.object_lock_error(e))?;
+
+        Ok(())
+    }
+
+    /// Fetches the object lock retention on the current version of an object, as its mode and when it ends
+    ///
+    /// An object which has never had a retention set returns `None`. As with [`S3Facade::set_retention`], a bucket without object lock fails with [`StorageError::ExpectationsNotMet`].
+    pub async fn get_retention(
+        &self,
+        path: &str,
+    ) -> Result<Option<(ObjectLockRetentionMode, SystemTime)>, StorageError> {
+        let retention = self
+            .client
+            .get_object_retention()
+            .bucket(&self.metadata.name)
+            .key(self.key(path)?)
+            .send()
+            .await;
+
+        match retention {
+            Ok(output) => Ok(output.retention().and_then(|retention| {
+                let mode = retention.mode()?.clone();
+                let until = SystemTime::try_from(*retention.retain_until_date()?).ok()?;
+                Some((mode, until))
+            })),
+            Err(e) if e.code() == Some("NoSuchObjectLockConfiguration") => Ok(None),
+            Err(e) => Err(self.object_lock_error(e)),
+        }
+    }
+
+    /// Deletes an object only if its ETag still matches the one the caller expects
+    ///
+    /// # Remarks
+    /// Guards against deleting an object which another writer has replaced since the caller last read it. Returns `false`, deleting nothing, if the ETags differ.
+    /// ETags are compared with surrounding quotes ignored, so either form S3 hands out can be parsed in.
+    /// S3 has no conditional delete, so this is a head_object() followed by a delete_object(). An object replaced in the window between the two is still deleted, so this narrows the race rather than closing it.
+    pub async fn delete_if_etag(
+        &self,
+        path: &str,
+        expected_etag: &str,
+    ) -> Result<bool, StorageError> {
+        let key = self.key(path)?;
+        let head = self
+            .get_object_head(&key)
+            .await
+            .map_err(|e| object_error(&key, e))?;
+        let unquote = |etag: &str| etag.trim_matches('"').to_string();
+
+        if head.e_tag().map(unquote) != Some(unquote(expected_etag)) {
+            return Ok(false);
+        }
+
+        self.delete_file(path).await?;
+        Ok(true)
+    }
+
+    /// Writes an object straight into a storage class, EG `STANDARD_IA` or `GLACIER` for archival data, rather than S3 Standard
+    ///
+    /// # Remarks
+    /// Otherwise this behaves as [`StorageFacade::write_data`] without an encrypt function: the facade's pipeline is applied, and the upload is retried and timed out in the same way. Writes aren't coalesced, as identical data written into two classes isn't the same write.
+    /// Directory buckets only accept `EXPRESS_ONEZONE`, and objects written into archive classes must be restored before they can be read, see [`S3Facade::ensure_retrievable`].
+    pub async fn write_data_with_class(
+        &self,
+        path: &str,
+        data: &[u8],
+        storage_class: StorageClass,
+    ) -> Result<(), StorageError> {
+        self.write_data_with_options::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>>(
+            path,
+            data,
+            None,
+            WriteOptions {
+                storage_class: Some(storage_class),
+                ..WriteOptions::default()
+            },
+        )
+        .await
+    }
+
+    /// Writes an object with a content type, user metadata or storage class, see [`WriteOptions`]
+    ///
+    /// # Remarks
+    /// Otherwise this behaves as [`StorageFacade::write_data`]: the encrypt function or facade's pipeline is applied, and the upload is retried and timed out in the same way. Writes aren't coalesced, as identical data written with different options isn't the same write.
+    /// The content type and metadata come back through [`StorageFacade::get_file_metadata`]. S3 limits user metadata to 2 KB per object, and rejects the write if it's over.
+    pub async fn write_data_with_options<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+        options: WriteOptions,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let key = self.key(path)?;
+        let data = Bytes::from(if let Some(encrypt_fn) = encrypt {
+            encrypt_fn(data).map_err(StorageError::Encryption)?
+        } else if let Some(pipeline) = &self.pipeline {
+            pipeline.encode(data)?
+        } else {
+            data.to_vec()
+        });
+
+        self.put_encoded(&key, data, &options).await?;
+        Ok(())
+    }
+
+    /// Writes an object, then confirms it can be read back before returning
+    ///
+    /// # Remarks
+    /// For critical write paths where a PUT being acknowledged isn't enough, EG on S3-compatible backends which don't offer read after write consistency.
+    /// After [`StorageFacade::write_data`] succeeds, the first byte of the object is fetched with a ranged GET. If that fails, it's retried up to `verification_retries` times, waiting 50ms before the first retry and doubling each time.
+    /// Returns an error if the object still can't be read once the retries are used up, in which case the write may or may not become visible later, so treat it as failed but not rolled back.
+    pub async fn write_data_verified(
+        &self,
+        path: &str,
+        data: &[u8],
+        verification_retries: u32,
+    ) -> Result<(), StorageError> {
+        self.write_data::<NoTransform>(path, data, None).await?;
+
+        let mut wait = Duration::from_millis(50);
+        for attempt in 0..=verification_retries {
+            if matches!(self.probe_with_get(path).await, Ok(true)) {
+                return Ok(());
+            }
+            if attempt < verification_retries {
+                tokio::time::sleep(wait).await;
+                wait *= 2;
+            }
+        }
+
+        Err(format!(
+            "{} was written but couldn't be read back after {} retries",
+            path, verification_retries
+        )
+        .into())
+    }
+
+    /// Writes an object, first copying any existing object at the path to a timestamped backup key
+    ///
+    /// # Remarks
+    /// The backup is written to `{path}.bak.{timestamp}`, where the timestamp is an RFC 3339 time in UTC taken from the facade's [`Clock`], EG `config.toml.bak.2026-01-12T09:30:00Z`.
+    /// Returns the backup key, or an empty string if nothing existed at `path` to back up. The backup is a server side copy of the stored object, so it's byte for byte what was there.
+    /// If the backup fails, nothing is written. Backups are never cleaned up here, so pair this with a lifecycle rule or [`S3Facade::find_older_than`] if they shouldn't accumulate.
+    pub async fn backup_then_write(&self, path: &str, data: &[u8]) -> Result<String, StorageError> {
+        let mut backup_key = String::new();
+
+        if self.file_exists(path).await? {
+            let timestamp = DateTime::from(self.clock.now())
+                .fmt(DateTimeFormat::DateTime)
+                .map_err(|e| StorageError::Backend(Box::new(e)))?;
+            backup_key = format!("{}.bak.{}", path, timestamp);
+            self.copy_file(path, &backup_key).await?;
+        }
+
+        self.write_data::<NoTransform>(path, data, None).await?;
+
+        Ok(backup_key)
+    }
+
+    /// Writes an object along with a `{path}.sha256` sidecar holding the hex SHA-256 digest of its contents
+    ///
+    /// # Remarks
+    /// The object is written first and the sidecar second, so a consumer which waits for the sidecar never sees it before the object it describes.
+    /// If the sidecar can't be written, the object is deleted again and the sidecar's error is returned, so a failure never leaves an object without its checksum.
+    /// That clean up is itself a request which can fail, in which case the object is left behind. S3 has no multi object transactions, so this is as close to atomic as it gets.
+    /// The digest covers `data` as given, which is what [`StorageFacade::read_data`] returns, so with a [`Pipeline`] set it won't match the bytes as stored.
+    pub async fn write_with_sidecar_checksum(
+        &self,
+        path: &str,
+        data: &[u8],
+    ) -> Result<(), StorageError> {
+        let digest: String = Sha256::digest(data)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        // Mapped up front, so a sidecar key the facade refuses fails before anything is written
+        let sidecar_key = self.key(&format!("{}.sha256", path))?;
+
+        self.write_data::<NoTransform>(path, data, None).await?;
+
+        if let Err(e) = self.put_bytes(&sidecar_key, digest.into_bytes()).await {
+            let _cleanup = self.delete_file(path).await;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Writes everything a reader produces to an object, using a multipart upload if it's large
+    ///
+    /// # Remarks
+    /// With `content_length` given and under the facade's part size, 8 MiB by default, the reader is read to exactly that length and uploaded in a single PUT, skipping the three extra requests a multipart upload costs.
+    /// Otherwise the reader is uploaded in parts of that size, except that a reader which runs out within the first part is still uploaded in a single PUT.
+    /// At most one part is held in memory at a time. If any part fails, the upload is aborted so no orphaned parts are left behind to be billed for.
+    /// A reader producing fewer bytes than `content_length` is an error, and nothing is written. Bytes are written as read, without the facade's pipeline applied.
+    pub async fn write_from_reader(
+        &self,
+        path: &str,
+        mut reader: impl AsyncRead + Unpin + Send,
+        content_length: Option<u64>,
+    ) -> Result<(), StorageError> {
+        let key = self.key(path)?;
+
+        let first_part = match content_length {
+            Some(len) if len < self.part_size as u64 => {
+                let mut data = Vec::with_capacity(len as usize);
+                (&mut reader).take(len).read_to_end(&mut data).await?;
+                if (data.len() as u64) < len {
+                    return Err(
+                        format!("reader ended after {} of {} bytes", data.len(), len).into(),
+                    );
+                }
+                return self.put_bytes(&key, data).await;
+            }
+            _ => read_part(&mut reader, self.part_size).await?,
+        };
+        if first_part.len() < self.part_size {
+            return self.put_bytes(&key, first_part).await;
+        }
+
+        self.multipart_upload(&key, self.part_size, first_part, reader)
+            .await?;
+        Ok(())
+    }
+
+    /// Writes bytes as a multipart upload split into parts of exactly `part_size` bytes, returning the object's ETag
+    ///
+    /// # Remarks
+    /// A multipart ETag depends on how the object was split into parts, so writing the same content with the same part size always gives the same ETag, which [`multipart_etag`] can compute ahead of time.
+    /// The upload is multipart even when the data fits in a single part. S3 requires every part but the last to be at least 5 MiB, so a smaller `part_size` is only accepted when the data fits in one part.
+    /// Bytes are written as given, without the facade's pipeline applied, as a transform would change the content being hashed. If any part fails the upload is aborted.
+    pub async fn write_with_part_size(
+        &self,
+        path: &str,
+        data: &[u8],
+        part_size: usize,
+    ) -> Result<String, StorageError> {
+        if part_size == 0 {
+            return Err("part size must be greater than zero".into());
+        }
+        if part_size < MIN_MULTIPART_PART_SIZE && data.len() > part_size {
+            return Err(format!(
+                "part size of {} bytes is below the 5 MiB S3 allows for all but the last part",
+                part_size
+            )
+            .into());
+        }
+
+        let mut reader = data;
+        let first_part = read_part(&mut reader, part_size).await?;
+        let etag = self
+            .multipart_upload(&self.key(path)?, part_size, first_part, reader)
+            .await?;
+
+        etag.ok_or_else(|| "S3 did not return an ETag".into())
+    }
+
+    /// Creates a multipart upload and uploads a reader to it, aborting the upload if any part fails
+    ///
+    /// Returns the ETag S3 gives the completed object.
+    async fn multipart_upload(
+        &self,
+        key: &str,
+        part_size: usize,
+        first_part: Vec<u8>,
+        reader: impl AsyncRead + Unpin + Send,
+    ) -> Result<Option<String>, StorageError> {
+        let upload_id = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.metadata.name)
+            .key(key)
+            .set_content_type(self.content_type_for(key, None))
+            .send()
+            .await?
+            .upload_id()
+            .map(String::from)
+            .ok_or("S3 did not return an upload id")?;
+
+        let completed = self
+            .upload_parts(key, &upload_id, part_size, first_part, reader)
+            .await;
+        if completed.is_err() {
+            let _abort = self
+                .client
+                .abort_multipart_upload()
+                .bucket(&self.metadata.name)
+                .key(key)
+                .upload_id(&upload_id)
+                .send()
+                .await;
+        }
+
+        completed
+    }
+
+    /// Uploads a reader to a multipart upload part by part, starting with a part already read, then completes it
+    ///
+    /// The first part is always uploaded, even if empty, as S3 won't complete an upload without any parts.
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_size: usize,
+        first_part: Vec<u8>,
+        mut reader: impl AsyncRead + Unpin + Send,
+    ) -> Result<Option<String>, StorageError> {
+        let mut parts = Vec::new();
+        let mut part = first_part;
+
+        while parts.is_empty() || !part.is_empty() {
+            let part_number = parts.len() as i32 + 1;
+            let uploaded = self
+                .client
+                .upload_part()
+                .bucket(&self.metadata.name)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from(part))
+                .send()
+                .await?;
+
+            parts.push(
+                CompletedPart::builder()
+                    .part_number(part_number)
+                    .set_e_tag(uploaded.e_tag().map(String::from))
+                    .build(),
+            );
+            part = read_part(&mut reader, part_size).await?;
+        }
+
+        let completed = self
+            .client
+            .complete_multipart_upload()
+            .bucket(&self.metadata.name)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        Ok(completed.e_tag().map(String::from))
+    }
+
+    /// Uploads bytes to an already mapped key in a single PUT
+    async fn put_bytes(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.metadata.name)
+            .key(key)
+            .set_content_type(self.content_type_for(key, None))
+            .body(ByteStream::from(data))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Cuts an object down to its first `len` bytes, without downloading it
+    ///
+    /// # Remarks
+    /// The kept range is copied server side into a multipart upload with upload_part_copy(), which then replaces the object when completed, so this costs a handful of requests however large the object is.
+    /// A `len` at or beyond the object's size leaves it untouched, and a `len` of zero replaces it with an empty object, as S3 can't copy an empty range.
+    /// Every copy is conditional on the ETag the object had when its size was read, so an object overwritten part way fails with [`StorageError::Conflict`] rather than being cut down from a mix of both versions.
+    /// The content type, storage class and server side encryption settings are carried over, as [`S3Facade::copy_file_with_options`] carries them. Tags and user metadata are not, and if the upload fails it is aborted and the original is left as it was.
+    /// Ranges are copied in parts of up to 5 GiB, the largest S3 allows per part.
+    pub async fn truncate_object(&self, path: &str, len: u64) -> Result<(), StorageError> {
+        let key = self.key(path)?;
+        let head = self
+            .get_object_head(&key)
+            .await
+            .map_err(|e| object_error(&key, e))?;
+        let size = head.content_length().unwrap_or_default() as u64;
+        if len >= size {
+            return Ok(());
+        }
+        let etag = head.e_tag().unwrap_or_default();
+
+        if len == 0 {
+            self.client
+                .put_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .if_match(etag)
+                .set_content_type(head.content_type().map(str::to_string))
+                .set_storage_class(head.storage_class().cloned())
+                .set_server_side_encryption(head.server_side_encryption().cloned())
+                .set_ssekms_key_id(head.ssekms_key_id().map(str::to_string))
+                .body(ByteStream::from_static(b""))
+                .send()
+                .await
+                .map_err(|e| conflict_error(&key, etag, e))?;
+            return Ok(());
+        }
+
+        let upload_id = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .set_content_type(head.content_type().map(str::to_string))
+            .set_storage_class(head.storage_class().cloned())
+            .set_server_side_encryption(head.server_side_encryption().cloned())
+            .set_ssekms_key_id(head.ssekms_key_id().map(str::to_string))
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?
+            .upload_id()
+            .map(String::from)
+            .ok_or("S3 did not return an upload id")?;
+
+        let completed = self.copy_range_parts(&key, etag, &upload_id, len).await;
+        if completed.is_err() {
+            let _abort = self
+                .client
+                .abort_multipart_upload()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .upload_id(&upload_id)
+                .send()
+                .await;
+        }
+
+        completed
+    }
+
+    /// Copies bytes `0..len` of an object into a multipart upload of the same key and completes it, provided the object's ETag is still `etag`
+    async fn copy_range_parts(
+        &self,
+        key: &str,
+        etag: &str,
+        upload_id: &str,
+        len: u64,
+    ) -> Result<(), StorageError> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        while start < len {
+            let end = (start + MAX_MULTIPART_PART_SIZE as u64).min(len);
+            let part_number = parts.len() as i32 + 1;
+
+            let copied = self
+                .client
+                .upload_part_copy()
+                .bucket(&self.metadata.name)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .copy_source(copy_source(&self.metadata.name, key))
+                .copy_source_if_match(etag)
+                .copy_source_range(format!("bytes={}-{}", start, end - 1))
+                .send()
+                .await
+                .map_err(|e| conflict_error(key, etag, e))?;
+
+            parts.push(
+                CompletedPart::builder()
+                    .part_number(part_number)
+                    .set_e_tag(
+                        copied
+                            .copy_part_result()
+                            .and_then(|r| r.e_tag())
+                            .map(String::from),
+                    )
+                    .build(),
+            );
+            start = end;
+        }
+
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.metadata.name)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| object_error(key, e))?;
+
+        Ok(())
+    }
+
+    /// Checks an object can be read straight away, starting a restore if it's archived in `GLACIER` or `DEEP_ARCHIVE`
+    ///
+    /// # Remarks
+    /// Objects in other classes, and archived objects whose restored copy is ready, return `Ok`. Otherwise a Standard tier restore is requested, keeping the restored copy for seven days, unless one is already running, and [`StorageError::RestoreInProgress`] is returned so the caller can retry later.
+    /// This never waits for the restore, which can take hours. [`StorageFacade::read_data`] calls this itself when S3 refuses to read an archived object, so callers only need it to start restores ahead of reading.
+    pub async fn ensure_retrievable(&self, path: &str) -> Result<(), StorageError> {
+        let key = self.key(path)?;
+        let head = self
+            .get_object_head(&key)
+            .await
+            .map_err(|e| object_error(&key, e))?;
+        if !matches!(
+            head.storage_class(),
+            Some(StorageClass::Glacier | StorageClass::DeepArchive)
+        ) {
+            return Ok(());
+        }
+
+        match head.restore() {
+            // A finished restore leaves a readable copy until its expiry date
+            Some(restore) if restore.contains("ongoing-request=\"false\"") => return Ok(()),
+            Some(_) => {}
+            None => {
+                let request = RestoreRequest::builder()
+                    .days(RESTORE_DAYS)
+                    .glacier_job_parameters(
+                        GlacierJobParameters::builder()
+                            .tier(Tier::Standard)
+                            .build()?,
+                    )
+                    .build();
+                let restore = self
+                    .client
+                    .restore_object()
+                    .bucket(&self.metadata.name)
+                    .key(&key)
+                    .restore_request(request)
+                    .send()
+                    .await;
+
+                match restore {
+                    Ok(_) => {}
+                    // Another caller started the restore since the head request
+                    Err(e) if e.code() == Some("RestoreAlreadyInProgress") => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+
+        Err(StorageError::RestoreInProgress(key))
+    }
+
+    /// Reads an object only if its size is within the given bounds, both inclusive
+    ///
+    /// # Remarks
+    /// Intended as a validation gate, rejecting objects which are suspiciously small (EG truncated) or too large to process, before any of the body is downloaded.
+    /// The size is checked with head_object() first, so an object outside the bounds returns [`StorageError::SizeOutOfRange`] at the cost of a single request.
+    /// The bounds apply to the object as stored, before the facade's pipeline is applied on read. Leave a bound as `None` to not check it.
+    pub async fn read_data_bounded(
+        &self,
+        path: &str,
+        min: Option<u64>,
+        max: Option<u64>,
+    ) -> Result<Vec<u8>, StorageError> {
+        let key = self.key(path)?;
+        let size = self
+            .get_object_head(&key)
+            .await
+            .map_err(|e| object_error(&key, e))?
+            .content_length()
+            .unwrap_or_default() as u64;
+
+        if min.is_some_and(|min| size < min) || max.is_some_and(|max| size > max) {
+            return Err(StorageError::SizeOutOfRange { size, min, max });
+        }
+
+        self.read_data::<NoTransform>(path, None).await
+    }
+
+    /// Downloads an object and computes its SHA-256 in the same pass
+    ///
+    /// # Remarks
+    /// Each chunk is fed through the hasher as it arrives, so there's no second pass over the data once it's in memory.
+    /// The bytes and digest are of the object as stored: neither the facade's pipeline nor a decrypt function is applied, so the digest matches what any other client downloading the object would compute.
+    /// For objects too large to hold in memory, use [`S3Facade::read_and_hash_stream`].
+    pub async fn read_and_hash(&self, path: &str) -> Result<(Vec<u8>, [u8; 32]), StorageError> {
+        let key = self.key(path)?;
+        let object = self
+            .read_client
+            .get_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?;
+        self.check_read_limit(&key, &object)?;
+
+        let mut body = object.body;
+        let mut data = Vec::new();
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = body.try_next().await? {
+            self.pace_read(chunk.len()).await;
+            hasher.update(&chunk);
+            data.extend_from_slice(&chunk);
+        }
+
+        Ok((data, hasher.finalize().into()))
+    }
+
+    /// Streams every object under a prefix back to back, in key order, as one continuous stream of bytes
+    ///
+    /// # Remarks
+    /// Built for datasets split into ordered chunk objects, EG `data/0000`, `data/0001` and so on. Keys sort lexicographically, so chunk numbers need zero padding to be read in numeric order.
+    /// The prefix is listed up front, then each object is requested only once the previous one has been streamed, so at most one object's body is in flight, and none are buffered whole.
+    /// Chunks are yielded as stored, without the facade's pipeline applied. An object deleted after the listing ends the stream with an error once it's reached.
+    pub async fn read_concatenated(
+        &self,
+        prefix: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes, StorageError>> + Send + use<>, StorageError> {
+        let keys = self
+            .list_objects(prefix)
+            .await?
+            .iter()
+            .map(|key| self.key(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        let client = self.read_client.clone();
+        let bucket = self.metadata.name.clone();
+        let limiter = self.read_rate_limit.clone();
+
+        let chunks = stream::iter(keys)
+            .then(move |key| {
+                let request = client.get_object().bucket(&bucket).key(&key).send();
+                async move {
+                    let object = request.await.map_err(|e| object_error(&key, e))?;
+                    Ok::<_, StorageError>(object.body)
+                }
+            })
+            .map_ok(move |body| body_chunks(body, limiter.clone()))
+            .try_flatten();
+
+        Ok(chunks)
+    }
+
+    /// Finds groups of objects under a prefix with identical content, for reclaiming space taken by copies
+    ///
+    /// # Remarks
+    /// Objects are first grouped by size from a single listing, as only objects of the same size can match, so objects with a unique size cost nothing further.
+    /// Each remaining object is then hashed with SHA-256. Where S3 holds a full object SHA-256 checksum, from an upload made with that checksum algorithm, it is read with a head request.
+    /// Otherwise the whole object is downloaded and hashed, so on a prefix of equally sized objects written without checksums, this downloads nearly every byte under it.
+    /// Hashes are of the objects as stored. Groups are returned with their keys in lexicographical order, ordered by their first key, and objects with no duplicate are left out.
+    pub async fn find_duplicates(&self, prefix: &str) -> Result<Vec<Vec<String>>, StorageError> {
+        let mut by_size: HashMap<i64, Vec<String>> = HashMap::new();
+        self.for_each_object(prefix, |object| {
+            if let Some(key) = object.key() {
+                by_size
+                    .entry(object.size().unwrap_or_default())
+                    .or_default()
+                    .push(key.to_string());
+            }
+        })
+        .await?;
+
+        let mut by_hash: HashMap<[u8; 32], Vec<String>> = HashMap::new();
+        for key in by_size
+            .into_values()
+            .filter(|keys| keys.len() > 1)
+            .flatten()
+        {
+            let hash = self.content_hash(&key).await?;
+            by_hash.entry(hash).or_default().push(key);
+        }
+
+        let mut groups: Vec<Vec<String>> = by_hash
+            .into_values()
+            .filter(|keys| keys.len() > 1)
+            .map(|mut keys| {
+                keys.sort();
+                keys
+            })
+            .collect();
+        groups.sort();
+
+        Ok(groups)
+    }
+
+    /// Returns the SHA-256 of an object as stored, from its stored checksum if S3 has a full object one, otherwise by downloading it
+    async fn content_hash(&self, path: &str) -> Result<[u8; 32], StorageError> {
+        let key = self.key(path)?;
+        let head = self
+            .read_client
+            .head_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .checksum_mode(ChecksumMode::Enabled)
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?;
+
+        // Multipart uploads store a checksum of the part checksums instead, suffixed with the part count
+        let stored = head
+            .checksum_sha256()
+            .filter(|checksum| !checksum.contains('-'))
+            .filter(|_| head.checksum_type() != Some(&ChecksumType::Composite))
+            .and_then(|checksum| BASE64.decode(checksum).ok())
+            .and_then(|digest| <[u8; 32]>::try_from(digest).ok());
+        if let Some(digest) = stored {
+            return Ok(digest);
+        }
+
+        let (_, digest) = self.read_and_hash(path).await?;
+        Ok(digest)
+    }
+
+    /// Streams an object, delivering its SHA-256 once the stream has been fully consumed
+    ///
+    /// # Remarks
+    /// The receiver resolves after the stream yields its last chunk. If the stream ends in an error or is dropped early, the receiver is cancelled instead, as the digest of a partial download is meaningless.
+    /// As with [`S3Facade::read_and_hash`], chunks are yielded as stored, without the facade's pipeline applied.
+    pub async fn read_and_hash_stream(
+        &self,
+        path: &str,
+    ) -> Result<
+        (
+            impl Stream<Item = Result<Bytes, StorageError>> + Send + use<>,
+            oneshot::Receiver<[u8; 32]>,
+        ),
+        StorageError,
+    > {
+        let key = self.key(path)?;
+        let body = self
+            .read_client
+            .get_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| object_error(&key, e))?
+            .body;
+        let body = Box::pin(body_chunks(body, self.read_rate_limit.clone()));
+        let (sender, receiver) = oneshot::channel();
+
+        let chunks = stream::try_unfold(
+            (body, Sha256::new(), Some(sender)),
+            |(mut body, mut hasher, mut sender)| async move {
+                match body.try_next().await? {
+                    Some(chunk) => {
+                        hasher.update(&chunk);
+                        Ok(Some((chunk, (body, hasher, sender))))
+                    }
+                    None => {
+                        if let Some(sender) = sender.take() {
+                            // The caller may have dropped the receiver, in which case nobody wants the digest
+                            let _ = sender.send(hasher.finalize_reset().into());
+                        }
+                        Ok::<_, StorageError>(None)
+                    }
+                }
+            },
+        );
+
+        Ok((chunks, receiver))
+    }
+
+    /// Lists a prefix, returning the entries only if its fingerprint differs from one taken earlier
+    ///
+    /// # Remarks
+    /// Designed for sync clients polling a prefix: pass in the fingerprint from the previous poll, and `None` means nothing has changed since.
+    /// Otherwise the fresh listing is returned, in lexicographical key order, with the fingerprint to pass in next time. An empty `prior_fingerprint` always returns the listing.
+    /// S3 has no conditional listing, so the prefix is still listed in full each time, but a single listing yields both the fingerprint and entries, and callers skip all downstream work when it's unchanged.
+    /// Fingerprints match those from [`StorageFacade::prefix_fingerprint`].
+    pub async fn list_objects_if_changed(
+        &self,
+        prefix: &str,
+        prior_fingerprint: &str,
+    ) -> Result<Option<(Vec<ObjectEntry>, String)>, StorageError> {
+        let (entries, fingerprint) = self.fingerprinted_listing(prefix).await?;
+
+        if fingerprint == prior_fingerprint {
+            Ok(None)
+        } else {
+            Ok(Some((entries, fingerprint)))
+        }
+    }
+
+    /// Lists a prefix in key order, along with the fingerprint of that listing
+    async fn fingerprinted_listing(
+        &self,
+        prefix: &str,
+    ) -> Result<(Vec<ObjectEntry>, String), StorageError> {
+        let logical_prefix = self.prefix_key(prefix);
+        let mut entries: Vec<ObjectEntry> = self
+            .list_all_objects(prefix)
+            .await?
+            .iter()
+            .map(object_entry)
+            .collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let mut hasher = Sha256::new();
+        for entry in &entries {
+            let relative = entry
+                .key
+                .strip_prefix(&logical_prefix)
+                .unwrap_or(&entry.key);
+            // Lengths are included so no two manifests can serialise to the same bytes
+            hasher.update((relative.len() as u64).to_be_bytes());
+            hasher.update(relative.as_bytes());
+            hasher.update(entry.size.to_be_bytes());
+            hasher.update(entry.etag.as_deref().unwrap_or_default().as_bytes());
+            hasher.update(b"\n");
+        }
+
+        let fingerprint = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        Ok((entries, fingerprint))
+    }
+
+    /// Fetches a single page of a listing, retrying throttled requests if a [`ListingBackoff`] is set
+    async fn list_objects_page(
+        &self,
+        prefix: &str,
+        continuation_token: Option<String>,
+        max_keys: Option<i32>,
+        delimiter: Option<&str>,
+    ) -> Result<ListObjectsV2Output, StorageError> {
+        let mut attempt = 0;
+        loop {
+            let page = self
+                .read_client
+                .list_objects_v2()
+                .bucket(&self.metadata.name)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token.clone())
+                .set_max_keys(max_keys)
+                .set_delimiter(delimiter.map(String::from))
+                .send()
+                .await;
+
+            let backoff = match (page, &self.listing_backoff) {
+                (Err(e), Some(backoff)) if attempt < backoff.max_retries => {
+                    let error = StorageError::Backend(Box::new(e));
+                    if !self.is_retryable(&error) {
+                        return Err(error);
+                    }
+                    backoff
+                }
+                (Err(e), _) => {
+                    tracing::error!(prefix, error = %DisplayErrorContext(&e), "S3 listing failed");
+                    return Err(e.into());
+                }
+                (Ok(page), _) => return Ok(page),
+            };
+
+            let wait = backoff
+                .initial_backoff
+                .saturating_mul(2u32.saturating_pow(attempt));
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+
+    /// Uploads bytes which have already been encoded, retrying under the facade's [`WriteRetries`] and within its write timeout
+    ///
+    /// Returns the ETag and version id S3 gave the new object.
+    async fn put_encoded(
+        &self,
+        key: &str,
+        data: Bytes,
+        options: &WriteOptions,
+    ) -> Result<WriteOutput, StorageError> {
+        let attempts = async {
+            let mut attempt = 0;
+            loop {
+                let upload = self
+                    .client
+                    .put_object()
+                    .bucket(&self.metadata.name)
+                    .key(key)
+                    .set_storage_class(options.storage_class.clone())
+                    .set_content_type(self.content_type_for(key, options.content_type.clone()))
+                    .set_content_encoding(options.content_encoding.clone())
+                    .set_metadata((!options.metadata.is_empty()).then(|| options.metadata.clone()))
+                    .body(ByteStream::from(data.clone()))
+                    .send()
+                    .await;
+
+                let retries = match (upload, &self.write_retries) {
+                    (Err(e), Some(retries)) if attempt < retries.max_retries => {
+                        let error = object_error(key, e);
+                        if !self.is_retryable(&error) {
+                            return Err(error);
+                        }
+                        retries
+                    }
+                    (upload, _) => {
+                        return upload
+                            .map(|output| WriteOutput {
+                                etag: output.e_tag().map(String::from),
+                                version_id: output.version_id().map(String::from),
+                            })
+                            .map_err(|e| object_error(key, e));
+                    }
+                };
+
+                let wait = retries
+                    .initial_backoff
+                    .saturating_mul(2u32.saturating_pow(attempt));
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+        };
+        let output = self.within(self.timeouts.write, "write", attempts).await?;
+        tracing::debug!(key, bytes = data.len(), "wrote object");
+
+        Ok(output)
+    }
+
+    /// Returns [`StorageError::Unsupported`] for directory buckets, which keep no object versions
+    fn check_versions_supported(&self) -> Result<(), StorageError> {
+        if self.directory_bucket {
+            return Err(StorageError::Unsupported(format!(
+                "directory bucket {} doesn't keep object versions",
+                self.metadata.name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns [`StorageError::Unsupported`] unless versioning has been enabled on the bucket at some point
+    ///
+    /// Suspended versioning passes, as the versions written while it was enabled are still there to act on.
+    async fn check_versioning_configured(&self) -> Result<(), StorageError> {
+        self.check_versions_supported()?;
+        let versioning = self
+            .client
+            .get_bucket_versioning()
+            .bucket(&self.metadata.name)
+            .send()
+            .await?;
+        if versioning.status().is_none() {
+            return Err(StorageError::Unsupported(format!(
+                "versioning has never been enabled on bucket {}",
+                self.metadata.name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Asks the facade's retry classifier, or the default one, whether a failed request is worth retrying
+    fn is_retryable(&self, error: &StorageError) -> bool {
+        match &self.retry_classifier {
+            Some(classifier) => classifier(error),
+            None => default_retry_classifier(error),
+        }
+    }
+
+    /// Collects every object under a prefix across all pages of a listing
+    async fn list_all_objects(&self, dir_path: &str) -> Result<Vec<Object>, StorageError> {
+        let mut objects = Vec::new();
+        self.for_each_object(dir_path, |object| objects.push(object))
+            .await?;
+        tracing::debug!(count = objects.len(), "listed objects");
+        Ok(objects)
+    }
+
+    /// Walks every object under a prefix a page at a time, so callers aggregating a listing needn't hold all of it in memory
+    ///
+    /// With hashed partitions on, the whole bucket is listed, and objects are visited with their logical keys.
+    async fn for_each_object(
+        &self,
+        dir_path: &str,
+        mut visit: impl FnMut(Object),
+    ) -> Result<(), StorageError> {
+        self.walk_objects(dir_path, None, |object| {
+            visit(object);
+            ControlFlow::Continue(())
+        })
+        .await
+    }
+
+    /// Walks objects under a prefix as [`S3Facade::for_each_object`] does, until `visit` breaks, after which no further pages are requested
+    ///
+    /// `page_size` caps the keys requested per page, or `None` for S3's default of 1000.
+    async fn walk_objects(
+        &self,
+        dir_path: &str,
+        page_size: Option<i32>,
+        mut visit: impl FnMut(Object) -> ControlFlow<()>,
+    ) -> Result<(), StorageError> {
+        let (logical_prefix, prefix) = self.listing_prefixes(dir_path);
+        let mut continuation_token = None;
+
+        // The timeout covers the whole listing, not each page, so it bounds what the caller actually waits
+        self.within(self.timeouts.list, "list", async move {
+            // Walks the pages by hand rather than through the SDK paginator, so throttled pages can be retried
+            loop {
+                let page = self
+                    .list_objects_page(&prefix, continuation_token, page_size, None)
+                    .await?;
+                continuation_token = page.next_continuation_token().map(String::from);
+
+                for object in page.contents.unwrap_or_default() {
+                    let Some(object) = self.logical_object(object, &logical_prefix) else {
+                        continue;
+                    };
+                    if visit(object).is_break() {
+                        return Ok(());
+                    }
+                }
+
+                if continuation_token.is_none() {
+                    break;
+                }
+                self.pause_between_pages().await;
+            }
+
+            Ok::<_, StorageError>(())
+        })
+        .await
+    }
+
+    /// Returns the logical prefix a listing matches keys against, and the prefix sent to S3, which is empty with hashed partitions on
+    fn listing_prefixes(&self, dir_path: &str) -> (String, String) {
+        let mut logical_prefix = self.prefix_key(dir_path);
+        if self.treat_prefix_as_directory
+            && !logical_prefix.is_empty()
+            && !logical_prefix.ends_with('/')
+        {
+            logical_prefix.push('/');
+        }
+        let prefix = if self.hashed_partitions {
+            String::new()
+        } else {
+            logical_prefix.clone()
+        };
+
+        (logical_prefix, prefix)
+    }
+
+    /// Maps a listed object onto its logical key, or `None` if it's outside the listing's logical prefix or the facade's [`KeyPolicy`]
+    fn logical_object(&self, mut object: Object, logical_prefix: &str) -> Option<Object> {
+        if self.hashed_partitions {
+            match object.key().and_then(strip_partition) {
+                Some(logical) if logical.starts_with(logical_prefix) => {
+                    object.key = Some(logical.to_string());
+                }
+                _ => return None,
+            }
+        }
+
+        self.key_policy
+            .allows(object.key().unwrap_or_default())
+            .then_some(object)
+    }
+
+    /// Walks every version and delete marker under a prefix, a page at a time, visiting each with its logical key, version id, last modified time and whether it's a delete marker
+    ///
+    /// As with [`S3Facade::for_each_object`], partitions are stripped and keys outside the facade's [`KeyPolicy`] are skipped.
+    async fn for_each_version(
+        &self,
+        dir_path: &str,
+        mut visit: impl FnMut(&str, &str, Option<SystemTime>, bool),
+    ) -> Result<(), StorageError> {
+        self.check_versions_supported()?;
+        let logical_prefix = self.prefix_key(dir_path);
+        let prefix = if self.hashed_partitions {
+            ""
+        } else {
+            logical_prefix.as_str()
+        };
+        let mut key_marker = None;
+        let mut version_id_marker = None;
+
+        loop {
+            let page = self
+                .read_client
+                .list_object_versions()
+                .bucket(&self.metadata.name)
+                .prefix(prefix)
+                .set_key_marker(key_marker.take())
+                .set_version_id_marker(version_id_marker.take())
+                .send()
+                .await?;
+
+            let versions = page.versions().iter().map(|version| {
+                (
+                    version.key(),
+                    version.version_id(),
+                    version.last_modified(),
+                    false,
+                )
+            });
+            let markers = page.delete_markers().iter().map(|marker| {
+                (
+                    marker.key(),
+                    marker.version_id(),
+                    marker.last_modified(),
+                    true,
+                )
+            });
+            for (stored_key, version_id, modified, is_delete_marker) in versions.chain(markers) {
+                let Some(stored_key) = stored_key else {
+                    continue;
+                };
+                let key = if self.hashed_partitions {
+                    match strip_partition(stored_key) {
+                        Some(logical) if logical.starts_with(&logical_prefix) => logical,
+                        _ => continue,
+                    }
+                } else {
+                    stored_key
+                };
+                if !self.key_policy.allows(key) {
+                    continue;
+                }
+
+                let modified = modified.and_then(|t| SystemTime::try_from(*t).ok());
+                visit(
+                    key,
+                    version_id.unwrap_or("null"),
+                    modified,
+                    is_delete_marker,
+                );
+            }
+
+            if page.is_truncated() != Some(true) {
+                break;
+            }
+            key_marker = page.next_key_marker().map(String::from);
+            version_id_marker = page.next_version_id_marker().map(String::from);
+        }
+
+        Ok(())
+    }
+
+    /// Runs an operation under one of the facade's [`OperationTimeouts`], failing with [`StorageError::TimedOut`] if it overruns
+    async fn within<T, E>(
+        &self,
+        limit: Option<Duration>,
+        operation: &str,
+        future: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, StorageError>
+    where
+        E: Into<StorageError>,
+    {
+        let Some(limit) = limit else {
+            return future.await.map_err(Into::into);
+        };
+
+        match tokio::time::timeout(limit, future).await {
+            Ok(result) => result.map_err(Into::into),
+            Err(_) => {
+                tracing::error!(operation, ?limit, "S3 request timed out");
+                Err(StorageError::TimedOut {
+                    operation: operation.to_string(),
+                    after: limit,
+                })
+            }
+        }
+    }
+
+    /// Waits out the configured delay between listing pages, if any
+    async fn pause_between_pages(&self) {
+        if let Some(backoff) = &self.listing_backoff {
+            tokio::time::sleep(backoff.page_delay).await;
+        }
+    }
+
+    /// Downloads an object's bytes exactly as stored
+    async fn read_raw(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        self.within(self.timeouts.read, "read", async {
+            let object = self.get_object_within_limit(path).await?;
+            self.collect_body(object.body).await
+        })
+        .await
+    }
+
+    /// Picks the content type to write an object with, preferring one the caller gave over a guess from the key, see [`S3Facade::with_content_type_guessing`]
+    fn content_type_for(&self, key: &str, explicit: Option<String>) -> Option<String> {
+        explicit.or_else(|| {
+            self.guess_content_type
+                .then(|| guess_content_type(key))
+                .flatten()
+                .map(String::from)
+        })
+    }
+
+    /// Sends a GET for an object, refusing it before its body is downloaded if it's over the facade's read limit
+    async fn get_object_within_limit(&self, path: &str) -> Result<GetObjectOutput, StorageError> {
+        let key = self.key(path)?;
+        let object = match self
+            .read_client
+            .get_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .send()
+            .await
+        {
+            Ok(object) => object,
+            // S3 refuses to read archived objects, so start a restore and say so rather than failing opaquely
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(GetObjectError::is_invalid_object_state) =>
+            {
+                self.ensure_retrievable(path).await?;
+                return Err(e.into());
+            }
+            Err(e) => return Err(object_error(&key, e)),
+        };
+
+        self.check_read_limit(&key, &object)?;
+        Ok(object)
+    }
+
+    /// Refuses a GET response for an object over the facade's read limit, before its body is downloaded
     ///
-    /// This constructor returns the S3Facade struct if the name argument matches the name of an existing s3 bucket.
-    /// We use head_bucket() as a lightweight call to check for bucket existence.
-    /// If the bucket exists, we return an S3Facade struct containing metadata useful to the calling layer. If not, we return an error, and will log specifics from here.
-    /// In all cases, we want to return an arn for the bucket as part of the metadata, even if one is not provided by the sdk. This is because AWS is known to use bucket names and arns for different purposes, and we want to cover all bases.
-    /// If the SDK is unable to return the ARN automatically, we construct it using String::format();
-    pub async fn new(name: &str, description: &str) -> Result<Self, Box<dyn Error>> {
-        let config = aws::load_defaults(aws::BehaviorVersion::v2026_01_12()).await;
-        let client = s3::Client::new(&config);
+    /// Methods which buffer a whole object but send their own GET, EG with conditional headers, call this in place of [`S3Facade::get_object_within_limit`].
+    fn check_read_limit(&self, key: &str, object: &GetObjectOutput) -> Result<(), StorageError> {
+        // Dropping the response unread closes the connection, so the body is never downloaded
+        let size = object.content_length().unwrap_or_default() as u64;
+        if size > self.max_read_bytes {
+            return Err(StorageError::TooLarge {
+                key: key.to_string(),
+                size,
+                limit: self.max_read_bytes,
+            });
+        }
 
-        let request = client.head_bucket().bucket(name).send().await;
+        Ok(())
+    }
 
-        match request {
-            Err(e) => {
-                // Logging logic goes here
-                return Err(e.into());
+    /// Reads an object body into memory, paced by the facade's read rate limit if it has one
+    async fn collect_body(&self, body: ByteStream) -> Result<Vec<u8>, StorageError> {
+        if self.read_rate_limit.is_none() {
+            return Ok(Vec::from(body.collect().await?.into_bytes()));
+        }
+
+        body_chunks(body, self.read_rate_limit.clone())
+            .try_fold(Vec::new(), |mut data, chunk| async move {
+                data.extend_from_slice(&chunk);
+                Ok(data)
+            })
+            .await
+    }
+
+    /// Waits until `bytes` more of an object body can be read under the facade's read rate limit, returning straight away without one
+    async fn pace_read(&self, bytes: usize) {
+        if let Some(limiter) = &self.read_rate_limit {
+            limiter.take(bytes).await;
+        }
+    }
+
+    /// Checks an object exists by fetching its first byte, for targets which may not support head_object()
+    async fn probe_with_get(&self, path: &str) -> Result<bool, StorageError> {
+        let key = self.key(path)?;
+        let check = self
+            .read_client
+            .get_object()
+            .bucket(&self.metadata.name)
+            .key(&key)
+            .range("bytes=0-0")
+            .send()
+            .await;
+
+        match check {
+            Ok(_) => Ok(true),
+            Err(e) => match e.raw_response().map(|r| r.status().as_u16()) {
+                // An empty object can't satisfy any range, but still exists
+                Some(416) => Ok(true),
+                Some(404) => Ok(false),
+                _ => Err(object_error(&key, e)),
+            },
+        }
+    }
+
+    /// Deletes objects with DeleteObjects in batches of 1000, returning the paths deleted and the paths which failed with why
+    ///
+    /// Paths refused by the facade's key settings, and objects S3 refuses to delete, are recorded as failures without affecting the rest of the batch. Errors sending a batch are returned as an error, in which case earlier batches will already have been deleted.
+    async fn delete_batched(
+        &self,
+        paths: &[String],
+    ) -> Result<(Vec<String>, Vec<(String, String)>), StorageError> {
+        let mut deleted = Vec::new();
+        let mut failed = Vec::new();
+        let mut keyed = Vec::new();
+        for path in paths {
+            match self.key(path) {
+                Ok(key) => keyed.push((path, key)),
+                Err(e) => failed.push((path.clone(), e.to_string())),
             }
-            Ok(result) => {
-                let arn = result
-                    .bucket_arn()
-                    .map(String::from)
-                    .unwrap_or_else(|| format!("arn:aws:s3:::{}", name));
+        }
 
-                let facade = S3Facade {
-                    client,
-                    metadata: StoreMetadata {
-                        id: DataStoreId::S3(arn),
-                        name: name.to_string(),
-                        description: description.to_string(),
-                    },
-                };
+        for batch in keyed.chunks(DELETE_BATCH_SIZE) {
+            let identifiers = batch
+                .iter()
+                .map(|(_, key)| ObjectIdentifier::builder().key(key).build())
+                .collect::<Result<Vec<_>, _>>()?;
+            let output = self
+                .client
+                .delete_objects()
+                .bucket(&self.metadata.name)
+                .delete(
+                    Delete::builder()
+                        .set_objects(Some(identifiers))
+                        .quiet(true)
+                        .build()?,
+                )
+                .send()
+                .await?;
 
-                Ok(facade)
+            // Quiet mode only reports failures, so anything not reported was deleted
+            let failures: HashMap<String, String> = output
+                .errors()
+                .iter()
+                .map(|error| {
+                    (
+                        error.key().unwrap_or_default().to_string(),
+                        error.message().unwrap_or("delete failed").to_string(),
+                    )
+                })
+                .collect();
+            for (path, key) in batch {
+                match failures.get(key) {
+                    Some(message) => failed.push(((*path).clone(), message.clone())),
+                    None => deleted.push((*path).clone()),
+                }
             }
         }
+
+        Ok((deleted, failed))
+    }
+
+    /// Maps the error S3 returns for object lock requests on a bucket without object lock to [`StorageError::ExpectationsNotMet`]
+    ///
+    /// S3 reports this as a generic `InvalidRequest`, so it's told apart from other invalid requests by its message.
+    fn object_lock_error<E>(&self, error: SdkError<E>) -> StorageError
+    where
+        E: ProvideErrorMetadata + Error + Send + Sync + 'static,
+    {
+        let lock_missing = error.code() == Some("InvalidRequest")
+            && error.message().is_some_and(|message| {
+                message
+                    .to_lowercase()
+                    .replace(' ', "")
+                    .contains("objectlockconfiguration")
+            });
+
+        if lock_missing {
+            StorageError::ExpectationsNotMet(vec![format!(
+                "bucket {} should have object lock enabled",
+                self.metadata.name
+            )])
+        } else {
+            StorageError::Backend(Box::new(error))
+        }
     }
 
+    /// Heads an already mapped key, keeping the SDK error so callers can tell a missing object from other failures
     async fn get_object_head(
         &self,
-        path: &str,
+        key: &str,
     ) -> Result<HeadObjectOutput, SdkError<HeadObjectError>> {
         let head = self
-            .client
+            .read_client
             .head_object()
             .bucket(&self.metadata.name)
-            .key(path)
+            .key(key)
             .send()
             .await?;
 
@@ -85,85 +4913,339 @@ impl S3Facade {
     }
 }
 
+/// Builds the metadata for an object from its head response
+fn head_metadata(head: &HeadObjectOutput) -> ObjectMetadata {
+    ObjectMetadata {
+        size: head.content_length().unwrap_or_default() as u64,
+        last_modified: head
+            .last_modified()
+            .and_then(|t| SystemTime::try_from(*t).ok()),
+        etag: head.e_tag().map(String::from),
+        storage_class: head.storage_class().map(|c| c.as_str().to_string()),
+        content_type: head.content_type().map(String::from),
+        user_metadata: head.metadata().cloned().unwrap_or_default(),
+    }
+}
+
 impl StorageFacade for S3Facade {
     /// Reads binary data from a file in an S3 Bucket
     ///
     /// # Remarks
     /// Designed to read files from an s3 bucket and return raw binary data. This can be used on larger files, though it will block the thread until the file is read.
-    /// We intend to implement streaming functions for larger files measured in GBs and TBs, after which a size limit will be imposed on the use of this function.
+    /// Objects larger than the facade's read limit, see [`S3Facade::with_max_read_bytes`], fail with [`StorageError::TooLarge`] before any of the body is downloaded. Use [`StorageFacade::read_stream`] for those.
     ///
     /// # Arguments
     /// * `path` - the path of the file to read, using forward slash "/" separators
-    /// * `decrypt` - An optional function which can be parsed in to decrypt raw bytes before they are returned to the calling layer
+    /// * `decrypt` - An optional function which can be parsed in to decrypt raw bytes before they are returned to the calling layer. If omitted, the facade's [`Pipeline`] is applied instead, if it has one.
     ///
     /// # Examples
-    async fn read_data<F>(
-        &self,
-        path: &str,
-        decrypt: Option<F>,
-    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path))]
+    async fn read_data<F>(&self, path: &str, decrypt: Option<F>) -> Result<Vec<u8>, StorageError>
     where
         F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> + Send + Sync,
     {
-        // When ready, call get_file_metadata here to check size before reading
-
-        let data = self
-            .client
-            .get_object()
-            .bucket(&self.metadata.name)
-            .key(path)
-            .send()
-            .await?;
-
-        let bytes = data.body.collect().await?.into_bytes();
+        let bytes = self.read_raw(path).await?;
+        tracing::debug!(bytes = bytes.len(), "read object");
 
         if let Some(decrypt_fn) = decrypt {
             let cleartext = decrypt_fn(&bytes);
             match cleartext {
                 Ok(bytes) => return Ok(bytes),
-                Err(e) => return Err(e.into()),
+                Err(e) => return Err(StorageError::Encryption(e)),
             }
         };
 
-        Ok(Vec::from(bytes))
+        if let Some(pipeline) = &self.pipeline {
+            return Ok(pipeline.decode(&bytes)?);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Opens an object for streaming, without buffering its body in memory
+    ///
+    /// # Remarks
+    /// Bytes are returned as stored, without a decrypt function or the facade's [`Pipeline`] applied, as both work on whole objects.
+    /// The facade's read timeout covers the request up to the response headers, but not reading the body, as a multi-gigabyte body can legitimately take longer than any sensible timeout. Reads are paced by the facade's read rate limit if it has one.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path))]
+    async fn read_stream(&self, path: &str) -> Result<DataReader, StorageError> {
+        let key = self.key(path)?;
+        let request = async {
+            self.read_client
+                .get_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .send()
+                .await
+                .map_err(|e| object_error(&key, e))
+        };
+        let object = self.within(self.timeouts.read, "read", request).await?;
+
+        let chunks =
+            body_chunks(object.body, self.read_rate_limit.clone()).map_err(io::Error::other);
+        Ok(Box::pin(StreamReader::new(chunks)))
+    }
+
+    /// Reads a byte range of an object with a ranged GET, so only the requested bytes are downloaded
+    ///
+    /// # Remarks
+    /// Bytes are returned as stored, without a decrypt function or the facade's [`Pipeline`] applied. An empty range returns no bytes without sending a request.
+    /// S3 cuts a range running past the end of the object short, and rejects one starting at or past the end with a 416, which is returned as an error.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path, start, end))]
+    async fn read_range(
+        &self,
+        path: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<Vec<u8>, StorageError> {
+        if check_range(start, end)? {
+            return Ok(Vec::new());
+        }
+
+        let range = match end {
+            Some(end) => format!("bytes={}-{}", start, end - 1),
+            None => format!("bytes={}-", start),
+        };
+        self.within(self.timeouts.read, "read", async {
+            let key = self.key(path)?;
+            let data = self
+                .read_client
+                .get_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .range(range)
+                .send()
+                .await
+                .map_err(|e| object_error(&key, e))?;
+
+            self.collect_body(data.body).await
+        })
+        .await
     }
 
     /// Writes a byte-slice to an S3 bucket and returns result
     ///
     /// This function does not take ownership, allowing callers to continue using data due to be written, if required.
     /// The tradeoff is that this function adopts the slight overhead of copying referenced data into a vector owned by the function.
-    /// We do this as part of the encrypt operation if an encryption function has been parsed, and as part of the else if one has not.
+    /// We do this as part of the encrypt operation if an encryption function has been parsed, as part of the facade's [`Pipeline`] if it has one, and as part of the else if neither applies.
     /// As with the read_data function, this operation blocks a thread until the file write is complete. Whilst it works with large uploads, we intend to write a streaming or multi-part upload function for files measured in GBs and TBs.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path, bytes = data.len()))]
     async fn write_data<F>(
         &self,
         path: &str,
         data: &[u8],
         encrypt: Option<F>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    ) -> Result<(), StorageError>
     where
         F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> + Send + Sync,
     {
+        let key = self.key(path)?;
+        // Encoded once up front, so retries resend the same bytes rather than running the encrypt function again
+        let data = Bytes::from(if let Some(encrypt_fn) = encrypt {
+            encrypt_fn(data).map_err(StorageError::Encryption)?
+        } else if let Some(pipeline) = &self.pipeline {
+            pipeline.encode(data)?
+        } else {
+            data.to_vec()
+        });
+
+        if !self.coalesce_writes {
+            self.put_encoded(&key, data, &WriteOptions::default())
+                .await?;
+            return Ok(());
+        }
+
+        let id = (key.clone(), Sha256::digest(&data).into());
+        let waiter = {
+            let mut in_flight = self.in_flight_writes.lock().unwrap();
+            match in_flight.get_mut(&id) {
+                Some(waiters) => {
+                    let (sender, receiver) = oneshot::channel();
+                    waiters.push(sender);
+                    Some(receiver)
+                }
+                None => {
+                    in_flight.insert(id.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+        if let Some(waiter) = waiter {
+            return match waiter.await {
+                Ok(result) => result.map_err(StorageError::from),
+                Err(_) => Err("the identical write this one was waiting on was cancelled".into()),
+            };
+        }
+
+        let leader = WriteLeader {
+            in_flight: &self.in_flight_writes,
+            id: Some(id),
+        };
+        let result = self
+            .put_encoded(&key, data, &WriteOptions::default())
+            .await
+            .map(|_| ());
+        leader.finish(&result);
+        result
+    }
+
+    /// Writes an object as [`StorageFacade::write_data`] does, returning the ETag and version id from S3's PutObject response
+    ///
+    /// # Remarks
+    /// The version id is only set on buckets with versioning enabled, and is `None` otherwise. The write is retried and timed out as any other, but never coalesced, as each caller needs the response to its own write.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path, bytes = data.len()))]
+    async fn write_data_returning<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<WriteOutput, StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let key = self.key(path)?;
+        let data = Bytes::from(if let Some(encrypt_fn) = encrypt {
+            encrypt_fn(data).map_err(StorageError::Encryption)?
+        } else if let Some(pipeline) = &self.pipeline {
+            pipeline.encode(data)?
+        } else {
+            data.to_vec()
+        });
+
+        self.put_encoded(&key, data, &WriteOptions::default()).await
+    }
+
+    /// Writes an object with an `If-None-Match: *` header, so S3 itself refuses the write if anything is stored at the key
+    ///
+    /// # Remarks
+    /// S3 answers a write to an existing key with a 412, which is returned as [`StorageError::AlreadyExists`]. The data is encoded as [`StorageFacade::write_data`] encodes it, and the write timeout applies.
+    /// The write is never retried or coalesced, as a retry after a lost response would find the object it had itself written and report it as already existing.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path, bytes = data.len()))]
+    async fn write_data_if_absent<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let key = self.key(path)?;
         let data = if let Some(encrypt_fn) = encrypt {
-            encrypt_fn(data)?
+            encrypt_fn(data).map_err(StorageError::Encryption)?
+        } else if let Some(pipeline) = &self.pipeline {
+            pipeline.encode(data)?
         } else {
             data.to_vec()
         };
 
-        let upload = self
-            .client
-            .put_object()
-            .bucket(&self.metadata.name)
-            .key(path)
-            .body(ByteStream::from(data))
-            .send()
-            .await;
+        let request = async {
+            match self
+                .client
+                .put_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .set_content_type(self.content_type_for(&key, None))
+                .if_none_match("*")
+                .body(ByteStream::from(data))
+                .send()
+                .await
+            {
+                Ok(_) => Ok(()),
+                Err(e) if e.raw_response().map(|r| r.status().as_u16()) == Some(412) => {
+                    Err(StorageError::AlreadyExists(key.clone()))
+                }
+                Err(e) => Err(object_error(&key, e)),
+            }
+        };
+        self.within(self.timeouts.write, "write", request).await
+    }
 
-        if let Err(e) = upload {
-            // ToDo put some error logging code here with tracing
-            return Err(e.into());
-        }
+    /// Reads the object and writes it back with the data on the end, as S3 has no append
+    ///
+    /// # Remarks
+    /// The write back is conditional on the ETag the read returned, so an object changed in between fails with [`StorageError::Conflict`], as does an object created between a read finding nothing and the write. Nothing is lost either way, and the append can be retried.
+    /// Each append costs a read and a write of the whole object, so this suits small, infrequently appended files. The facade's pipeline is applied to the whole object, and the write timeout covers both requests.
+    /// An object already over [`S3Facade::with_max_read_bytes`] fails with [`StorageError::TooLarge`] before it is downloaded.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path))]
+    async fn append(&self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+        let key = self.key(path)?;
+        let appended = async {
+            let read = self
+                .read_client
+                .get_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .send()
+                .await;
+            let (mut contents, etag) = match read {
+                Ok(object) => {
+                    self.check_read_limit(&key, &object)?;
+                    let etag = object.e_tag().map(String::from);
+                    let stored = self.collect_body(object.body).await?;
+                    let contents = match &self.pipeline {
+                        Some(pipeline) => pipeline.decode(&stored)?,
+                        None => stored,
+                    };
+                    (contents, etag)
+                }
+                Err(e) => {
+                    let error = object_error(&key, e);
+                    if !matches!(error, StorageError::NotFound(_)) {
+                        return Err(error);
+                    }
+                    (Vec::new(), None)
+                }
+            };
 
-        Ok(())
+            contents.extend_from_slice(data);
+            let contents = match &self.pipeline {
+                Some(pipeline) => pipeline.encode(&contents)?,
+                None => contents,
+            };
+            let request = self
+                .client
+                .put_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .set_content_type(self.content_type_for(&key, None))
+                .body(ByteStream::from(contents));
+            let request = match &etag {
+                Some(etag) => request.if_match(etag),
+                None => request.if_none_match("*"),
+            };
+
+            match request.send().await {
+                Ok(_) => Ok(()),
+                // 409 is S3 reporting a concurrent conditional write which beat this one
+                Err(e)
+                    if matches!(
+                        e.raw_response().map(|r| r.status().as_u16()),
+                        Some(409 | 412)
+                    ) =>
+                {
+                    Err(StorageError::Conflict {
+                        key: key.clone(),
+                        expected_etag: etag.unwrap_or_default(),
+                    })
+                }
+                Err(e) => Err(object_error(&key, e)),
+            }
+        };
+        self.within(self.timeouts.write, "append", appended).await
+    }
+
+    /// Writes everything a reader produces to an object, in parts of the facade's part size once it outgrows a single PUT
+    ///
+    /// # Remarks
+    /// This is [`S3Facade::write_from_reader`] without a known length, so large objects are uploaded without ever being held in memory whole, and a failed part aborts the upload rather than leaving orphaned parts.
+    /// Bytes are written as read, without the facade's [`Pipeline`] applied, matching [`StorageFacade::read_stream`].
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path))]
+    async fn write_stream<R>(&self, path: &str, reader: R) -> Result<(), StorageError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        self.write_from_reader(path, reader, None).await
     }
 
     /// Lists objects with a given prefix in an S3 bucket, returned in lexicographical alphabetical order
@@ -171,92 +5253,171 @@ impl StorageFacade for S3Facade {
     /// Callers note that due to the nature of bucket storage, flat structure means this function will list all objects in all contained directories within the specified directory
     /// For speed, we are electing to keep this as is for now, so you may need to filter your output lists.
     /// either that, or it will save you a few extra cpu cycles for recursive listings down the tree.
-    async fn list_objects(
+    /// Prefixes match literally unless [`S3Facade::with_prefix_as_directory`] is on, so `logs` also matches `logs-archive/...` by default.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, prefix = dir_path))]
+    async fn list_objects(&self, dir_path: &str) -> Result<Vec<String>, StorageError> {
+        let mut keys: Vec<String> = self
+            .list_all_objects(dir_path)
+            .await?
+            .iter()
+            .filter_map(|object| object.key().map(String::from))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Lists objects with the size, last modified time, ETag and storage class ListObjectsV2 returns for each, so no head request is made per object
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, prefix = dir_path))]
+    async fn list_objects_detailed(
         &self,
         dir_path: &str,
-    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let request = self
-            .client
-            .list_objects_v2()
-            .bucket(&self.metadata.name)
-            .prefix(dir_path)
-            .into_paginator()
-            .send();
+    ) -> Result<Vec<ObjectEntry>, StorageError> {
+        let mut entries: Vec<ObjectEntry> = self
+            .list_all_objects(dir_path)
+            .await?
+            .iter()
+            .map(object_entry)
+            .collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(entries)
+    }
 
-        // Takes each optional pagination object, and unwraps it into a vector of pagination objects.
-        let pages: Vec<ListObjectsV2Output> = request
-            .collect::<Result<Vec<ListObjectsV2Output>, SdkError<ListObjectsV2Error>>>()
-            .await?;
+    /// Lists a single level of a directory, passing a `/` delimiter to ListObjectsV2 so S3 rolls deeper keys up into their subdirectories
+    ///
+    /// # Remarks
+    /// Subdirectories are S3's common prefixes, so each costs one entry in the listing however many objects it holds, and only the directory's own level is paged through.
+    /// Files outside the facade's [`KeyPolicy`] are left out, while subdirectories are listed as S3 reports them. With [`S3Facade::with_hashed_partitions`] on, a directory's keys are scattered across partitions, so the whole tree under it is listed and rolled up instead.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, prefix = dir_path))]
+    async fn list_directory(&self, dir_path: &str) -> Result<DirectoryListing, StorageError> {
+        let prefix = as_directory(&self.prefix_key(dir_path));
+        if self.hashed_partitions {
+            let keys = self.list_objects(&prefix).await?;
+            return Ok(DirectoryListing::from_keys(&prefix, keys));
+        }
 
-        let mut keys: Vec<String> = Vec::new();
-        for p in pages {
-            for object in p.contents() {
-                if let Some(key) = object.key() {
-                    keys.push(key.to_string());
+        self.within(self.timeouts.list, "list", async {
+            let mut listing = DirectoryListing::default();
+            let mut continuation_token = None;
+            loop {
+                let page = self
+                    .list_objects_page(&prefix, continuation_token, None, Some("/"))
+                    .await?;
+                continuation_token = page.next_continuation_token().map(String::from);
+
+                for object in page.contents() {
+                    let Some(key) = object.key() else {
+                        continue;
+                    };
+                    // A key equal to the prefix is a directory marker rather than a file inside it
+                    if key != prefix && self.key_policy.allows(key) {
+                        listing.files.push(key.to_string());
+                    }
+                }
+                listing.directories.extend(
+                    page.common_prefixes()
+                        .iter()
+                        .filter_map(|common| common.prefix().map(String::from)),
+                );
+
+                if continuation_token.is_none() {
+                    break;
                 }
+                self.pause_between_pages().await;
             }
-        }
-        keys.sort();
-        Ok(keys)
+
+            listing.files.sort();
+            listing.directories.sort();
+            Ok::<_, StorageError>(listing)
+        })
+        .await
     }
 
-    async fn list_object_versions(
-        &self,
-        file_path: &str,
-    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let request = self
-            .client
-            .list_object_versions()
-            .bucket(&self.metadata.name)
-            .prefix(file_path)
-            .send()
-            .await?;
+    /// Lists every version of the objects whose keys start with a path, as `{key}?versionId={version id}`
+    ///
+    /// The path is matched as a prefix, so the key is kept alongside each version id to tell apart keys sharing it. Entries are in key order, newest version first, and delete markers are left out.
+    /// Objects written before versioning was enabled have a `null` version id. A bucket without versioning returns each object's single `null` version, and a missing key returns an empty list.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, prefix = file_path))]
+    async fn list_object_versions(&self, file_path: &str) -> Result<Vec<String>, StorageError> {
+        // Refuses a path outside the key policy or limits up front, as a listing would only filter it out
+        self.key(file_path)?;
 
-        let mut versions = request.versions.unwrap();
-        let mut next_key_marker = request.next_key_marker.unwrap_or_default();
-        let mut next_version_id_marker = request.next_version_id_marker.unwrap_or_default();
-        let mut truncated = request.is_truncated.unwrap_or(false);
-        while truncated {
-            let next_request = self
-                .client
-                .list_object_versions()
-                .bucket(&self.metadata.name)
-                .prefix(file_path)
-                .key_marker(&next_key_marker)
-                .version_id_marker(&next_version_id_marker)
-                .send()
-                .await?;
+        let mut versions = Vec::new();
+        self.for_each_version(file_path, |key, version_id, _, is_delete_marker| {
+            if !is_delete_marker {
+                versions.push(format!("{}?versionId={}", key, version_id));
+            }
+        })
+        .await?;
 
-            versions.extend_from_slice(next_request.versions());
-            next_key_marker = next_request.next_key_marker.unwrap_or_default();
-            next_version_id_marker = next_request.next_version_id_marker.unwrap_or_default();
+        Ok(versions)
+    }
 
-            truncated = next_request.is_truncated.unwrap_or(false);
+    /// Deletes an object with delete_object()
+    ///
+    /// S3 answers a delete of a missing key with success, but some S3 compatible stores answer `NoSuchKey` instead, so a [`StorageError::NotFound`] is treated as success too and deletes behave the same everywhere.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path))]
+    async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
+        let key = self.key(path)?;
+        let request = async {
+            self.client
+                .delete_object()
+                .bucket(&self.metadata.name)
+                .key(&key)
+                .send()
+                .await
+                .map_err(|e| object_error(&key, e))
+        };
+        match self.within(self.timeouts.delete, "delete", request).await {
+            Err(StorageError::NotFound(_)) => Ok(()),
+            result => result.map(|_deleted| ()),
         }
+    }
 
-        todo!()
+    /// Deletes objects with DeleteObjects, up to 1000 per request, rather than one request each
+    ///
+    /// # Remarks
+    /// Returns each path which couldn't be deleted with the reason why, EG a key refused by the facade's key settings or an object S3 refused to delete, without aborting the rest.
+    /// Errors sending a batch are returned as an error, in which case earlier batches will already have been deleted. As with [`StorageFacade::delete_file`], deleting an object which doesn't exist succeeds.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, count = paths.len()))]
+    async fn delete_files(&self, paths: &[String]) -> Result<Vec<(String, String)>, StorageError> {
+        let (_, failed) = self.delete_batched(paths).await?;
+        Ok(failed)
     }
 
-    async fn delete_file(
-        &self,
-        path: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let _request = self
-            .client
-            .delete_object()
-            .bucket(&self.metadata.name)
-            .key(path)
-            .send()
-            .await?;
+    /// Lists every object under the directory, then deletes them with DeleteObjects in batches of 1000
+    ///
+    /// # Remarks
+    /// The directory is listed in full before anything is deleted, so objects written under it while this runs may survive. If any object can't be deleted, the rest still are, and an error naming each failure is returned.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, prefix = dir_path))]
+    async fn delete_prefix(&self, dir_path: &str) -> Result<(), StorageError> {
+        let keys = self.list_objects(&directory_prefix(dir_path)?).await?;
+        let failed = self.delete_files(&keys).await?;
+        if failed.is_empty() {
+            return Ok(());
+        }
 
-        Ok(())
+        let failures: Vec<String> = failed
+            .iter()
+            .map(|(key, message)| format!("{}: {}", key, message))
+            .collect();
+        Err(format!(
+            "{} of {} objects couldn't be deleted: {}",
+            failed.len(),
+            keys.len(),
+            failures.join("; ")
+        )
+        .into())
     }
 
-    async fn move_file(
-        &self,
-        from: &str,
-        to: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// Copies the object to `to`, then deletes it at `from`
+    ///
+    /// Moving an object onto its own key does nothing, rather than copying it onto itself and then deleting it.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, from, to))]
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        if self.key(from)? == self.key(to)? {
+            return Ok(());
+        }
+
         self.copy_file(from, to).await?;
         self.delete_file(from).await?;
 
@@ -267,31 +5428,123 @@ impl StorageFacade for S3Facade {
     ///
     /// The design choice was taken to keep copy operations within the same bucket, due to the nature of how the AWS SDK expects to work with the copy_source string.
     /// We use the bucket name stored in the struct's metadata prepended to the copy source to fulfill this requirement.
-    /// Eventually, we should look at creating a migrate function which is able to not only work between two S3 buckets, but also be completely backend agnostic, using server side operations when possible and performing carefully managed copies between source and dest when not.
-    async fn copy_file(
-        &self,
-        from: &str,
-        to: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let _request = self
-            .client
-            .copy_object()
-            .copy_source(format!("{}/{}", &self.metadata.name, from))
-            .bucket(&self.metadata.name)
-            .key(to)
-            .send()
-            .await?;
+    /// To copy between buckets, or to and from other backends, use [`crate::storage_facade::migrate`], which falls back to streaming the file between facades when a server side copy isn't possible.
+    /// Copying an object onto its own key does nothing and sends no request, as S3 refuses a copy onto itself which changes nothing. To rewrite an object's metadata or storage class in place, use [`S3Facade::copy_file_with_options`].
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, from, to))]
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        if self.key(from)? == self.key(to)? {
+            return Ok(());
+        }
 
-        Ok(())
+        self.copy_file_with_options(from, to, CopyOptions::default())
+            .await
+    }
+
+    /// Fetches the metadata with head_object(), so it costs a single request and no body is downloaded
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path))]
+    async fn get_file_metadata(&self, path: &str) -> Result<ObjectMetadata, StorageError> {
+        let key = self.key(path)?;
+        let head = self
+            .get_object_head(&key)
+            .await
+            .map_err(|e| object_error(&key, e))?;
+        Ok(head_metadata(&head))
+    }
+
+    /// Checks for an object with head_object(), reporting only a 404 as the object not existing
+    ///
+    /// Every other failure is returned as an error, EG a 403 from missing permissions, a timeout, or a key refused by the facade's key settings, so a transient failure is never mistaken for a free key.
+    /// Note that without `s3:ListBucket` on the bucket, S3 answers a HEAD for a missing object with a 403 rather than a 404, which is returned as an error.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, key = path))]
+    async fn file_exists(&self, path: &str) -> Result<bool, StorageError> {
+        if is_object_lambda_arn(&self.metadata.name) {
+            return self.probe_with_get(path).await;
+        }
+
+        let key = self.key(path)?;
+        match self.get_object_head(&key).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(e) => Err(object_error(&key, e)),
+        }
     }
 
-    async fn file_exists(&self, path: &str) -> bool {
-        let check = self.get_object_head(path).await;
+    /// Lists a single key under the directory with list_objects_v2(), so a prefix holding millions of objects costs one small request
+    ///
+    /// S3 has no real directories, so a prefix only exists while at least one key starts with it. With hashed partitions on, keys for the prefix are spread across the bucket, so full pages are listed until one turns up.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, prefix = dir_path))]
+    async fn prefix_exists(&self, dir_path: &str) -> Result<bool, StorageError> {
+        let page_size = (!self.hashed_partitions).then_some(1);
+        let mut found = false;
+        self.walk_objects(&as_directory(dir_path), page_size, |_| {
+            found = true;
+            ControlFlow::Break(())
+        })
+        .await?;
+
+        Ok(found)
+    }
+
+    /// Hashes a sorted manifest of every key under a prefix, with its size and ETag, into a SHA-256 hex digest
+    ///
+    /// # Remarks
+    /// Object bodies are never downloaded, so this costs one listing of the prefix. Keys are hashed relative to the prefix, so identical datasets under different prefixes share a fingerprint.
+    /// ETags change whenever an object is rewritten with different content, though an identical rewrite keeps its fingerprint, which is usually what cache busting wants.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name, prefix))]
+    async fn prefix_fingerprint(&self, prefix: &str) -> Result<String, StorageError> {
+        let (_, fingerprint) = self.fingerprinted_listing(prefix).await?;
+        Ok(fingerprint)
+    }
+
+    /// Sends a fresh head_bucket(), the same check [`S3Facade::new`] makes, under the facade's read timeout
+    ///
+    /// Bad credentials are reported as [`StorageError::AccessDenied`] and a missing bucket as [`StorageError::NotFound`], both naming the bucket. Access point ARNs are headed as the access point.
+    #[tracing::instrument(level = "debug", skip_all, fields(bucket = %self.metadata.name))]
+    async fn health_check(&self) -> Result<(), StorageError> {
+        let request = self.client.head_bucket().bucket(&self.metadata.name).send();
+        let result =
+            match self.timeouts.read {
+                Some(limit) => tokio::time::timeout(limit, request).await.map_err(|_| {
+                    StorageError::TimedOut {
+                        operation: "health check".to_string(),
+                        after: limit,
+                    }
+                })?,
+                None => request.await,
+            };
 
-        if let Ok(_) = check { true } else { false }
+        let Err(e) = result else {
+            return Ok(());
+        };
+        tracing::error!(error = %DisplayErrorContext(&e), "health check failed");
+        let status = e.raw_response().map(|r| r.status().as_u16());
+        Err(match (status, e.code()) {
+            (_, Some("NoSuchBucket" | "NotFound")) | (Some(404), _) => {
+                StorageError::NotFound(self.metadata.name.clone())
+            }
+            (_, Some("AccessDenied" | "Forbidden")) | (Some(403), _) => {
+                StorageError::AccessDenied(self.metadata.name.clone())
+            }
+            _ => StorageError::Backend(Box::new(e)),
+        })
     }
 
     fn metadata(&self) -> &StoreMetadata {
         &self.metadata
     }
+
+    /// Reports everything as supported, except for directory buckets, which have no versions, object lock or tags
+    ///
+    /// Object Lambda access points are reported the same as buckets, though AWS rejects anything but reads through them, see [`S3Facade::new`].
+    fn capabilities(&self) -> Capabilities {
+        let general_purpose = !self.directory_bucket;
+        Capabilities {
+            versioning: general_purpose,
+            server_side_copy: true,
+            presigned_urls: true,
+            object_lock: general_purpose,
+            tagging: general_purpose,
+            range_reads: true,
+        }
+    }
 }
@@ -1,9 +1,19 @@
 // Contains abstractions for the calling layer to interface with any supported storage backend
 // More to follow ...
 
+use futures::stream::{self, StreamExt};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::error::Error;
+use std::fmt;
 use std::future::Future;
+use std::io;
 use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, ReadBuf};
 
 /// Identifies the data store by backend type and ID / Location
 ///
@@ -11,11 +21,85 @@ use std::path::PathBuf;
 /// This helps the calling layer know if it's dealing with an ARN, a local filesystem path or an Azure Blob URL, without implementing any logic beyond pattern matching the case.
 /// All types that are stored in this enum should be able to be stored and read by tools from the standard library or prelude
 /// So parts of the program who haven't a clue what s3 is should be able to send it to methods that do, saving everyone a headache and halving the coffee budget.
+/// With the `serde` feature on, the case is used as the tag, EG `{"S3": "arn:aws:s3:::my-bucket"}` or `{"Local": "/srv/store"}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataStoreId {
     S3(String),
     Local(PathBuf),
 }
 
+impl DataStoreId {
+    /// Returns the bucket an S3 id names, EG `my-bucket` for `arn:aws:s3:::my-bucket`
+    ///
+    /// `None` for other backends, malformed ARNs and access point ARNs, which name an access point rather than a bucket.
+    pub fn bucket_name(&self) -> Option<&str> {
+        match self {
+            DataStoreId::S3(arn) => parse_s3_arn(arn)?.bucket_name(),
+            DataStoreId::Local(_) => None,
+        }
+    }
+
+    /// Returns the AWS partition an S3 id belongs to, EG `aws`, `aws-us-gov` or `aws-cn`
+    ///
+    /// `None` for other backends and malformed ARNs.
+    pub fn partition(&self) -> Option<&str> {
+        match self {
+            DataStoreId::S3(arn) => Some(parse_s3_arn(arn)?.partition),
+            DataStoreId::Local(_) => None,
+        }
+    }
+}
+
+/// The components of an S3 ARN, as split by [`parse_s3_arn`]
+///
+/// # Parameters:
+/// * partition: AWS partition, EG `aws`, or `aws-us-gov` and `aws-cn` for the GovCloud and China regions.
+/// * service: S3 flavour the ARN belongs to, EG `s3`, `s3express` for directory buckets or `s3-object-lambda`.
+/// * region: Region of the resource, empty for general purpose buckets as their names are global.
+/// * account_id: Owning account, empty for general purpose buckets.
+/// * resource: Everything after the account, EG the bucket name or `accesspoint/{name}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct S3Arn<'a> {
+    pub partition: &'a str,
+    pub service: &'a str,
+    pub region: &'a str,
+    pub account_id: &'a str,
+    pub resource: &'a str,
+}
+
+impl<'a> S3Arn<'a> {
+    /// Returns the bucket the ARN names, or `None` if it names something else, EG an access point
+    pub fn bucket_name(&self) -> Option<&'a str> {
+        match (self.service, self.resource.split_once('/')) {
+            ("s3", None) => Some(self.resource),
+            ("s3express", Some(("bucket", name))) if !name.is_empty() => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Splits an S3 ARN into its components, returning `None` if it isn't a well formed S3 ARN
+///
+/// Any `aws` partition is accepted, so `arn:aws:s3:::bucket`, `arn:aws-us-gov:s3:::bucket` and `arn:aws-cn:s3:::bucket` all parse. Only the services S3 ids are built from, those starting `s3`, are accepted.
+pub fn parse_s3_arn(arn: &str) -> Option<S3Arn<'_>> {
+    let parts: Vec<&str> = arn.splitn(6, ':').collect();
+    let ["arn", partition, service, region, account_id, resource] = parts.as_slice() else {
+        return None;
+    };
+    if !partition.starts_with("aws") || !service.starts_with("s3") || resource.is_empty() {
+        return None;
+    }
+
+    Some(S3Arn {
+        partition,
+        service,
+        region,
+        account_id,
+        resource,
+    })
+}
+
 /// Common metadata for any storage backend
 ///
 /// Note, cargo will bully you if you don't include one of these in your structs.
@@ -27,12 +111,766 @@ pub enum DataStoreId {
 /// * id: Platform specific ID, EG ARN, Azure Blob storage url or B2 ID. In cases of a local FS, this should be a filepath to the root directory of the data store.
 /// * name: Name of the data store. In the case of bucket storage, the name of the bucket. In the case of local fs facades, this should be the name of the data store directory.
 /// *  description: What is this store for, or why does it need to exist. We've elected to make this mandatory for better oversight and auditability.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StoreMetadata {
     pub id: DataStoreId,
     pub name: String,
     pub description: String,
 }
 
+/// Errors a facade can report in a form callers can match on
+///
+/// Every [`StorageFacade`] method returns this, so callers can match on a failure's kind without downcasting.
+/// Missing objects, refused permissions and failing encrypt or decrypt functions are reported as their own variants by every facade, so callers can tell them apart without knowing the backend. Other backend errors are wrapped in [`StorageError::Backend`] as the backend raised them, so they can still be downcast to the backend's own error type.
+#[derive(Debug)]
+pub enum StorageError {
+    /// The backend failed in a way that doesn't map onto a more specific variant
+    Backend(Box<dyn Error + Send + Sync>),
+    /// Nothing is stored at the key
+    NotFound(String),
+    /// The backend refused the operation on the key, EG a 403 from S3 or a permissions error from the filesystem
+    AccessDenied(String),
+    /// Something is already stored at the key, and the operation only writes where nothing is
+    AlreadyExists(String),
+    /// The encrypt or decrypt function given to a read or write failed, with its error as the source
+    Encryption(Box<dyn Error + Send + Sync>),
+    /// The data store doesn't meet the caller's stated expectations, with one entry per expectation that failed
+    ExpectationsNotMet(Vec<String>),
+    /// An object's size falls outside the bounds the caller allowed, with the bound which wasn't set left as `None`
+    SizeOutOfRange {
+        size: u64,
+        min: Option<u64>,
+        max: Option<u64>,
+    },
+    /// An operation didn't complete within the time the facade allows it
+    TimedOut { operation: String, after: Duration },
+    /// An object's bytes couldn't be decoded into the form the caller asked for, EG text
+    Decode(String),
+    /// The key falls outside the facade's [`KeyPolicy`], so the operation was refused before reaching the backend
+    KeyNotAllowed(String),
+    /// The key breaks one of the facade's [`KeyLimits`], with the reason naming the limit exceeded
+    InvalidKey { key: String, reason: String },
+    /// The object no longer matches the version the caller expected, EG its ETag changed since it was listed
+    Conflict { key: String, expected_etag: String },
+    /// The data store can't perform the operation at all, EG versioning on a bucket type without versions, with the reason why
+    Unsupported(String),
+    /// An encrypt and decrypt pair don't undo each other, with the reason naming the sample they failed on
+    Crypto(String),
+    /// The object is archived and a restore has been started or is already running, so it can't be read until the restore completes
+    RestoreInProgress(String),
+    /// The object is larger than the facade will read into memory, so it should be read with [`StorageFacade::read_stream`] instead
+    TooLarge { key: String, size: u64, limit: u64 },
+    /// A range given to [`StorageFacade::read_range`] can't be read from the object, with the reason why
+    InvalidRange(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Backend(e) => write!(f, "backend error: {}", e),
+            StorageError::NotFound(key) => write!(f, "{} was not found", key),
+            StorageError::AccessDenied(key) => write!(f, "access to {} was denied", key),
+            StorageError::AlreadyExists(key) => write!(f, "{} already exists", key),
+            StorageError::Encryption(e) => write!(f, "encryption error: {}", e),
+            StorageError::ExpectationsNotMet(failures) => {
+                write!(f, "expectations not met: {}", failures.join("; "))
+            }
+            StorageError::SizeOutOfRange { size, min, max } => {
+                let bound = |b: &Option<u64>| b.map_or("unbounded".to_string(), |b| b.to_string());
+                write!(
+                    f,
+                    "object size {} is outside the allowed range {} to {}",
+                    size,
+                    bound(min),
+                    bound(max)
+                )
+            }
+            StorageError::TimedOut { operation, after } => {
+                write!(f, "{} timed out after {:?}", operation, after)
+            }
+            StorageError::Decode(reason) => write!(f, "decode error: {}", reason),
+            StorageError::KeyNotAllowed(key) => {
+                write!(f, "key {} is not allowed by the facade's key policy", key)
+            }
+            StorageError::InvalidKey { key, reason } => {
+                write!(f, "invalid key {}: {}", key, reason)
+            }
+            StorageError::Conflict { key, expected_etag } => {
+                write!(
+                    f,
+                    "{} no longer has the expected ETag {}",
+                    key, expected_etag
+                )
+            }
+            StorageError::Unsupported(reason) => write!(f, "unsupported operation: {}", reason),
+            StorageError::Crypto(reason) => write!(f, "crypto error: {}", reason),
+            StorageError::RestoreInProgress(key) => {
+                write!(
+                    f,
+                    "{} is archived and is being restored, try again later",
+                    key
+                )
+            }
+            StorageError::TooLarge { key, size, limit } => {
+                write!(
+                    f,
+                    "{} is {} bytes, over the {} byte limit for reading into memory, use read_stream instead",
+                    key, size, limit
+                )
+            }
+            StorageError::InvalidRange(reason) => write!(f, "invalid range: {}", reason),
+        }
+    }
+}
+
+impl Error for StorageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StorageError::Backend(e) | StorageError::Encryption(e) => Some(e.as_ref()),
+            StorageError::NotFound(_)
+            | StorageError::AccessDenied(_)
+            | StorageError::AlreadyExists(_)
+            | StorageError::ExpectationsNotMet(_)
+            | StorageError::SizeOutOfRange { .. }
+            | StorageError::TimedOut { .. }
+            | StorageError::Decode(_)
+            | StorageError::KeyNotAllowed(_)
+            | StorageError::InvalidKey { .. }
+            | StorageError::Conflict { .. }
+            | StorageError::Unsupported(_)
+            | StorageError::Crypto(_)
+            | StorageError::RestoreInProgress(_)
+            | StorageError::TooLarge { .. }
+            | StorageError::InvalidRange(_) => None,
+        }
+    }
+}
+
+/// Wraps a boxed error as [`StorageError::Backend`], unless it already is a [`StorageError`], which is unwrapped rather than nested
+impl From<Box<dyn Error + Send + Sync>> for StorageError {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        match error.downcast::<StorageError>() {
+            Ok(error) => *error,
+            Err(error) => StorageError::Backend(error),
+        }
+    }
+}
+
+/// Wraps a message as [`StorageError::Backend`], for failures a facade detects itself which no more specific variant describes
+impl From<String> for StorageError {
+    fn from(message: String) -> Self {
+        StorageError::Backend(message.into())
+    }
+}
+
+impl From<&str> for StorageError {
+    fn from(message: &str) -> Self {
+        StorageError::Backend(message.into())
+    }
+}
+
+/// Wraps an IO error as [`StorageError::Backend`], for failures with no key to name, EG reading from a caller's stream
+///
+/// Facades map errors on a particular file to [`StorageError::NotFound`] or [`StorageError::AccessDenied`] themselves, as only they know the key.
+impl From<io::Error> for StorageError {
+    fn from(error: io::Error) -> Self {
+        StorageError::Backend(Box::new(error))
+    }
+}
+
+/// Backend agnostic metadata describing a single stored object
+///
+/// `std::fs::Metadata` can't be constructed outside of the standard library, so facades describe their objects with this instead.
+/// Fields a backend has no concept of are left as `None`.
+///
+/// # Parameters:
+/// * size: Size of the object in bytes.
+/// * last_modified: When the object was last written.
+/// * etag: Backend generated tag which changes whenever the object content does. For S3, this is the object ETag.
+/// * storage_class: Backend specific name of the storage tier holding the object, EG `STANDARD` or `GLACIER`.
+/// * content_type: MIME type the object is served with, EG `application/json`.
+/// * user_metadata: Custom key value pairs stored alongside the object, EG S3's `x-amz-meta-*` headers. Empty where the backend has none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectMetadata {
+    pub size: u64,
+    pub last_modified: Option<SystemTime>,
+    pub etag: Option<String>,
+    pub storage_class: Option<String>,
+    pub content_type: Option<String>,
+    pub user_metadata: HashMap<String, String>,
+}
+
+/// A single object returned by a detailed listing
+///
+/// Carries what a backend returns in its listing responses, so callers rendering sizes or dates needn't fetch metadata object by object.
+///
+/// # Parameters:
+/// * key: Full key of the object, including the listed prefix.
+/// * size: Size of the object in bytes.
+/// * last_modified: When the object was last written.
+/// * etag: Backend generated tag which changes whenever the object content does.
+/// * storage_class: Backend specific name of the storage tier holding the object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectEntry {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: Option<SystemTime>,
+    pub etag: Option<String>,
+    pub storage_class: Option<String>,
+}
+
+/// What a backend reported about the object it created, returned by [`StorageFacade::write_data_returning`]
+///
+/// # Parameters:
+/// * etag: Backend generated tag of the new content, EG for a later conditional write. `None` where the backend has no tags.
+/// * version_id: Id of the version the write created, on a backend keeping versions of each object. `None` where the backend keeps no versions, EG an S3 bucket without versioning.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteOutput {
+    pub etag: Option<String>,
+    pub version_id: Option<String>,
+}
+
+/// Reader over a file's bytes as stored, returned by [`StorageFacade::read_stream`]
+pub type DataReader = Pin<Box<dyn AsyncRead + Send>>;
+
+/// Each path written by [`StorageFacade::write_many`], paired with the result of its write
+pub type WriteResults = Vec<(String, Result<(), StorageError>)>;
+
+/// A sensible number of requests for bulk operations such as [`StorageFacade::write_many`] to have in flight at once, when the caller has no better figure
+pub const DEFAULT_CONCURRENCY: usize = 16;
+
+/// How many bytes a stream moves between calls to its progress callback
+const PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// Wraps a reader to report how many bytes have been read through it, for [`StorageFacade::read_stream_with_progress`] and [`StorageFacade::write_stream_with_progress`]
+///
+/// The callback is given the bytes read so far and the total if known. It's called once every [`PROGRESS_INTERVAL`] bytes rather than on every read, and once more when the reader runs out.
+struct ProgressReader<R> {
+    inner: R,
+    progress: Box<dyn Fn(u64, Option<u64>) + Send>,
+    total: Option<u64>,
+    transferred: u64,
+    reported: u64,
+    finished: bool,
+}
+
+impl<R> ProgressReader<R> {
+    fn new(
+        inner: R,
+        total: Option<u64>,
+        progress: impl Fn(u64, Option<u64>) + Send + 'static,
+    ) -> Self {
+        ProgressReader {
+            inner,
+            progress: Box::new(progress),
+            total,
+            transferred: 0,
+            reported: 0,
+            finished: false,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ProgressReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let before = buf.filled().len();
+        let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(())) = result {
+            let read = (buf.filled().len() - before) as u64;
+            this.transferred += read;
+            // A read of nothing means the reader has run out, which is reported once however often it's polled
+            let ended = read == 0 && !this.finished;
+            if ended || this.transferred - this.reported >= PROGRESS_INTERVAL {
+                this.finished |= ended;
+                this.reported = this.transferred;
+                (this.progress)(this.transferred, this.total);
+            }
+        }
+        result
+    }
+}
+
+/// Checks a range passed to [`StorageFacade::read_range`], returning whether it's empty
+pub(crate) fn check_range(start: u64, end: Option<u64>) -> Result<bool, StorageError> {
+    match end {
+        Some(end) if end < start => Err(StorageError::InvalidRange(format!(
+            "range end {} is before its start {}",
+            end, start
+        ))),
+        Some(end) => Ok(end == start),
+        None => Ok(false),
+    }
+}
+
+/// The immediate contents of a directory, as returned by [`StorageFacade::list_directory`]
+///
+/// # Parameters:
+/// * files: Full paths of the files directly inside the directory, in lexicographical order.
+/// * directories: Full paths of the subdirectories directly inside the directory, each ending in a `/`, in lexicographical order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DirectoryListing {
+    pub files: Vec<String>,
+    pub directories: Vec<String>,
+}
+
+impl DirectoryListing {
+    /// Splits the keys under `prefix` into the files directly inside it and the subdirectories holding the rest
+    pub(crate) fn from_keys(prefix: &str, keys: impl IntoIterator<Item = String>) -> Self {
+        let mut files = Vec::new();
+        let mut directories = BTreeSet::new();
+        for key in keys {
+            let Some(relative) = key.strip_prefix(prefix) else {
+                continue;
+            };
+            match relative.find('/') {
+                Some(index) => {
+                    directories.insert(format!("{}{}", prefix, &relative[..=index]));
+                }
+                // A key equal to the prefix is a directory marker rather than a file inside it
+                None if relative.is_empty() => {}
+                None => files.push(key),
+            }
+        }
+        files.sort();
+
+        DirectoryListing {
+            files,
+            directories: directories.into_iter().collect(),
+        }
+    }
+}
+
+/// Returns a directory path ending in a `/`, leaving an empty path, meaning the root, as it is
+pub(crate) fn as_directory(dir_path: &str) -> String {
+    if dir_path.is_empty() || dir_path.ends_with('/') {
+        dir_path.to_string()
+    } else {
+        format!("{}/", dir_path)
+    }
+}
+
+/// Returns the directory path [`StorageFacade::delete_prefix`] should delete, ending in a `/`, refusing an empty one
+pub(crate) fn directory_prefix(dir_path: &str) -> Result<String, StorageError> {
+    let trimmed = dir_path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Err(StorageError::InvalidKey {
+            key: dir_path.to_string(),
+            reason: "refusing to delete the whole data store".to_string(),
+        });
+    }
+    Ok(format!("{}/", trimmed))
+}
+
+/// Optional features a backend supports, so backend agnostic code can check before relying on one
+///
+/// Each flag describes the data store behind a facade, rather than which methods it implements, so generic code can degrade gracefully, EG listing files instead of their versions.
+///
+/// # Parameters:
+/// * versioning: The store keeps previous versions of overwritten files, which [`StorageFacade::list_object_versions`] can return.
+/// * server_side_copy: Copies and moves run within the store, without the bytes passing through the caller.
+/// * presigned_urls: The store can issue URLs granting temporary access to a file without credentials.
+/// * object_lock: Files can be locked against deletion and overwrites, EG with a retention period or legal hold.
+/// * tagging: Files can carry key value tags alongside their content.
+/// * range_reads: Part of a file can be read without reading the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    pub versioning: bool,
+    pub server_side_copy: bool,
+    pub presigned_urls: bool,
+    pub object_lock: bool,
+    pub tagging: bool,
+    pub range_reads: bool,
+}
+
+/// Source of the current time for facades making time based decisions
+///
+/// Facades default to [`SystemClock`], but tests and simulations can supply their own, so ages can be checked without waiting on the wall clock.
+pub trait Clock: Send + Sync {
+    /// Returns the current time
+    fn now(&self) -> SystemTime;
+}
+
+/// [`Clock`] backed by the operating system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Conditions a caller's cached copy must fail for a conditional read to return data
+///
+/// An object is considered modified if its ETag differs from `etag`, or it was last written after `modified_since`.
+/// Leaving both as `None` makes the read unconditional.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReadPrecondition {
+    pub etag: Option<String>,
+    pub modified_since: Option<SystemTime>,
+}
+
+/// Result of a conditional read
+///
+/// Mirrors the semantics of an HTTP 304, so read-through caches can tell "your copy is still good" apart from fresh data without unwrapping an `Option`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadOutcome {
+    /// The object has changed, so here are its current bytes and metadata
+    Modified(Vec<u8>, ObjectMetadata),
+    /// The caller's copy is still current
+    NotModified,
+}
+
+/// A reversible stage of a [`Pipeline`], such as compression or encryption
+///
+/// `decode` must undo `encode`, so that anything written through a stage can be read back through it.
+pub trait Transform: Send + Sync {
+    /// Applied to data on its way into storage
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+
+    /// Applied to data on its way out of storage
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+}
+
+/// An ordered set of [`Transform`] stages a facade applies to every read and write
+///
+/// Stages are listed in the order they run on read, EG decompress, then decrypt, then validate. Writes run the stages in reverse, so data always comes back out the way it went in.
+/// A facade configured once with a pipeline saves callers chaining closures by hand on every call, which is where the ordering mistakes creep in.
+/// Per call encrypt and decrypt functions take precedence over a facade's pipeline, so a pipeline is only applied when one hasn't been parsed in.
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Arc<dyn Transform>>,
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline, which passes data through untouched
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    /// Adds a stage, which runs after all previously added stages on read and before them on write
+    pub fn stage(mut self, transform: impl Transform + 'static) -> Self {
+        self.stages.push(Arc::new(transform));
+        self
+    }
+
+    /// Runs every stage's `encode` in reverse order, ready for writing
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut data = data.to_vec();
+        for stage in self.stages.iter().rev() {
+            data = stage.encode(&data)?;
+        }
+        Ok(data)
+    }
+
+    /// Runs every stage's `decode` in order, undoing [`Pipeline::encode`]
+    pub fn decode(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut data = data.to_vec();
+        for stage in &self.stages {
+            data = stage.decode(&data)?;
+        }
+        Ok(data)
+    }
+}
+
+/// Checks that `decrypt` undoes `encrypt` over a handful of sample inputs, so a mismatched key or algorithm is caught before any data is written with them
+///
+/// # Remarks
+/// The samples are empty, a short piece of text, every byte value in turn, and 64KiB of repeating bytes, as a pair which handles text can still mangle binary data or fail on empty input.
+/// Nothing is stored, so this is cheap enough to run at startup. The first sample which fails to encrypt, decrypt, or come back identical returns [`StorageError::Crypto`] naming it.
+pub fn verify_crypto_roundtrip<F, G>(encrypt: &F, decrypt: &G) -> Result<(), StorageError>
+where
+    F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>,
+    G: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>,
+{
+    let binary: Vec<u8> = (0..=u8::MAX).collect();
+    let large: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+    let samples: [(&str, &[u8]); 4] = [
+        ("empty", b""),
+        ("text", b"The quick brown fox jumps over the lazy dog"),
+        ("binary", &binary),
+        ("64KiB", &large),
+    ];
+
+    for (name, sample) in samples {
+        let encrypted = encrypt(sample).map_err(|e| {
+            StorageError::Crypto(format!("encrypting the {} sample failed: {}", name, e))
+        })?;
+        let decrypted = decrypt(&encrypted).map_err(|e| {
+            StorageError::Crypto(format!("decrypting the {} sample failed: {}", name, e))
+        })?;
+        if decrypted != sample {
+            return Err(StorageError::Crypto(format!(
+                "the {} sample didn't survive a round trip, {} bytes in and {} bytes out",
+                name,
+                sample.len(),
+                decrypted.len()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies a file from one facade to another, which may be on different backends, EG from an S3 bucket to a local directory
+///
+/// # Remarks
+/// When both facades report the same [`DataStoreId`] and the store supports server side copies, EG two paths in one S3 bucket, the copy is left to `src` with [`StorageFacade::copy_file`], so the bytes never leave the store. The source facade's key settings are then applied to both paths.
+/// Otherwise the file is streamed from [`StorageFacade::read_stream`] into [`StorageFacade::write_stream`], so it's never held in memory whole, and bytes are copied as stored, so encrypted files remain encrypted.
+/// The source is opened before anything is written, so a missing source returns the error opening it reports, rather than leaving an empty file at the destination.
+pub async fn migrate(
+    src: &impl StorageFacade,
+    src_path: &str,
+    dst: &impl StorageFacade,
+    dst_path: &str,
+) -> Result<(), StorageError> {
+    if src.metadata().id == dst.metadata().id && src.capabilities().server_side_copy {
+        return src.copy_file(src_path, dst_path).await;
+    }
+
+    let reader = src.read_stream(src_path).await?;
+    dst.write_stream(dst_path, reader).await
+}
+
+/// What [`sync_directory`] or [`migrate_prefix`] did to bring the destination in line with the source
+///
+/// # Parameters:
+/// * copied: Files which were missing from the destination or differed in size, and were copied over.
+/// * deleted: Files which were only in the destination, and were deleted. Always 0 unless `delete_extra` was given to [`sync_directory`].
+/// * skipped: Files which were already in the destination with the same size, and were left alone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub copied: usize,
+    pub deleted: usize,
+    pub skipped: usize,
+}
+
+/// Copies every file under a prefix in one facade to a prefix in another, which may be on different backends, EG to move a dataset from S3 to a local directory
+///
+/// # Remarks
+/// Each file is copied with [`migrate`] to the same relative path under `dst_prefix`. Prefixes are stripped and prepended exactly as given, as with [`sync_directory`], so pass ones ending in a `/`.
+/// With `skip_existing`, the destination is listed first and a file already there with the same size is left alone, so running an interrupted migration again only copies what it hadn't reached. As with [`sync_directory`], size is the only check, so a file changed without changing size isn't copied again.
+/// Without it, every file is copied whether the destination has it or not. Files are copied one at a time and the first error is returned, leaving whatever was already copied in place.
+pub async fn migrate_prefix(
+    src: &(impl StorageFacade + Sync),
+    src_prefix: &str,
+    dst: &(impl StorageFacade + Sync),
+    dst_prefix: &str,
+    skip_existing: bool,
+) -> Result<SyncSummary, StorageError> {
+    let source = relative_sizes(src.list_objects_detailed(src_prefix).await?, src_prefix);
+    let destination = match skip_existing {
+        true => relative_sizes(dst.list_objects_detailed(dst_prefix).await?, dst_prefix),
+        false => BTreeMap::new(),
+    };
+
+    copy_changed(src, src_prefix, &source, dst, dst_prefix, &destination).await
+}
+
+/// Makes a directory in one facade match a directory in another, which may be on different backends, EG to back an S3 prefix up to a local directory
+///
+/// # Remarks
+/// Both sides are listed with [`StorageFacade::list_objects_detailed`], and each source file is copied with [`migrate`] unless the destination already has a file at the same relative path with the same size. Size is the only change detection, as it's the one signal every backend lists cheaply, so an edit which keeps a file's size exactly the same isn't picked up.
+/// Prefixes are stripped and prepended exactly as given, as with [`StorageFacade::list_objects_relative`], so pass ones ending in a `/`. Directory marker objects, keys equal to the prefix itself, are ignored.
+/// With `delete_extra`, files under `dst_prefix` with no counterpart in the source are deleted afterwards, making the destination a mirror. Deletes only happen once every copy has succeeded.
+/// Files are processed one at a time and the first error is returned, leaving whatever was already copied in place, so a failed sync can simply be run again.
+pub async fn sync_directory(
+    src: &(impl StorageFacade + Sync),
+    src_prefix: &str,
+    dst: &(impl StorageFacade + Sync),
+    dst_prefix: &str,
+    delete_extra: bool,
+) -> Result<SyncSummary, StorageError> {
+    let source = relative_sizes(src.list_objects_detailed(src_prefix).await?, src_prefix);
+    let destination = relative_sizes(dst.list_objects_detailed(dst_prefix).await?, dst_prefix);
+
+    let mut summary = copy_changed(src, src_prefix, &source, dst, dst_prefix, &destination).await?;
+
+    if delete_extra {
+        for relative in destination.keys().filter(|key| !source.contains_key(*key)) {
+            dst.delete_file(&format!("{}{}", dst_prefix, relative))
+                .await?;
+            summary.deleted += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Maps each listed file to its size, keyed by its path relative to `prefix`, leaving out the directory marker at the prefix itself
+fn relative_sizes(entries: Vec<ObjectEntry>, prefix: &str) -> BTreeMap<String, u64> {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let relative = entry.key.strip_prefix(prefix)?;
+            (!relative.is_empty()).then(|| (relative.to_string(), entry.size))
+        })
+        .collect()
+}
+
+/// Copies each source file with [`migrate`] unless the destination already has one of the same size at the same relative path
+async fn copy_changed(
+    src: &(impl StorageFacade + Sync),
+    src_prefix: &str,
+    source: &BTreeMap<String, u64>,
+    dst: &(impl StorageFacade + Sync),
+    dst_prefix: &str,
+    destination: &BTreeMap<String, u64>,
+) -> Result<SyncSummary, StorageError> {
+    let mut summary = SyncSummary::default();
+    for (relative, size) in source {
+        if destination.get(relative) == Some(size) {
+            summary.skipped += 1;
+            continue;
+        }
+
+        migrate(
+            src,
+            &format!("{}{}", src_prefix, relative),
+            dst,
+            &format!("{}{}", dst_prefix, relative),
+        )
+        .await?;
+        summary.copied += 1;
+    }
+
+    Ok(summary)
+}
+
+/// Controls how a facade treats the case of keys before they reach the backend
+///
+/// Some S3-compatible stores and local filesystems (Windows being the usual suspect) treat keys case-insensitively, so `Foo` and `foo` can collide on one backend and be two distinct objects on another.
+/// `LowerNormalized` lowercases every key a facade is handed, across all operations, so behaviour is uniform regardless of what sits underneath.
+///
+/// **Warning:** switching an existing data store from `Sensitive` to `LowerNormalized` will make any object with uppercase characters in its key unreachable through the facade, and writes may then overwrite objects which previously had distinct keys.
+/// Pick a mode when a store is created and stick with it, or migrate the keys first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseMode {
+    /// Keys are passed to the backend exactly as given. This is the default.
+    #[default]
+    Sensitive,
+    /// Keys are lowercased before every operation.
+    LowerNormalized,
+}
+
+impl CaseMode {
+    /// Applies the mode to a key, returning the key the backend should see
+    pub fn apply(&self, key: &str) -> String {
+        match self {
+            CaseMode::Sensitive => key.to_string(),
+            CaseMode::LowerNormalized => key.to_lowercase(),
+        }
+    }
+}
+
+/// Restricts which keys a facade will operate on, as a guard against a buggy caller reaching into another tenant's data
+///
+/// Patterns are globs, matched against whole keys after the facade's [`CaseMode`] is applied, where `*` doesn't cross a `/` and `**` matches any number of segments, EG `tenant-a/**`.
+/// A key is allowed when it matches at least one allowed pattern, or there are no allowed patterns, and matches no denied pattern, so a denial always wins.
+#[derive(Debug, Clone, Default)]
+pub struct KeyPolicy {
+    allowed: Option<GlobSet>,
+    denied: Option<GlobSet>,
+}
+
+impl KeyPolicy {
+    /// Compiles the allowed and denied patterns, failing if any of them isn't a valid glob
+    pub fn new(allowed: &[&str], denied: &[&str]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(KeyPolicy {
+            allowed: compile_globs(allowed)?,
+            denied: compile_globs(denied)?,
+        })
+    }
+
+    /// Whether the policy allows a key
+    pub fn allows(&self, key: &str) -> bool {
+        self.allowed.as_ref().is_none_or(|set| set.is_match(key))
+            && !self.denied.as_ref().is_some_and(|set| set.is_match(key))
+    }
+
+    /// Returns [`StorageError::KeyNotAllowed`] if the policy doesn't allow a key
+    pub fn check(&self, key: &str) -> Result<(), StorageError> {
+        if self.allows(key) {
+            Ok(())
+        } else {
+            Err(StorageError::KeyNotAllowed(key.to_string()))
+        }
+    }
+}
+
+/// Limits on the shape of keys, so a store never accepts a key that the least capable backend in a fleet couldn't hold
+///
+/// Keys are measured after the facade's [`CaseMode`] is applied, and before any backend specific mapping such as hashed partitions.
+/// Depth counts the segments between slashes, ignoring a trailing slash, so `a/b/c.txt` and `a/b/c/` both have a depth of 3. Limits left as `None` aren't checked.
+///
+/// # Parameters:
+/// * max_key_bytes: Longest key allowed, in bytes of UTF-8. S3 allows 1024, while many filesystems cap a whole path at 260 or 4096.
+/// * max_key_depth: Most segments a key may have.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyLimits {
+    pub max_key_bytes: Option<usize>,
+    pub max_key_depth: Option<usize>,
+}
+
+impl KeyLimits {
+    /// Returns [`StorageError::InvalidKey`] if a key breaks any of the limits
+    pub fn check(&self, key: &str) -> Result<(), StorageError> {
+        let invalid = |reason: String| {
+            Err(StorageError::InvalidKey {
+                key: key.to_string(),
+                reason,
+            })
+        };
+
+        if let Some(max) = self.max_key_bytes
+            && key.len() > max
+        {
+            return invalid(format!(
+                "{} bytes is over the {} byte limit",
+                key.len(),
+                max
+            ));
+        }
+
+        let depth = key.strip_suffix('/').unwrap_or(key).split('/').count();
+        if let Some(max) = self.max_key_depth
+            && depth > max
+        {
+            return invalid(format!(
+                "{} segments is over the {} segment limit",
+                depth, max
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Compiles glob patterns into a set, or `None` when there are no patterns
+fn compile_globs(patterns: &[&str]) -> Result<Option<GlobSet>, Box<dyn Error + Send + Sync>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Passes bytes through unchanged, standing in as the encrypt or decrypt function so a facade stores or returns bytes exactly as given
+fn stored_bytes(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    Ok(bytes.to_vec())
+}
+
 /// Required trait for modules used to read and write directly to long term storage
 pub trait StorageFacade {
     /// Reads binary data from a file at a path, optionally takes a decryption function.
@@ -40,56 +878,440 @@ pub trait StorageFacade {
         &self,
         path: &str,
         decrypt: Option<F>,
-    ) -> impl Future<Output = Result<Vec<u8>, Box<dyn Error + Send + Sync>>> + Send
+    ) -> impl Future<Output = Result<Vec<u8>, StorageError>> + Send
     where
         F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync;
 
+    /// Reads binary data from a file at a path, decrypting it with an async function, EG one fetching a data key from KMS or Vault.
+    ///
+    /// # Remarks
+    /// The bytes are read as stored, without the facade's own decoding, and then handed to `decrypt`, whose failure is returned as [`StorageError::Encryption`].
+    /// The future `decrypt` returns can't borrow the bytes it's given, so copy what it needs before the first await.
+    fn read_data_async<F, Fut>(
+        &self,
+        path: &str,
+        decrypt: F,
+    ) -> impl Future<Output = Result<Vec<u8>, StorageError>> + Send
+    where
+        Self: Sync,
+        F: Fn(&[u8]) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<Vec<u8>, Box<dyn Error + Send + Sync>>> + Send,
+    {
+        async move {
+            let bytes = self.read_data(path, Some(stored_bytes)).await?;
+            decrypt(&bytes).await.map_err(StorageError::Encryption)
+        }
+    }
+
+    /// Opens a file at a path for reading as a stream, so large files can be piped elsewhere without buffering them whole
+    ///
+    /// Decrypt functions work on whole files, so the bytes are returned exactly as stored, and decrypting them is left to the caller.
+    fn read_stream(
+        &self,
+        path: &str,
+    ) -> impl Future<Output = Result<DataReader, StorageError>> + Send;
+
+    /// Opens a file for reading as a stream as [`StorageFacade::read_stream`] does, calling `progress` with the bytes read so far and the file's size as the stream is read
+    ///
+    /// # Remarks
+    /// The size comes from [`StorageFacade::get_file_metadata`], so this costs a request more than [`StorageFacade::read_stream`]. `progress` is called once per MiB read rather than for every chunk, and once more when the stream ends, so it suits driving a progress bar.
+    fn read_stream_with_progress<P>(
+        &self,
+        path: &str,
+        progress: P,
+    ) -> impl Future<Output = Result<DataReader, StorageError>> + Send
+    where
+        Self: Sync,
+        P: Fn(u64, Option<u64>) + Send + 'static,
+    {
+        async move {
+            let total = self.get_file_metadata(path).await?.size;
+            let reader = self.read_stream(path).await?;
+            Ok(Box::pin(ProgressReader::new(reader, Some(total), progress)) as DataReader)
+        }
+    }
+
+    /// Reads the bytes from `start` up to but excluding `end` of a file at a path, or up to the end of the file if `end` is `None`
+    ///
+    /// As with [`StorageFacade::read_stream`], the bytes are returned exactly as stored, as decrypt functions work on whole files.
+    /// A range running past the end of the file is cut short at the end, while a range starting at or past it is an error, as is an `end` before `start`. A range with `end` equal to `start` returns no bytes.
+    fn read_range(
+        &self,
+        path: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<u8>, StorageError>> + Send;
+
     /// Writes binary data to a file at a path, optionally takes an encryption function.
     fn write_data<F>(
         &self,
         path: &str,
         data: &[u8],
         encrypt: Option<F>,
-    ) -> impl Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send
+    ) -> impl Future<Output = Result<(), StorageError>> + Send
     where
         F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync;
 
+    /// Writes binary data to a file at a path as [`StorageFacade::write_data`] does, returning the ETag and version id the backend gave the new object
+    ///
+    /// # Remarks
+    /// Saves a follow up request in flows which record the version written, EG a listing of versions or a head of the object.
+    /// By default this calls [`StorageFacade::write_data`] and reports neither, which suits backends with no ETags or versions, EG a local filesystem. Implementations whose writes return them, EG S3's PutObject, should override this.
+    fn write_data_returning<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> impl Future<Output = Result<WriteOutput, StorageError>> + Send
+    where
+        Self: Sync,
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        async move {
+            self.write_data(path, data, encrypt).await?;
+            Ok(WriteOutput::default())
+        }
+    }
+
+    /// Writes binary data to a file at a path, encrypting it with an async function first, EG one fetching a fresh data key from KMS or Vault.
+    ///
+    /// # Remarks
+    /// `encrypt` runs before anything is written, and its failure is returned as [`StorageError::Encryption`]. Its output is stored as it is, without the facade's own encoding.
+    /// The future `encrypt` returns can't borrow the bytes it's given, so copy what it needs before the first await.
+    fn write_data_async<F, Fut>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: F,
+    ) -> impl Future<Output = Result<(), StorageError>> + Send
+    where
+        Self: Sync,
+        F: Fn(&[u8]) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<Vec<u8>, Box<dyn Error + Send + Sync>>> + Send,
+    {
+        async move {
+            let encrypted = encrypt(data).await.map_err(StorageError::Encryption)?;
+            self.write_data(path, &encrypted, Some(stored_bytes)).await
+        }
+    }
+
+    /// Writes binary data to a file at a path only if nothing is stored there yet, optionally takes an encryption function.
+    ///
+    /// The check and the write are a single atomic step wherever the backend allows, so of two concurrent writers only one succeeds. The other fails with [`StorageError::AlreadyExists`], leaving the first writer's file as it was.
+    fn write_data_if_absent<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> impl Future<Output = Result<(), StorageError>> + Send
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync;
+
+    /// Writes many files at once, returning each path paired with the result of its own write, in the order they were given
+    ///
+    /// # Remarks
+    /// Up to `concurrency` writes are in flight at once, see [`DEFAULT_CONCURRENCY`]. Each goes through [`StorageFacade::write_data`] without an encrypt function, so the facade's own encoding is applied if it has one.
+    /// A failed write is reported alongside its path rather than stopping the rest, so check every result before assuming the batch landed.
+    fn write_many(
+        &self,
+        entries: Vec<(String, Vec<u8>)>,
+        concurrency: usize,
+    ) -> impl Future<Output = WriteResults> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let mut results: Vec<_> = stream::iter(entries.into_iter().enumerate())
+                .map(|(index, (path, data))| async move {
+                    let result = self
+                        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>>(
+                            &path, &data, None,
+                        )
+                        .await;
+                    (index, path, result)
+                })
+                .buffer_unordered(concurrency.max(1))
+                .collect()
+                .await;
+
+            results.sort_by_key(|(index, _, _)| *index);
+            results
+                .into_iter()
+                .map(|(_, path, result)| (path, result))
+                .collect()
+        }
+    }
+
+    /// Appends bytes to the end of a file at a path, creating the file if nothing is stored there yet
+    ///
+    /// # Remarks
+    /// Backends with a native append, EG a local filesystem, append in place. Others, EG S3, read the whole file and write it back on the condition it hasn't changed, so a file another writer changed in between fails with [`StorageError::Conflict`] rather than losing either write, and the append can be retried.
+    /// Either way the file is never left holding only part of what was there before, though a native append interrupted part way can leave part of `data` on the end.
+    fn append(
+        &self,
+        path: &str,
+        data: &[u8],
+    ) -> impl Future<Output = Result<(), StorageError>> + Send;
+
+    /// Writes everything a reader produces to a file at a path, so large files can be written without buffering them whole
+    ///
+    /// Encrypt functions work on whole files, so the bytes are written exactly as read, and encrypting them is left to the caller.
+    fn write_stream<R>(
+        &self,
+        path: &str,
+        reader: R,
+    ) -> impl Future<Output = Result<(), StorageError>> + Send
+    where
+        R: AsyncRead + Unpin + Send;
+
+    /// Writes everything a reader produces to a file as [`StorageFacade::write_stream`] does, calling `progress` with the bytes written so far and `total` as the reader is consumed
+    ///
+    /// # Remarks
+    /// `total` is passed through to `progress` as it is, so give the reader's length if it's known, EG a local file's size, or `None` if not.
+    /// Bytes are counted as they're taken from the reader, so where a backend buffers before sending, EG S3 uploading a part at a time, the count can run up to a part ahead of what has been sent. `progress` is called once per MiB rather than for every read, and once more when the reader runs out.
+    fn write_stream_with_progress<R, P>(
+        &self,
+        path: &str,
+        reader: R,
+        total: Option<u64>,
+        progress: P,
+    ) -> impl Future<Output = Result<(), StorageError>> + Send
+    where
+        Self: Sync,
+        R: AsyncRead + Unpin + Send,
+        P: Fn(u64, Option<u64>) + Send + 'static,
+    {
+        async move {
+            self.write_stream(path, ProgressReader::new(reader, total, progress))
+                .await
+        }
+    }
+
     /// Lists files at a given directory path
     ///
     fn list_objects(
         &self,
         dir_path: &str,
-    ) -> impl Future<Output = Result<Vec<String>, Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl Future<Output = Result<Vec<String>, StorageError>> + Send;
+
+    /// Lists files at a given directory path as [`StorageFacade::list_objects`] does, with `dir_path` stripped from the front of each key
+    ///
+    /// EG listing `tenant-123/docs/` returns `a.txt` and `sub/c.txt` rather than `tenant-123/docs/a.txt` and `tenant-123/docs/sub/c.txt`. The prefix is stripped exactly as given, so pass one ending in a `/` to get keys without a leading slash.
+    /// A key equal to `dir_path` itself, EG a directory marker object, has nothing left once stripped and is skipped.
+    fn list_objects_relative(
+        &self,
+        dir_path: &str,
+    ) -> impl Future<Output = Result<Vec<String>, StorageError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            Ok(self
+                .list_objects(dir_path)
+                .await?
+                .into_iter()
+                .filter_map(|key| key.strip_prefix(dir_path).map(str::to_string))
+                .filter(|relative| !relative.is_empty())
+                .collect())
+        }
+    }
+
+    /// Lists files at a given directory path along with their size, last modified time and storage class, in lexicographical key order
+    ///
+    /// # Remarks
+    /// By default this fetches [`StorageFacade::get_file_metadata`] for each listed key, costing a request per file. Implementations whose listings already carry these details, EG S3's ListObjectsV2, should override this to build the entries from the listing alone.
+    fn list_objects_detailed(
+        &self,
+        dir_path: &str,
+    ) -> impl Future<Output = Result<Vec<ObjectEntry>, StorageError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let mut entries = Vec::new();
+            for key in self.list_objects(dir_path).await? {
+                let metadata = self.get_file_metadata(&key).await?;
+                entries.push(ObjectEntry {
+                    key,
+                    size: metadata.size,
+                    last_modified: metadata.last_modified,
+                    etag: metadata.etag,
+                    storage_class: metadata.storage_class,
+                });
+            }
+            entries.sort_by(|a, b| a.key.cmp(&b.key));
+            Ok(entries)
+        }
+    }
+
+    /// Lists the files and subdirectories directly inside a directory, without descending into the subdirectories
+    ///
+    /// `dir_path` is always treated as a directory, so listing `docs` lists `docs/`, and an empty path lists the root. Suited to file browsers, where [`StorageFacade::list_objects`] would return every file at every depth.
+    /// By default this is derived from [`StorageFacade::list_objects`], so the whole tree under the directory is still listed. Implementations which can list a single level, EG with a delimiter on S3, should override this.
+    fn list_directory(
+        &self,
+        dir_path: &str,
+    ) -> impl Future<Output = Result<DirectoryListing, StorageError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let prefix = as_directory(dir_path);
+            let keys = self.list_objects(&prefix).await?;
+            Ok(DirectoryListing::from_keys(&prefix, keys))
+        }
+    }
 
     /// Lists versions of a file at a filepath, originally intended for buckets but custom filesystem implementations are welcome
     fn list_object_versions(
         &self,
         file_path: &str,
-    ) -> impl Future<Output = Result<Vec<String>, Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl Future<Output = Result<Vec<String>, StorageError>> + Send;
 
     /// Deletes a file at a filepath
-    fn delete_file(
+    ///
+    /// Deleting a file which doesn't exist succeeds, as it does on S3, so a delete is safe to retry. Any other failure, EG a permissions error, is returned as an error.
+    fn delete_file(&self, path: &str) -> impl Future<Output = Result<(), StorageError>> + Send;
+
+    /// Deletes many files at once, returning each path which couldn't be deleted with the reason why
+    ///
+    /// A failure to delete one file doesn't stop the rest from being deleted. Implementations with a batch delete, EG DeleteObjects on S3, should override this, as by default each file is deleted in turn with [`StorageFacade::delete_file`].
+    fn delete_files(
         &self,
-        path: &str,
-    ) -> impl Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send;
+        paths: &[String],
+    ) -> impl Future<Output = Result<Vec<(String, String)>, StorageError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let mut failed = Vec::new();
+            for path in paths {
+                if let Err(e) = self.delete_file(path).await {
+                    failed.push((path.clone(), e.to_string()));
+                }
+            }
+            Ok(failed)
+        }
+    }
+
+    /// Deletes every file under a directory path, at all depths
+    ///
+    /// `dir_path` is always treated as a directory, so deleting `tenant-1` never touches `tenant-10/`. Deleting a directory which doesn't exist succeeds, so this is safe to retry.
+    /// An empty `dir_path` is refused with [`StorageError::InvalidKey`], rather than deleting the whole data store.
+    fn delete_prefix(
+        &self,
+        dir_path: &str,
+    ) -> impl Future<Output = Result<(), StorageError>> + Send;
 
     /// Moves a file from one location to another, both paths must include the filename to facilitate renaming
     fn move_file(
         &self,
         from: &str,
         to: &str,
-    ) -> impl Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl Future<Output = Result<(), StorageError>> + Send;
 
     /// Copies a file from one location to another, both paths must include the filename to facilitate rename on copy
     fn copy_file(
         &self,
         from: &str,
         to: &str,
-    ) -> impl Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl Future<Output = Result<(), StorageError>> + Send;
+
+    /// Returns a file's size, last modified time and version tag, without reading its contents
+    fn get_file_metadata(
+        &self,
+        path: &str,
+    ) -> impl Future<Output = Result<ObjectMetadata, StorageError>> + Send;
+
+    /// Returns when a file was last written, EG to answer conditional HTTP requests with `Last-Modified` and `304 Not Modified`
+    ///
+    /// # Remarks
+    /// Taken from [`StorageFacade::get_file_metadata`], so it costs the same single request, EG a head_object() on S3 or a metadata call on a local filesystem. A missing file is reported as [`StorageError::NotFound`].
+    /// Backends which don't record modification times return [`StorageError::Unsupported`] rather than a made up time.
+    fn last_modified(
+        &self,
+        path: &str,
+    ) -> impl Future<Output = Result<SystemTime, StorageError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            self.get_file_metadata(path)
+                .await?
+                .last_modified
+                .ok_or_else(|| {
+                    StorageError::Unsupported(format!(
+                        "{} has no modification time for {}",
+                        self.metadata().name,
+                        path
+                    ))
+                })
+        }
+    }
 
-    /// Checks if a file exists at a given path, cannot be used for directories
-    fn file_exists(&self, path: &str) -> impl Future<Output = bool> + Send;
+    /// Checks if a file exists at a given path, cannot be used for directories, see [`StorageFacade::prefix_exists`]
+    ///
+    /// Only a backend's not found response is reported as `false`. Any other failure, EG a permissions error or timeout, is returned as an error, so callers can't mistake it for the file being missing.
+    fn file_exists(&self, path: &str) -> impl Future<Output = Result<bool, StorageError>> + Send;
+
+    /// Checks if anything is stored under a directory path, at any depth
+    ///
+    /// # Remarks
+    /// `dir_path` is always treated as a directory, so `tenant-1` doesn't match `tenant-10/`, and an empty `dir_path` asks whether the data store holds anything at all.
+    /// As with bucket storage, a directory only exists while at least one file is stored beneath it, so a directory left empty on a filesystem is reported as `false`.
+    /// The default lists every file under the directory, so implementations with a cheaper way to find the first one are free to override this.
+    fn prefix_exists(
+        &self,
+        dir_path: &str,
+    ) -> impl Future<Output = Result<bool, StorageError>> + Send
+    where
+        Self: Sync,
+    {
+        async move { Ok(!self.list_objects(&as_directory(dir_path)).await?.is_empty()) }
+    }
+
+    /// Lists every distinct directory under a prefix, at all depths, in lexicographical order
+    ///
+    /// Directories are derived from the keys [`StorageFacade::list_objects`] returns, so on bucket storage they are the `/` terminated prefixes keys share, EG `a/b/c.txt` yields `a/` and `a/b/`.
+    /// Only directories below `prefix` are returned, each as a full path including `prefix`. Implementations with a cheaper way to enumerate directories are free to override this.
+    fn list_all_directories(
+        &self,
+        prefix: &str,
+    ) -> impl Future<Output = Result<Vec<String>, StorageError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let mut directories = BTreeSet::new();
+            for key in self.list_objects(prefix).await? {
+                let Some(relative) = key.strip_prefix(prefix) else {
+                    continue;
+                };
+                for (index, _) in relative.match_indices('/') {
+                    directories.insert(format!("{}{}", prefix, &relative[..=index]));
+                }
+            }
+            Ok(directories.into_iter().collect())
+        }
+    }
+
+    /// Summarises the contents of a prefix as a single hex digest, for cache busting and change detection
+    ///
+    /// Implementations should hash a sorted manifest of each object's key, size and version tag (EG an ETag) rather than the bodies, so this stays cheap on large prefixes.
+    /// Identical prefixes produce the same fingerprint, and adding, removing or rewriting any object changes it.
+    fn prefix_fingerprint(
+        &self,
+        prefix: &str,
+    ) -> impl Future<Output = Result<String, StorageError>> + Send;
+
+    /// Checks the data store is reachable and usable with the facade's credentials, EG for a service's readiness endpoint
+    ///
+    /// Meant to be polled, so implementations keep this to a request or two and never list or read files. A missing store or refused credentials are reported as [`StorageError::NotFound`] or [`StorageError::AccessDenied`] where the backend says which.
+    fn health_check(&self) -> impl Future<Output = Result<(), StorageError>> + Send;
 
     /// Returns a reference to the metadata field of the struct
     fn metadata(&self) -> &StoreMetadata;
+
+    /// Reports which optional features the data store behind the facade supports
+    fn capabilities(&self) -> Capabilities;
 }
@@ -0,0 +1,309 @@
+// Provides a facade which layers a fast primary data store over a slower secondary one
+//
+// The typical setup is a hot S3 bucket in front of a cold archive, where most reads are served by the primary and the remainder fall through to the secondary.
+// Both tiers are themselves facades, so any combination of backends can be layered, including a tiered facade as either tier.
+//
+// Writes always go to the primary. Reads, existence checks and listings consult both tiers, with the primary taking precedence wherever the two disagree.
+// Each method below documents its own precedence, as there isn't one rule which makes sense for every operation.
+use crate::storage_facade::{
+    Capabilities, DataReader, ObjectMetadata, StorageError, StorageFacade, StoreMetadata,
+};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use tokio::io::AsyncRead;
+
+/// Reads through a primary facade to a secondary one, optionally promoting objects found in the secondary
+///
+/// A read counts as a miss when the primary's read fails and the primary reports the file doesn't exist, so errors such as a failed decryption are returned rather than masked by the secondary.
+pub struct TieredFacade<P, S> {
+    primary: P,
+    secondary: S,
+    promote_on_miss: bool,
+}
+
+impl<P, S> TieredFacade<P, S>
+where
+    P: StorageFacade + Sync,
+    S: StorageFacade + Sync,
+{
+    /// Layers `primary` over `secondary`
+    ///
+    /// With `promote_on_miss` on, an object read from the secondary is written to the primary, so later reads are served by the faster tier.
+    pub fn new(primary: P, secondary: S, promote_on_miss: bool) -> Self {
+        TieredFacade {
+            primary,
+            secondary,
+            promote_on_miss,
+        }
+    }
+
+    /// Returns the primary tier
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// Returns the secondary tier
+    pub fn secondary(&self) -> &S {
+        &self.secondary
+    }
+
+    /// Checks whether an operation which failed on the primary was a miss, which the secondary should then serve
+    ///
+    /// Only the primary reporting that the file doesn't exist counts, so if the existence check fails too, the original error is returned rather than masked by the secondary.
+    async fn missed_primary(&self, path: &str) -> bool {
+        matches!(self.primary.file_exists(path).await, Ok(false))
+    }
+
+    /// Reads a file from the secondary, promoting it to the primary if configured to
+    ///
+    /// The bytes are promoted as the secondary returns them without a decrypt function, so the primary stores the same form the secondary does.
+    async fn read_secondary(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        let data = self
+            .secondary
+            .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>>(path, None)
+            .await?;
+
+        if self.promote_on_miss {
+            self.primary
+                .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>>(
+                    path, &data, None,
+                )
+                .await?;
+        }
+
+        Ok(data)
+    }
+}
+
+impl<P, S> StorageFacade for TieredFacade<P, S>
+where
+    P: StorageFacade + Sync,
+    S: StorageFacade + Sync,
+{
+    /// Reads from the primary, falling back to the secondary if the primary doesn't have the file
+    async fn read_data<F>(&self, path: &str, decrypt: Option<F>) -> Result<Vec<u8>, StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        let error = match self.primary.read_data(path, decrypt.as_ref()).await {
+            Ok(data) => return Ok(data),
+            Err(e) => e,
+        };
+
+        if !self.missed_primary(path).await {
+            return Err(error);
+        }
+
+        let data = self.read_secondary(path).await?;
+        match decrypt {
+            Some(function) => function(&data).map_err(StorageError::Encryption),
+            None => Ok(data),
+        }
+    }
+
+    /// Streams from the primary, falling back to the secondary on a miss as reads do
+    ///
+    /// Streams from the secondary are never promoted, as that would mean buffering or reading the file twice.
+    async fn read_stream(&self, path: &str) -> Result<DataReader, StorageError> {
+        let error = match self.primary.read_stream(path).await {
+            Ok(reader) => return Ok(reader),
+            Err(e) => e,
+        };
+
+        if !self.missed_primary(path).await {
+            return Err(error);
+        }
+
+        self.secondary.read_stream(path).await
+    }
+
+    /// Reads the range from the primary, falling back to the secondary on a miss as reads do
+    async fn read_range(
+        &self,
+        path: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<Vec<u8>, StorageError> {
+        let error = match self.primary.read_range(path, start, end).await {
+            Ok(data) => return Ok(data),
+            Err(e) => e,
+        };
+
+        if !self.missed_primary(path).await {
+            return Err(error);
+        }
+
+        self.secondary.read_range(path, start, end).await
+    }
+
+    /// Writes to the primary only
+    async fn write_data<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        self.primary.write_data(path, data, encrypt).await
+    }
+
+    /// Writes to the primary only if the file is in neither tier
+    ///
+    /// The primary's write is as atomic as the primary makes it, but the secondary is checked beforehand, so a file written to the secondary in between isn't noticed.
+    async fn write_data_if_absent<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        if self.secondary.file_exists(path).await? {
+            return Err(StorageError::AlreadyExists(path.to_string()));
+        }
+        self.primary.write_data_if_absent(path, data, encrypt).await
+    }
+
+    /// Appends in the primary, first copying the file up from the secondary if only the secondary has it
+    ///
+    /// The copy keeps the appended bytes from landing in a new primary file without the secondary's contents ahead of them.
+    async fn append(&self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+        if !self.primary.file_exists(path).await? && self.secondary.file_exists(path).await? {
+            self.copy_file(path, path).await?;
+        }
+        self.primary.append(path, data).await
+    }
+
+    /// Writes to the primary only, as writes do
+    async fn write_stream<R>(&self, path: &str, reader: R) -> Result<(), StorageError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        self.primary.write_stream(path, reader).await
+    }
+
+    /// Lists files in either tier, merged and returned in lexicographical order without duplicates
+    async fn list_objects(&self, dir_path: &str) -> Result<Vec<String>, StorageError> {
+        let mut keys = self.primary.list_objects(dir_path).await?;
+        keys.extend(self.secondary.list_objects(dir_path).await?);
+        keys.sort();
+        keys.dedup();
+        Ok(keys)
+    }
+
+    /// Lists versions from the primary if it has the file, otherwise from the secondary
+    async fn list_object_versions(&self, file_path: &str) -> Result<Vec<String>, StorageError> {
+        if self.primary.file_exists(file_path).await? {
+            self.primary.list_object_versions(file_path).await
+        } else {
+            self.secondary.list_object_versions(file_path).await
+        }
+    }
+
+    /// Deletes the file from every tier holding it, so a fallback read can't resurrect it
+    async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
+        if self.primary.file_exists(path).await? {
+            self.primary.delete_file(path).await?;
+        }
+        if self.secondary.file_exists(path).await? {
+            self.secondary.delete_file(path).await?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the directory from both tiers, so fallback reads can't resurrect any of it
+    async fn delete_prefix(&self, dir_path: &str) -> Result<(), StorageError> {
+        self.primary.delete_prefix(dir_path).await?;
+        self.secondary.delete_prefix(dir_path).await
+    }
+
+    /// Copies the file to `to` in the primary, then deletes it from every tier at `from`
+    ///
+    /// Moving a file onto its own path does nothing, rather than copying it and then deleting it from every tier.
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        if from == to {
+            return Ok(());
+        }
+
+        self.copy_file(from, to).await?;
+        self.delete_file(from).await
+    }
+
+    /// Copies within the primary if it has the file, otherwise copies the secondary's file into the primary
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        if self.primary.file_exists(from).await? {
+            return self.primary.copy_file(from, to).await;
+        }
+
+        let data = self
+            .secondary
+            .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>>(from, None)
+            .await?;
+        self.primary
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>>(
+                to, &data, None,
+            )
+            .await
+    }
+
+    /// Returns the primary's metadata, falling back to the secondary if the primary doesn't have the file
+    async fn get_file_metadata(&self, path: &str) -> Result<ObjectMetadata, StorageError> {
+        let error = match self.primary.get_file_metadata(path).await {
+            Ok(metadata) => return Ok(metadata),
+            Err(e) => e,
+        };
+
+        if !self.missed_primary(path).await {
+            return Err(error);
+        }
+
+        self.secondary.get_file_metadata(path).await
+    }
+
+    /// Checks the primary, then the secondary
+    async fn file_exists(&self, path: &str) -> Result<bool, StorageError> {
+        Ok(self.primary.file_exists(path).await? || self.secondary.file_exists(path).await?)
+    }
+
+    /// Combines both tiers' fingerprints, so a change in either tier changes the result
+    async fn prefix_fingerprint(&self, prefix: &str) -> Result<String, StorageError> {
+        let primary = self.primary.prefix_fingerprint(prefix).await?;
+        let secondary = self.secondary.prefix_fingerprint(prefix).await?;
+
+        let digest = Sha256::new()
+            .chain_update(primary)
+            .chain_update(b"\n")
+            .chain_update(secondary)
+            .finalize();
+
+        Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    /// Checks the primary, then the secondary, as reads can be served by either
+    async fn health_check(&self) -> Result<(), StorageError> {
+        self.primary.health_check().await?;
+        self.secondary.health_check().await
+    }
+
+    /// Returns the primary's metadata, as the tier which receives writes
+    fn metadata(&self) -> &StoreMetadata {
+        self.primary.metadata()
+    }
+
+    /// Reports a capability only if both tiers have it, as an operation may be served by either
+    fn capabilities(&self) -> Capabilities {
+        let primary = self.primary.capabilities();
+        let secondary = self.secondary.capabilities();
+        Capabilities {
+            versioning: primary.versioning && secondary.versioning,
+            server_side_copy: primary.server_side_copy && secondary.server_side_copy,
+            presigned_urls: primary.presigned_urls && secondary.presigned_urls,
+            object_lock: primary.object_lock && secondary.object_lock,
+            tagging: primary.tagging && secondary.tagging,
+            range_reads: primary.range_reads && secondary.range_reads,
+        }
+    }
+}
@@ -0,0 +1,1041 @@
+//! Tests for LocalFacade
+//!
+//! These tests run against a fresh directory under the system temp directory,
+//! so they need no credentials or network access.
+
+use fallible::local_facade::LocalFacade;
+use fallible::storage_facade::{
+    Capabilities, DEFAULT_CONCURRENCY, DataReader, DataStoreId, DirectoryListing, ObjectEntry,
+    ObjectMetadata, StorageError, StorageFacade, StoreMetadata, SyncSummary, WriteOutput, migrate,
+    migrate_prefix, sync_directory,
+};
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+type NoTransform = fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+
+/// Creates an empty directory unique to one test, along with a facade rooted in it.
+async fn local_store(name: &str) -> (PathBuf, LocalFacade) {
+    let root = std::env::temp_dir().join(format!("fallible-{}-{}", name, uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    let facade = LocalFacade::new(&root, "Local facade test")
+        .await
+        .expect("Failed to create LocalFacade for test");
+    (root, facade)
+}
+
+fn xor(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    Ok(data.iter().map(|byte| byte ^ 0x5a).collect())
+}
+
+#[tokio::test]
+async fn test_new_requires_directory() {
+    let (root, facade) = local_store("new").await;
+    assert!(matches!(&facade.metadata().id, DataStoreId::Local(id) if *id == root));
+    assert_eq!(
+        facade.metadata().name,
+        root.file_name().unwrap().to_str().unwrap()
+    );
+
+    assert!(
+        LocalFacade::new(root.join("missing"), "Missing root")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_write_and_read_round_trip() {
+    let (root, facade) = local_store("round-trip").await;
+
+    facade
+        .write_data::<NoTransform>("nested/dir/plain.txt", b"hello", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(root.join("nested").join("dir").join("plain.txt")).unwrap(),
+        b"hello"
+    );
+    assert_eq!(
+        facade
+            .read_data::<NoTransform>("nested/dir/plain.txt", None)
+            .await
+            .unwrap(),
+        b"hello"
+    );
+
+    // Encrypted bytes are what lands on disk, and decrypt undoes them on the way back
+    facade
+        .write_data("secret.bin", b"hidden", Some(xor))
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(root.join("secret.bin")).unwrap(),
+        xor(b"hidden").unwrap()
+    );
+    assert_eq!(
+        facade.read_data("secret.bin", Some(xor)).await.unwrap(),
+        b"hidden"
+    );
+}
+
+#[tokio::test]
+async fn test_write_data_leaves_no_temp_files() {
+    let (root, facade) = local_store("atomic-write").await;
+
+    facade
+        .write_data::<NoTransform>("dir/file.txt", b"first", None)
+        .await
+        .unwrap();
+    facade
+        .write_data::<NoTransform>("dir/file.txt", b"second", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(root.join("dir").join("file.txt")).unwrap(),
+        b"second"
+    );
+
+    // The temporary file each write renamed into place is gone, leaving only the destination
+    let entries: Vec<_> = std::fs::read_dir(root.join("dir"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(entries, ["file.txt"]);
+
+    // A write which can't be renamed into place, as a directory is in the way, cleans up after itself too
+    assert!(
+        facade
+            .write_data::<NoTransform>("dir", b"blocked", None)
+            .await
+            .is_err()
+    );
+    let entries: Vec<_> = std::fs::read_dir(&root)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(entries, ["dir"]);
+}
+
+#[tokio::test]
+async fn test_temp_files_are_never_listed() {
+    let (root, facade) = local_store("temp-files").await;
+    facade
+        .write_data::<NoTransform>("dir/file.txt", b"original", None)
+        .await
+        .unwrap();
+
+    // As a crash part way through a write would leave behind
+    let orphan = format!(".file.txt.{}.tmp", uuid::Uuid::new_v4());
+    std::fs::create_dir_all(root.join("empty")).unwrap();
+    std::fs::write(root.join("dir").join(&orphan), b"partial").unwrap();
+    std::fs::write(root.join("empty").join(&orphan), b"partial").unwrap();
+
+    assert_eq!(facade.list_objects("").await.unwrap(), ["dir/file.txt"]);
+    assert_eq!(
+        facade.list_directory("dir").await.unwrap().files,
+        ["dir/file.txt"]
+    );
+    assert!(!facade.prefix_exists("empty").await.unwrap());
+
+    // A stream which fails part way leaves the existing file as it was
+    let chunks = futures::stream::iter([
+        Ok(bytes::Bytes::from_static(b"replace")),
+        Err(std::io::Error::other("connection dropped")),
+    ]);
+    assert!(
+        facade
+            .write_stream("dir/file.txt", tokio_util::io::StreamReader::new(chunks))
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        facade
+            .read_data::<NoTransform>("dir/file.txt", None)
+            .await
+            .unwrap(),
+        b"original"
+    );
+    assert_eq!(std::fs::read_dir(root.join("dir")).unwrap().count(), 2);
+}
+
+#[tokio::test]
+async fn test_list_objects_is_flat_and_relative() {
+    let (_root, facade) = local_store("list").await;
+    for key in [
+        "logs/a.txt",
+        "logs/2024/b.txt",
+        "logs-archive/c.txt",
+        "other.txt",
+    ] {
+        facade
+            .write_data::<NoTransform>(key, key.as_bytes(), None)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        facade.list_objects("").await.unwrap(),
+        vec![
+            "logs-archive/c.txt",
+            "logs/2024/b.txt",
+            "logs/a.txt",
+            "other.txt"
+        ]
+    );
+    // Prefixes match literally, as they do in a bucket
+    assert_eq!(
+        facade.list_objects("logs").await.unwrap(),
+        vec!["logs-archive/c.txt", "logs/2024/b.txt", "logs/a.txt"]
+    );
+    assert_eq!(
+        facade.list_objects("logs/").await.unwrap(),
+        vec!["logs/2024/b.txt", "logs/a.txt"]
+    );
+    assert!(facade.list_objects("missing/").await.unwrap().is_empty());
+    assert_eq!(
+        facade.list_object_versions("logs/a").await.unwrap(),
+        vec!["logs/a.txt?versionId=null"]
+    );
+}
+
+#[tokio::test]
+async fn test_move_copy_delete_and_exists() {
+    let (_root, facade) = local_store("move-copy").await;
+    facade
+        .write_data::<NoTransform>("a.txt", b"data", None)
+        .await
+        .unwrap();
+
+    facade.copy_file("a.txt", "copies/b.txt").await.unwrap();
+    facade.move_file("a.txt", "moved/c.txt").await.unwrap();
+    assert!(!facade.file_exists("a.txt").await.unwrap());
+    assert!(facade.file_exists("copies/b.txt").await.unwrap());
+    assert!(facade.file_exists("moved/c.txt").await.unwrap());
+
+    let metadata = facade.get_file_metadata("moved/c.txt").await.unwrap();
+    assert_eq!(metadata.size, 4);
+    assert!(metadata.last_modified.is_some());
+
+    facade.delete_file("moved/c.txt").await.unwrap();
+    assert!(!facade.file_exists("moved/c.txt").await.unwrap());
+    // Deleting a missing file succeeds, as it does on S3
+    facade.delete_file("moved/c.txt").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_keys_cannot_escape_root() {
+    let (_root, facade) = local_store("escape").await;
+
+    for key in ["../escape.txt", "a/../../escape.txt", "/etc/passwd", "a//b"] {
+        let error = facade
+            .write_data::<NoTransform>(key, b"data", None)
+            .await
+            .expect_err("keys outside the root should be refused");
+        assert!(matches!(&error, StorageError::InvalidKey { .. }));
+        assert!(facade.file_exists(key).await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_prefix_fingerprint() {
+    let (_root, first) = local_store("fingerprint-a").await;
+    let (_root, second) = local_store("fingerprint-b").await;
+    first
+        .write_data::<NoTransform>("data/x.txt", b"x", None)
+        .await
+        .unwrap();
+
+    let before = first.prefix_fingerprint("data/").await.unwrap();
+    assert_eq!(before, first.prefix_fingerprint("data/").await.unwrap());
+    assert_ne!(before, second.prefix_fingerprint("data/").await.unwrap());
+
+    first
+        .write_data::<NoTransform>("data/y.txt", b"y", None)
+        .await
+        .unwrap();
+    assert_ne!(before, first.prefix_fingerprint("data/").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_capabilities() {
+    let (_root, facade) = local_store("capabilities").await;
+    let capabilities = facade.capabilities();
+    assert!(!capabilities.versioning);
+    assert!(capabilities.server_side_copy);
+    assert_eq!(
+        capabilities,
+        Capabilities {
+            server_side_copy: true,
+            range_reads: true,
+            ..Capabilities::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_read_stream() {
+    let (_root, facade) = local_store("read-stream").await;
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    facade
+        .write_data("stream.bin", &data, Some(xor))
+        .await
+        .unwrap();
+
+    // Streams return the bytes as stored, leaving decryption to the caller
+    let mut streamed = Vec::new();
+    facade
+        .read_stream("stream.bin")
+        .await
+        .unwrap()
+        .read_to_end(&mut streamed)
+        .await
+        .unwrap();
+    assert_eq!(xor(&streamed).unwrap(), data);
+}
+
+#[tokio::test]
+async fn test_write_stream() {
+    let (root, facade) = local_store("write-stream").await;
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    facade
+        .write_stream("streams/written.bin", data.as_slice())
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(root.join("streams").join("written.bin")).unwrap(),
+        data
+    );
+}
+
+#[tokio::test]
+async fn test_migrate() {
+    let (_root, source) = local_store("migrate-src").await;
+    let (dest_root, dest) = local_store("migrate-dst").await;
+    source
+        .write_data::<NoTransform>("in/file.txt", b"moving house", None)
+        .await
+        .unwrap();
+
+    migrate(&source, "in/file.txt", &dest, "out/file.txt")
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(dest_root.join("out").join("file.txt")).unwrap(),
+        b"moving house"
+    );
+
+    // Within one store, the copy is left to the facade
+    migrate(&source, "in/file.txt", &source, "in/copy.txt")
+        .await
+        .unwrap();
+    assert!(source.file_exists("in/copy.txt").await.unwrap());
+
+    // A missing source fails without creating an empty destination
+    assert!(
+        migrate(&source, "in/missing.txt", &dest, "out/missing.txt")
+            .await
+            .is_err()
+    );
+    assert!(!dest.file_exists("out/missing.txt").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_move_file_onto_itself() {
+    let (_root, facade) = local_store("move-onto-itself").await;
+    facade
+        .write_data::<NoTransform>("keep-me.txt", b"still here", None)
+        .await
+        .unwrap();
+
+    facade
+        .copy_file("keep-me.txt", "keep-me.txt")
+        .await
+        .unwrap();
+    facade
+        .move_file("keep-me.txt", "keep-me.txt")
+        .await
+        .unwrap();
+    assert_eq!(
+        facade
+            .read_data::<NoTransform>("keep-me.txt", None)
+            .await
+            .unwrap(),
+        b"still here"
+    );
+}
+
+#[tokio::test]
+async fn test_directories_are_not_files() {
+    let (_root, facade) = local_store("directories-not-files").await;
+    facade
+        .write_data::<NoTransform>("dir/a.txt", b"a", None)
+        .await
+        .unwrap();
+
+    // Moving a directory's path would move every file beneath it
+    assert!(matches!(
+        facade.move_file("dir", "elsewhere").await,
+        Err(StorageError::NotFound(key)) if key == "dir"
+    ));
+    assert!(matches!(
+        facade.move_file("missing.txt", "elsewhere.txt").await,
+        Err(StorageError::NotFound(_))
+    ));
+
+    // Deleting a directory's path is deleting a missing file, so it succeeds and leaves the directory alone
+    facade.delete_file("dir").await.unwrap();
+    assert_eq!(
+        facade
+            .read_data::<NoTransform>("dir/a.txt", None)
+            .await
+            .unwrap(),
+        b"a"
+    );
+}
+
+#[tokio::test]
+async fn test_sync_directory() {
+    let (_root, source) = local_store("sync-src").await;
+    let (_dest_root, dest) = local_store("sync-dst").await;
+    for (path, data) in [
+        ("site/index.html", &b"<h1>home</h1>"[..]),
+        ("site/css/main.css", b"body {}"),
+        ("site/about.html", b"<h1>about us</h1>"),
+    ] {
+        source
+            .write_data::<NoTransform>(path, data, None)
+            .await
+            .unwrap();
+    }
+    for (path, data) in [
+        ("backup/index.html", &b"<h1>home</h1>"[..]),
+        ("backup/about.html", b"<h1>about</h1>"),
+        ("backup/old.html", b"gone"),
+    ] {
+        dest.write_data::<NoTransform>(path, data, None)
+            .await
+            .unwrap();
+    }
+
+    let summary = sync_directory(&source, "site/", &dest, "backup/", false)
+        .await
+        .unwrap();
+    assert_eq!(
+        summary,
+        SyncSummary {
+            copied: 2,
+            deleted: 0,
+            skipped: 1,
+        }
+    );
+    assert_eq!(
+        dest.read_data::<NoTransform>("backup/about.html", None)
+            .await
+            .unwrap(),
+        b"<h1>about us</h1>"
+    );
+    assert!(dest.file_exists("backup/css/main.css").await.unwrap());
+    assert!(dest.file_exists("backup/old.html").await.unwrap());
+
+    // Once in sync, only the extra file is left to deal with
+    let summary = sync_directory(&source, "site/", &dest, "backup/", true)
+        .await
+        .unwrap();
+    assert_eq!(
+        summary,
+        SyncSummary {
+            copied: 0,
+            deleted: 1,
+            skipped: 3,
+        }
+    );
+    assert!(!dest.file_exists("backup/old.html").await.unwrap());
+}
+
+/// Wraps a LocalFacade, counting the writes made through it so tests can see what a bulk operation transferred.
+struct CountingFacade {
+    inner: LocalFacade,
+    writes: AtomicUsize,
+}
+
+impl StorageFacade for CountingFacade {
+    async fn read_data<F>(&self, path: &str, decrypt: Option<F>) -> Result<Vec<u8>, StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        self.inner.read_data(path, decrypt).await
+    }
+
+    async fn read_stream(&self, path: &str) -> Result<DataReader, StorageError> {
+        self.inner.read_stream(path).await
+    }
+
+    async fn read_range(
+        &self,
+        path: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<Vec<u8>, StorageError> {
+        self.inner.read_range(path, start, end).await
+    }
+
+    async fn write_data<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.inner.write_data(path, data, encrypt).await
+    }
+
+    async fn write_data_if_absent<F>(
+        &self,
+        path: &str,
+        data: &[u8],
+        encrypt: Option<F>,
+    ) -> Result<(), StorageError>
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> + Send + Sync,
+    {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.inner.write_data_if_absent(path, data, encrypt).await
+    }
+
+    async fn append(&self, path: &str, data: &[u8]) -> Result<(), StorageError> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.inner.append(path, data).await
+    }
+
+    async fn write_stream<R>(&self, path: &str, reader: R) -> Result<(), StorageError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.inner.write_stream(path, reader).await
+    }
+
+    async fn list_objects(&self, dir_path: &str) -> Result<Vec<String>, StorageError> {
+        self.inner.list_objects(dir_path).await
+    }
+
+    async fn list_objects_detailed(
+        &self,
+        dir_path: &str,
+    ) -> Result<Vec<ObjectEntry>, StorageError> {
+        self.inner.list_objects_detailed(dir_path).await
+    }
+
+    async fn list_object_versions(&self, file_path: &str) -> Result<Vec<String>, StorageError> {
+        self.inner.list_object_versions(file_path).await
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
+        self.inner.delete_file(path).await
+    }
+
+    async fn delete_prefix(&self, dir_path: &str) -> Result<(), StorageError> {
+        self.inner.delete_prefix(dir_path).await
+    }
+
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.inner.move_file(from, to).await
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.inner.copy_file(from, to).await
+    }
+
+    async fn get_file_metadata(&self, path: &str) -> Result<ObjectMetadata, StorageError> {
+        self.inner.get_file_metadata(path).await
+    }
+
+    async fn file_exists(&self, path: &str) -> Result<bool, StorageError> {
+        self.inner.file_exists(path).await
+    }
+
+    async fn prefix_fingerprint(&self, prefix: &str) -> Result<String, StorageError> {
+        self.inner.prefix_fingerprint(prefix).await
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        self.inner.health_check().await
+    }
+
+    fn metadata(&self) -> &StoreMetadata {
+        self.inner.metadata()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_prefix_skips_existing() {
+    let (_root, source) = local_store("migrate-prefix-src").await;
+    let (_dest_root, dest) = local_store("migrate-prefix-dst").await;
+    let dest = CountingFacade {
+        inner: dest,
+        writes: AtomicUsize::new(0),
+    };
+    for (path, data) in [
+        ("data/2024.csv", &b"a,b\n1,2"[..]),
+        ("data/2025.csv", b"a,b\n3,4"),
+        ("data/nested/extra.csv", b"a,b\n5,6"),
+    ] {
+        source
+            .write_data::<NoTransform>(path, data, None)
+            .await
+            .unwrap();
+    }
+
+    let summary = migrate_prefix(&source, "data/", &dest, "archive/", true)
+        .await
+        .unwrap();
+    assert_eq!(
+        summary,
+        SyncSummary {
+            copied: 3,
+            deleted: 0,
+            skipped: 0,
+        }
+    );
+    assert_eq!(dest.writes.load(Ordering::SeqCst), 3);
+
+    // Running it again with one new file should only transfer that file
+    source
+        .write_data::<NoTransform>("data/2026.csv", b"a,b\n7,8", None)
+        .await
+        .unwrap();
+    let summary = migrate_prefix(&source, "data/", &dest, "archive/", true)
+        .await
+        .unwrap();
+    assert_eq!(
+        summary,
+        SyncSummary {
+            copied: 1,
+            deleted: 0,
+            skipped: 3,
+        }
+    );
+    assert_eq!(dest.writes.load(Ordering::SeqCst), 4);
+    assert_eq!(
+        dest.read_data::<NoTransform>("archive/2026.csv", None)
+            .await
+            .unwrap(),
+        b"a,b\n7,8"
+    );
+
+    // Without skip_existing, everything is copied again
+    let summary = migrate_prefix(&source, "data/", &dest, "archive/", false)
+        .await
+        .unwrap();
+    assert_eq!(summary.copied, 4);
+    assert_eq!(dest.writes.load(Ordering::SeqCst), 8);
+}
+
+#[tokio::test]
+async fn test_read_range() {
+    let (_root, facade) = local_store("read-range").await;
+    let data: Vec<u8> = (0..64).collect();
+    facade
+        .write_data::<NoTransform>("header.bin", &data, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        facade.read_range("header.bin", 10, Some(20)).await.unwrap(),
+        data[10..20]
+    );
+    assert_eq!(
+        facade.read_range("header.bin", 60, None).await.unwrap(),
+        data[60..]
+    );
+    assert_eq!(
+        facade
+            .read_range("header.bin", 60, Some(100))
+            .await
+            .unwrap(),
+        data[60..]
+    );
+    assert!(facade.read_range("header.bin", 20, Some(10)).await.is_err());
+    assert!(facade.read_range("header.bin", 64, None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_delete_files() {
+    let (_root, facade) = local_store("delete-files").await;
+    for key in ["a.txt", "b.txt", "c.txt"] {
+        facade
+            .write_data::<NoTransform>(key, b"batch", None)
+            .await
+            .unwrap();
+    }
+
+    let failed = facade
+        .delete_files(&[
+            "a.txt".to_string(),
+            "b.txt".to_string(),
+            "../c.txt".to_string(),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].0, "../c.txt");
+    assert!(!facade.file_exists("a.txt").await.unwrap());
+    assert!(!facade.file_exists("b.txt").await.unwrap());
+    assert!(facade.file_exists("c.txt").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_delete_prefix() {
+    let (root, facade) = local_store("delete-prefix").await;
+    for key in [
+        "tenant-1/a.txt",
+        "tenant-1/docs/b.txt",
+        "tenant-1/docs/deep/c.txt",
+        "tenant-10/keep.txt",
+    ] {
+        facade
+            .write_data::<NoTransform>(key, b"tenant data", None)
+            .await
+            .unwrap();
+    }
+
+    facade.delete_prefix("tenant-1").await.unwrap();
+    assert!(facade.list_objects("tenant-1/").await.unwrap().is_empty());
+    assert!(!root.join("tenant-1").exists());
+    assert_eq!(
+        facade.list_objects("").await.unwrap(),
+        vec!["tenant-10/keep.txt"]
+    );
+
+    facade.delete_prefix("tenant-1/").await.unwrap();
+    assert!(facade.delete_prefix("").await.is_err());
+    assert!(root.exists());
+}
+
+#[tokio::test]
+async fn test_prefix_exists() {
+    let (root, facade) = local_store("prefix-exists").await;
+    assert!(!facade.prefix_exists("").await.unwrap());
+
+    facade
+        .write_data::<NoTransform>("parent/child/file.txt", b"nested", None)
+        .await
+        .unwrap();
+    assert!(facade.prefix_exists("").await.unwrap());
+    assert!(facade.prefix_exists("parent").await.unwrap());
+    assert!(facade.prefix_exists("parent/child/").await.unwrap());
+    assert!(!facade.prefix_exists("sibling").await.unwrap());
+    assert!(!facade.prefix_exists("par").await.unwrap());
+    // A file isn't a directory, so nothing is stored beneath it
+    assert!(!facade.prefix_exists("parent/child/file.txt").await.unwrap());
+    // And a directory isn't a file, as a prefix in a bucket isn't an object
+    assert!(!facade.file_exists("parent").await.unwrap());
+    assert!(
+        !facade
+            .file_exists("parent/child/file.txt/more")
+            .await
+            .unwrap()
+    );
+    assert!(facade.file_exists("parent/child/file.txt").await.unwrap());
+
+    // Deleting the file leaves its directories on disk, but as in a bucket the prefix is gone
+    facade.delete_file("parent/child/file.txt").await.unwrap();
+    assert!(root.join("parent").join("child").is_dir());
+    assert!(!facade.prefix_exists("parent").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_list_directory() {
+    let (_root, facade) = local_store("list-directory").await;
+    for key in ["a.txt", "b.txt", "sub/c.txt", "sub/deep/d.txt"] {
+        facade
+            .write_data::<NoTransform>(key, b"listed", None)
+            .await
+            .unwrap();
+    }
+
+    let listing = facade.list_directory("").await.unwrap();
+    assert_eq!(listing.files, vec!["a.txt", "b.txt"]);
+    assert_eq!(listing.directories, vec!["sub/"]);
+
+    let listing = facade.list_directory("sub").await.unwrap();
+    assert_eq!(listing.files, vec!["sub/c.txt"]);
+    assert_eq!(listing.directories, vec!["sub/deep/"]);
+    assert_eq!(
+        facade.list_directory("missing/").await.unwrap(),
+        DirectoryListing::default()
+    );
+}
+
+#[tokio::test]
+async fn test_list_objects_detailed() {
+    let (_root, facade) = local_store("list-detailed").await;
+    facade
+        .write_data::<NoTransform>("logs/b.txt", b"four", None)
+        .await
+        .unwrap();
+    facade
+        .write_data::<NoTransform>("logs/a.txt", b"one", None)
+        .await
+        .unwrap();
+
+    let entries = facade.list_objects_detailed("logs/").await.unwrap();
+    let keys: Vec<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+    assert_eq!(keys, vec!["logs/a.txt", "logs/b.txt"]);
+    assert_eq!(entries[0].size, 3);
+    assert_eq!(entries[1].size, 4);
+    assert!(entries[0].last_modified.is_some());
+    assert_eq!(entries[0].storage_class, None);
+}
+
+#[tokio::test]
+async fn test_write_data_returning() {
+    let (_root, facade) = local_store("write-returning").await;
+
+    // Files have no ETags or versions to report
+    let output = facade
+        .write_data_returning::<NoTransform>("notes.txt", b"written", None)
+        .await
+        .unwrap();
+    assert_eq!(output, WriteOutput::default());
+    assert_eq!(
+        facade
+            .read_data::<NoTransform>("notes.txt", None)
+            .await
+            .unwrap(),
+        b"written"
+    );
+}
+
+#[tokio::test]
+async fn test_last_modified() {
+    let (_root, facade) = local_store("last-modified").await;
+    facade
+        .write_data::<NoTransform>("page.html", b"<h1>hello</h1>", None)
+        .await
+        .unwrap();
+
+    let modified = facade.last_modified("page.html").await.unwrap();
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_else(|e| e.duration());
+    assert!(age < Duration::from_secs(5));
+
+    let error = facade
+        .last_modified("missing.html")
+        .await
+        .expect_err("a missing file has no modification time");
+    assert!(matches!(&error, StorageError::NotFound(_)));
+}
+
+#[tokio::test]
+async fn test_errors_are_typed() {
+    let (_root, facade) = local_store("errors").await;
+
+    let error = facade
+        .read_data::<NoTransform>("missing.txt", None)
+        .await
+        .expect_err("reading a missing file should fail");
+    assert!(matches!(
+        &error,
+        StorageError::NotFound(key) if key == "missing.txt"
+    ));
+    let error = facade
+        .get_file_metadata("missing.txt")
+        .await
+        .expect_err("a missing file has no metadata");
+    assert!(matches!(&error, StorageError::NotFound(_)));
+
+    let failing =
+        |_: &[u8]| -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> { Err("bad key".into()) };
+    let error = facade
+        .write_data("secret.bin", b"hidden", Some(failing))
+        .await
+        .expect_err("a failing encrypt function should fail the write");
+    assert!(matches!(
+        &error,
+        StorageError::Encryption(e) if e.to_string() == "bad key"
+    ));
+    assert!(!facade.file_exists("secret.bin").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_write_data_if_absent() {
+    let (root, facade) = local_store("if-absent").await;
+
+    facade
+        .write_data_if_absent::<NoTransform>("locks/job.lock", b"first", None)
+        .await
+        .unwrap();
+    let error = facade
+        .write_data_if_absent::<NoTransform>("locks/job.lock", b"second", None)
+        .await
+        .expect_err("an existing file should be left alone");
+    assert!(matches!(
+        &error,
+        StorageError::AlreadyExists(key) if key == "locks/job.lock"
+    ));
+    assert_eq!(
+        std::fs::read(root.join("locks").join("job.lock")).unwrap(),
+        b"first"
+    );
+}
+
+#[tokio::test]
+async fn test_stream_progress() {
+    let (_root, facade) = local_store("progress").await;
+    let data: Vec<u8> = (0..3 * 1024 * 1024 + 512)
+        .map(|i| (i % 251) as u8)
+        .collect();
+    let total = data.len() as u64;
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let record = calls.clone();
+    facade
+        .write_stream_with_progress(
+            "progress.bin",
+            data.as_slice(),
+            Some(total),
+            move |transferred, total| record.lock().unwrap().push((transferred, total)),
+        )
+        .await
+        .unwrap();
+    let written = calls.lock().unwrap().clone();
+    // Once per MiB and once at the end, not once per read
+    assert!(written.len() <= 4);
+    assert!(written.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert_eq!(written.last(), Some(&(total, Some(total))));
+
+    calls.lock().unwrap().clear();
+    let record = calls.clone();
+    let mut streamed = Vec::new();
+    facade
+        .read_stream_with_progress("progress.bin", move |transferred, total| {
+            record.lock().unwrap().push((transferred, total))
+        })
+        .await
+        .unwrap()
+        .read_to_end(&mut streamed)
+        .await
+        .unwrap();
+    assert_eq!(streamed, data);
+    let read = calls.lock().unwrap().clone();
+    assert!(read.len() <= 4);
+    assert_eq!(read.last(), Some(&(total, Some(total))));
+}
+
+#[tokio::test]
+async fn test_async_encryption() {
+    let (root, facade) = local_store("async-crypto").await;
+    // Stands in for a function fetching a data key over the network
+    let remote_xor = |data: &[u8]| {
+        let data = data.to_vec();
+        async move {
+            tokio::task::yield_now().await;
+            xor(&data)
+        }
+    };
+
+    facade
+        .write_data_async("envelope.bin", b"sealed", remote_xor)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read(root.join("envelope.bin")).unwrap(),
+        xor(b"sealed").unwrap()
+    );
+    assert_eq!(
+        facade
+            .read_data_async("envelope.bin", remote_xor)
+            .await
+            .unwrap(),
+        b"sealed"
+    );
+
+    let error = facade
+        .read_data_async("envelope.bin", |_: &[u8]| async {
+            Err::<Vec<u8>, Box<dyn Error + Send + Sync>>("key unavailable".into())
+        })
+        .await
+        .expect_err("a failing decrypt function should fail the read");
+    assert!(matches!(&error, StorageError::Encryption(_)));
+}
+
+#[tokio::test]
+async fn test_write_many() {
+    let (_root, facade) = local_store("write-many").await;
+    let mut entries: Vec<(String, Vec<u8>)> = (0..50)
+        .map(|i| (format!("batch/{:02}.txt", i), i.to_string().into_bytes()))
+        .collect();
+    entries.insert(10, ("batch/../escape.txt".to_string(), b"nope".to_vec()));
+
+    let results = facade.write_many(entries, DEFAULT_CONCURRENCY).await;
+    assert_eq!(results.len(), 51);
+    assert_eq!(results[10].0, "batch/../escape.txt");
+    assert!(matches!(
+        results[10].1.as_ref().unwrap_err(),
+        StorageError::InvalidKey { .. }
+    ));
+    assert_eq!(
+        results.iter().filter(|(_, result)| result.is_ok()).count(),
+        50
+    );
+
+    assert_eq!(facade.list_objects("batch/").await.unwrap().len(), 50);
+    assert_eq!(
+        facade
+            .read_data::<NoTransform>("batch/49.txt", None)
+            .await
+            .unwrap(),
+        b"49"
+    );
+}
+
+#[tokio::test]
+async fn test_health_check() {
+    let (root, facade) = local_store("health-check").await;
+    facade
+        .health_check()
+        .await
+        .expect("a fresh root should be healthy");
+    assert!(
+        facade.list_objects("").await.unwrap().is_empty(),
+        "the probe file should be removed"
+    );
+
+    tokio::fs::remove_dir_all(&root).await.unwrap();
+    assert!(matches!(
+        facade.health_check().await,
+        Err(StorageError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_append() {
+    let (_root, facade) = local_store("append").await;
+    facade.append("logs/app.log", b"first\n").await.unwrap();
+    facade.append("logs/app.log", b"second\n").await.unwrap();
+
+    assert_eq!(
+        facade
+            .read_data::<NoTransform>("logs/app.log", None)
+            .await
+            .unwrap(),
+        b"first\nsecond\n"
+    );
+}
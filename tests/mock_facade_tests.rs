@@ -0,0 +1,137 @@
+//! Tests for MockFacade
+//!
+//! Only built with the `testing` feature, EG `cargo test --features testing`.
+#![cfg(feature = "testing")]
+
+use fallible::mock_facade::MockFacade;
+use fallible::storage_facade::{StorageError, StorageFacade};
+use std::error::Error;
+use tokio::io::AsyncReadExt;
+
+type NoTransform = fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+
+#[tokio::test]
+async fn test_read_write_and_list() {
+    let facade = MockFacade::new("mock", "Unit test store")
+        .with_object("reports/b.csv", b"b")
+        .with_object("reports/a.csv", b"a")
+        .with_object("other.txt", b"other");
+
+    facade
+        .write_data(
+            "reports/c.csv",
+            b"plain",
+            Some(|data: &[u8]| Ok(data.to_ascii_uppercase())),
+        )
+        .await
+        .expect("write_data should succeed");
+    assert_eq!(
+        facade
+            .read_data::<NoTransform>("reports/c.csv", None)
+            .await
+            .unwrap(),
+        b"PLAIN"
+    );
+    assert_eq!(
+        facade
+            .read_range("reports/c.csv", 1, Some(3))
+            .await
+            .unwrap(),
+        b"LA"
+    );
+
+    let mut streamed = Vec::new();
+    facade
+        .read_stream("reports/a.csv")
+        .await
+        .unwrap()
+        .read_to_end(&mut streamed)
+        .await
+        .unwrap();
+    assert_eq!(streamed, b"a");
+
+    assert_eq!(
+        facade.list_objects("reports/").await.unwrap(),
+        vec!["reports/a.csv", "reports/b.csv", "reports/c.csv"]
+    );
+    assert!(facade.file_exists("other.txt").await.unwrap());
+    assert!(!facade.file_exists("reports").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_move_copy_and_delete() {
+    let facade = MockFacade::new("mock", "Unit test store").with_object("a.txt", b"data");
+
+    facade.copy_file("a.txt", "b.txt").await.unwrap();
+    facade.move_file("a.txt", "c/a.txt").await.unwrap();
+    assert_eq!(
+        facade.list_objects("").await.unwrap(),
+        vec!["b.txt", "c/a.txt"]
+    );
+
+    facade.delete_prefix("c").await.unwrap();
+    facade.delete_file("missing.txt").await.unwrap();
+    assert_eq!(facade.list_objects("").await.unwrap(), vec!["b.txt"]);
+
+    let error = facade
+        .move_file("a.txt", "d.txt")
+        .await
+        .expect_err("moving a missing file should fail");
+    assert!(matches!(
+        &error,
+        StorageError::NotFound(key) if key == "a.txt"
+    ));
+}
+
+#[tokio::test]
+async fn test_clones_share_objects() {
+    let facade = MockFacade::new("mock", "Unit test store");
+    let clone = facade.clone();
+
+    tokio::spawn(async move {
+        clone
+            .write_data::<NoTransform>("from-task.txt", b"hello", None)
+            .await
+            .unwrap();
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        facade.snapshot().get("from-task.txt"),
+        Some(&b"hello".to_vec())
+    );
+    let error = facade
+        .write_data_if_absent::<NoTransform>("from-task.txt", b"again", None)
+        .await
+        .expect_err("the clone's write should be visible");
+    assert!(matches!(&error, StorageError::AlreadyExists(_)));
+}
+
+#[tokio::test]
+async fn test_list_objects_relative() {
+    let facade = MockFacade::new("mock", "Unit test store")
+        .with_object("tenant-123/docs/", b"")
+        .with_object("tenant-123/docs/a.txt", b"a")
+        .with_object("tenant-123/docs/sub/c.txt", b"c")
+        .with_object("tenant-123/other.txt", b"other");
+
+    assert_eq!(
+        facade
+            .list_objects_relative("tenant-123/docs/")
+            .await
+            .unwrap(),
+        vec!["a.txt", "sub/c.txt"]
+    );
+}
+
+#[tokio::test]
+async fn test_prefix_exists() {
+    let facade =
+        MockFacade::new("mock", "Unit test store").with_object("parent/child/file.txt", b"nested");
+
+    assert!(facade.prefix_exists("parent").await.unwrap());
+    assert!(facade.prefix_exists("parent/child/").await.unwrap());
+    assert!(!facade.prefix_exists("sibling").await.unwrap());
+    assert!(!facade.prefix_exists("par").await.unwrap());
+}
@@ -0,0 +1,99 @@
+//! Tests for the retry helper
+
+use fallible::retry::{is_retryable, with_retry};
+use fallible::storage_facade::StorageError;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+fn timed_out() -> StorageError {
+    StorageError::TimedOut {
+        operation: "read".to_string(),
+        after: Duration::from_secs(1),
+    }
+}
+
+#[tokio::test]
+async fn test_with_retry_retries_until_ok() {
+    let calls = AtomicUsize::new(0);
+
+    let result = with_retry(5, || async {
+        // Fails the first two calls, then succeeds
+        match calls.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => Err(timed_out()),
+            _ => Ok("done"),
+        }
+    })
+    .await
+    .expect("Operation should succeed once it stops failing");
+
+    assert_eq!(result, "done");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_with_retry_fails_fast_on_permanent_errors() {
+    let calls = AtomicUsize::new(0);
+
+    let result: Result<(), _> = with_retry(5, || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err(StorageError::NotFound("missing.txt".to_string()))
+    })
+    .await;
+
+    let error = result.expect_err("NotFound should be returned");
+    assert!(matches!(error, StorageError::NotFound(_)));
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        1,
+        "NotFound shouldn't be retried"
+    );
+}
+
+#[tokio::test]
+async fn test_with_retry_gives_up_after_attempts() {
+    let calls = AtomicUsize::new(0);
+
+    let result: Result<(), _> = with_retry(3, || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err(timed_out())
+    })
+    .await;
+
+    let error = result.expect_err("The last error should be returned once attempts run out");
+    assert!(matches!(error, StorageError::TimedOut { .. }));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_with_retry_runs_at_least_once() {
+    let calls = AtomicUsize::new(0);
+
+    with_retry(0, || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok::<_, StorageError>(())
+    })
+    .await
+    .expect("Operation should run");
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_is_retryable() {
+    assert!(is_retryable(&timed_out()));
+    assert!(is_retryable(&StorageError::Backend(Box::new(
+        io::Error::from(io::ErrorKind::ConnectionReset)
+    ))));
+    assert!(is_retryable(
+        &io::Error::from(io::ErrorKind::Interrupted).into()
+    ));
+
+    assert!(!is_retryable(&StorageError::NotFound("key".to_string())));
+    assert!(!is_retryable(&StorageError::AccessDenied(
+        "key".to_string()
+    )));
+    assert!(!is_retryable(
+        &io::Error::from(io::ErrorKind::NotFound).into()
+    ));
+}
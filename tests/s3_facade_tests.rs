@@ -19,9 +19,23 @@
 
 use aws_config::{self as aws, BehaviorVersion};
 use aws_sdk_s3 as s3;
-use fallible::s3_facade::S3Facade;
-use fallible::storage_facade::StorageFacade;
-use std::sync::LazyLock;
+use aws_sdk_s3::config::interceptors::BeforeTransmitInterceptorContextRef;
+use aws_sdk_s3::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_sdk_s3::error::BoxError;
+use fallible::local_facade::LocalFacade;
+use fallible::s3_facade::{
+    BucketExpectations, ClientConfig, CopyOptions, ListingBackoff, ListingPage, OperationTimeouts,
+    PrefixStats, S3Facade, StoragePricing, TieringRule, VersioningStatus, WriteOptions,
+    WriteRetries, default_retry_classifier, multipart_etag,
+};
+use fallible::storage_facade::{
+    CaseMode, Clock, DEFAULT_CONCURRENCY, DataStoreId, KeyLimits, KeyPolicy, Pipeline, ReadOutcome,
+    ReadPrecondition, StorageError, StorageFacade, Transform, migrate,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncReadExt;
 use tokio::sync::OnceCell;
 use uuid::Uuid;
 
@@ -48,15 +62,14 @@ async fn ensure_bucket_exists() {
                 let mut create_req = client.create_bucket().bucket(TEST_BUCKET_NAME);
 
                 // S3 quirk: us-east-1 rejects LocationConstraint, other regions require it
-                if let Some(ref region_str) = region {
-                    if region_str != "us-east-1" {
-                        let constraint =
-                            s3::types::BucketLocationConstraint::from(region_str.as_str());
-                        let bucket_config = s3::types::CreateBucketConfiguration::builder()
-                            .location_constraint(constraint)
-                            .build();
-                        create_req = create_req.create_bucket_configuration(bucket_config);
-                    }
+                if let Some(ref region_str) = region
+                    && region_str != "us-east-1"
+                {
+                    let constraint = s3::types::BucketLocationConstraint::from(region_str.as_str());
+                    let bucket_config = s3::types::CreateBucketConfiguration::builder()
+                        .location_constraint(constraint)
+                        .build();
+                    create_req = create_req.create_bucket_configuration(bucket_config);
                 }
 
                 create_req
@@ -68,6 +81,28 @@ async fn ensure_bucket_exists() {
         .await;
 }
 
+/// Builds a raw SDK client, for verifying state the facade doesn't expose.
+async fn raw_client() -> s3::Client {
+    let config = aws::load_defaults(BehaviorVersion::v2026_01_12()).await;
+    s3::Client::new(&config)
+}
+
+/// Reads an object's tags straight from S3.
+async fn object_tags(key: &str) -> HashMap<String, String> {
+    raw_client()
+        .await
+        .get_object_tagging()
+        .bucket(TEST_BUCKET_NAME)
+        .key(key)
+        .send()
+        .await
+        .expect("get_object_tagging should succeed")
+        .tag_set()
+        .iter()
+        .map(|tag| (tag.key().to_string(), tag.value().to_string()))
+        .collect()
+}
+
 /// Provides test isolation via unique prefixes within the shared bucket.
 struct S3TestContext {
     prefix: String,
@@ -99,6 +134,29 @@ impl S3TestContext {
     fn path(&self, relative: &str) -> String {
         format!("{}{}", self.prefix, relative)
     }
+
+    /// Writes an unencrypted object at a path relative to the context's prefix.
+    async fn write(&self, relative: &str, data: &[u8]) {
+        self.facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &self.path(relative),
+                data,
+                None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+
+    /// Reads an object at a path relative to the context's prefix, without decryption.
+    async fn read(&self, relative: &str) -> Vec<u8> {
+        self.facade
+            .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &self.path(relative),
+                None,
+            )
+            .await
+            .expect("read_data should succeed")
+    }
 }
 
 #[tokio::test]
@@ -182,7 +240,7 @@ async fn test_file_exists_true_and_false() {
 
     // File should not exist initially
     assert!(
-        !facade.file_exists(&nonexistent_path).await,
+        !facade.file_exists(&nonexistent_path).await.unwrap(),
         "file_exists should return false for nonexistent file"
     );
 
@@ -198,13 +256,13 @@ async fn test_file_exists_true_and_false() {
 
     // Now it should exist
     assert!(
-        facade.file_exists(&existing_path).await,
+        facade.file_exists(&existing_path).await.unwrap(),
         "file_exists should return true for existing file"
     );
 
     // Other file still should not exist
     assert!(
-        !facade.file_exists(&nonexistent_path).await,
+        !facade.file_exists(&nonexistent_path).await.unwrap(),
         "file_exists should still return false for nonexistent file"
     );
 }
@@ -262,7 +320,10 @@ async fn test_delete_file() {
         .expect("write_data should succeed");
 
     // Verify it exists
-    assert!(facade.file_exists(&path).await, "File should exist before deletion");
+    assert!(
+        facade.file_exists(&path).await.unwrap(),
+        "File should exist before deletion"
+    );
 
     // Delete it
     facade
@@ -272,9 +333,15 @@ async fn test_delete_file() {
 
     // Verify it's gone
     assert!(
-        !facade.file_exists(&path).await,
+        !facade.file_exists(&path).await.unwrap(),
         "File should not exist after deletion"
     );
+
+    // Deleting it again finds nothing to delete, which still succeeds
+    facade
+        .delete_file(&path)
+        .await
+        .expect("deleting a missing file should succeed");
 }
 
 #[tokio::test]
@@ -302,13 +369,13 @@ async fn test_move_file() {
 
     // Source should be gone
     assert!(
-        !facade.file_exists(&source_path).await,
+        !facade.file_exists(&source_path).await.unwrap(),
         "Source file should not exist after move"
     );
 
     // Destination should exist
     assert!(
-        facade.file_exists(&dest_path).await,
+        facade.file_exists(&dest_path).await.unwrap(),
         "Destination file should exist after move"
     );
 
@@ -348,11 +415,11 @@ async fn test_copy_file() {
 
     // Both should exist
     assert!(
-        facade.file_exists(&source_path).await,
+        facade.file_exists(&source_path).await.unwrap(),
         "Source file should still exist after copy"
     );
     assert!(
-        facade.file_exists(&dest_path).await,
+        facade.file_exists(&dest_path).await.unwrap(),
         "Destination file should exist after copy"
     );
 
@@ -405,3 +472,4057 @@ async fn test_metadata() {
         _ => panic!("DataStoreId should be S3 variant"),
     }
 }
+
+#[tokio::test]
+async fn test_lower_normalized_keys() {
+    let ctx = S3TestContext::new("lower-normalized").await;
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Case normalisation test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_case_sensitivity(CaseMode::LowerNormalized);
+
+    let content = b"case insensitive content";
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("Foo.txt"),
+            content,
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    assert!(
+        facade.file_exists(&ctx.path("FOO.txt")).await.unwrap(),
+        "Differently cased key should resolve to the same object"
+    );
+
+    let result = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("foo.txt"),
+            None,
+        )
+        .await
+        .expect("read_data should succeed");
+
+    assert_eq!(result, content.to_vec());
+}
+
+#[tokio::test]
+async fn test_prefetch() {
+    let ctx = S3TestContext::new("prefetch").await;
+    let facade = ctx.facade();
+
+    let keys: Vec<String> = ["one.txt", "two.txt", "three.txt"]
+        .iter()
+        .map(|file| ctx.path(file))
+        .collect();
+    for key in &keys {
+        facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                key,
+                key.as_bytes(),
+                None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+
+    let warm_store = S3Facade::new(TEST_BUCKET_NAME, "Prefetch destination")
+        .await
+        .expect("Failed to create destination S3Facade");
+
+    let warmed = facade
+        .prefetch(&keys, &warm_store, 2)
+        .await
+        .expect("prefetch should succeed");
+
+    assert_eq!(warmed, keys.len(), "Every key should be warmed");
+    for key in &keys {
+        assert!(
+            warm_store.file_exists(key).await.unwrap(),
+            "Destination should contain {}",
+            key
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_read_conditional() {
+    let ctx = S3TestContext::new("read-conditional").await;
+    let facade = ctx.facade();
+    let path = ctx.path("cached-file.txt");
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            b"first version",
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    // An unconditional read hands back the current ETag
+    let etag = match facade
+        .read_conditional(&path, ReadPrecondition::default())
+        .await
+        .expect("read_conditional should succeed")
+    {
+        ReadOutcome::Modified(_, metadata) => metadata.etag.expect("S3 should return an ETag"),
+        ReadOutcome::NotModified => panic!("Unconditional read should return data"),
+    };
+
+    let precondition = ReadPrecondition {
+        etag: Some(etag),
+        modified_since: None,
+    };
+
+    // Matching ETag means the cached copy is still good
+    let outcome = facade
+        .read_conditional(&path, precondition.clone())
+        .await
+        .expect("read_conditional should succeed");
+    assert_eq!(outcome, ReadOutcome::NotModified);
+
+    // Overwrite, so the cached ETag is now stale
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            b"second version",
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    match facade
+        .read_conditional(&path, precondition)
+        .await
+        .expect("read_conditional should succeed")
+    {
+        ReadOutcome::Modified(bytes, _) => assert_eq!(bytes, b"second version".to_vec()),
+        ReadOutcome::NotModified => panic!("Stale ETag should return fresh data"),
+    }
+}
+
+#[tokio::test]
+async fn test_list_objects_with_backoff() {
+    let ctx = S3TestContext::new("list-backoff").await;
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Listing backoff test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_listing_backoff(ListingBackoff {
+            page_delay: Duration::from_millis(10),
+            ..ListingBackoff::default()
+        });
+
+    let files = ["file-a.txt", "file-b.txt", "file-c.txt"];
+    for file in &files {
+        facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &ctx.path(file),
+                file.as_bytes(),
+                None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+
+    let listed = facade
+        .list_objects(ctx.prefix())
+        .await
+        .expect("list_objects should succeed");
+
+    assert_eq!(listed.len(), files.len(), "Should list all created files");
+}
+
+#[tokio::test]
+async fn test_finalize_prefix() {
+    let ctx = S3TestContext::new("finalize-prefix").await;
+    let facade = ctx.facade();
+
+    let files = [("a.csv", 10), ("b.csv", 200), ("nested/c.csv", 3000)];
+    for (file, size) in &files {
+        facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &ctx.path(file),
+                &vec![b'x'; *size],
+                None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+
+    let manifest_key = facade
+        .finalize_prefix(ctx.prefix())
+        .await
+        .expect("finalize_prefix should succeed");
+    assert_eq!(manifest_key, ctx.path("_MANIFEST.json"));
+
+    let manifest = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &manifest_key,
+            None,
+        )
+        .await
+        .expect("read_data should succeed");
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&manifest).expect("Manifest should be valid JSON");
+
+    let entries = manifest["objects"]
+        .as_array()
+        .expect("Manifest should list objects");
+    assert_eq!(
+        entries.len(),
+        files.len(),
+        "Manifest should list exactly the written objects"
+    );
+
+    for (file, size) in &files {
+        let entry = entries
+            .iter()
+            .find(|entry| entry["key"] == ctx.path(file).as_str())
+            .unwrap_or_else(|| panic!("Manifest should contain {}", file));
+        assert_eq!(entry["size"], *size as u64);
+        assert!(entry["etag"].is_string(), "Entry should carry a checksum");
+    }
+}
+
+#[tokio::test]
+async fn test_versions_of_missing_key() {
+    let ctx = S3TestContext::new("missing-versions").await;
+    let facade = ctx.facade();
+    let path = ctx.path("never-written.txt");
+
+    let versions = facade
+        .list_object_versions(&path)
+        .await
+        .expect("list_object_versions should succeed on a missing key");
+    assert!(versions.is_empty(), "Missing key should have no versions");
+
+    assert!(
+        !facade
+            .has_any_version(&path)
+            .await
+            .expect("has_any_version should succeed"),
+        "Missing key should report no versions"
+    );
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            b"now it exists",
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    assert!(
+        facade
+            .has_any_version(&path)
+            .await
+            .expect("has_any_version should succeed"),
+        "Written key should report a version"
+    );
+}
+
+#[tokio::test]
+async fn test_move_file_onto_itself() {
+    let ctx = S3TestContext::new("move-onto-itself").await;
+    let facade = ctx.facade();
+    let path = ctx.path("keep-me.txt");
+    ctx.write("keep-me.txt", b"still here").await;
+
+    facade
+        .copy_file(&path, &path)
+        .await
+        .expect("copying a file onto itself should do nothing");
+    facade
+        .move_file(&path, &path)
+        .await
+        .expect("moving a file onto itself should do nothing");
+    assert_eq!(ctx.read("keep-me.txt").await, b"still here");
+}
+
+#[tokio::test]
+async fn test_copy_file_with_special_characters() {
+    let ctx = S3TestContext::new("copy-special").await;
+    let facade = ctx.facade();
+    ctx.write("my folder/файл #1.txt", b"special characters")
+        .await;
+
+    for (from, to) in [
+        ("my folder/файл #1.txt", "copies/a+b?c.txt"),
+        ("copies/a+b?c.txt", "moved/файл #2.txt"),
+    ] {
+        facade
+            .copy_file(&ctx.path(from), &ctx.path(to))
+            .await
+            .expect("copy_file should succeed");
+        assert_eq!(ctx.read(to).await, b"special characters");
+    }
+
+    facade
+        .move_file(
+            &ctx.path("moved/файл #2.txt"),
+            &ctx.path("moved/файл #3.txt"),
+        )
+        .await
+        .expect("move_file should succeed");
+    assert_eq!(ctx.read("moved/файл #3.txt").await, b"special characters");
+    assert!(
+        !facade
+            .file_exists(&ctx.path("moved/файл #2.txt"))
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_move_prefix() {
+    let ctx = S3TestContext::new("move-prefix").await;
+    let facade = ctx.facade();
+    let files = ["readme.txt", "docs/guide.md", "docs/images/logo.png"];
+    for file in files {
+        ctx.write(&format!("old-name/{}", file), file.as_bytes())
+            .await;
+    }
+    ctx.write("old-name-2/untouched.txt", b"sibling").await;
+
+    let moved = facade
+        .move_prefix(&ctx.path("old-name"), &ctx.path("new-name/"))
+        .await
+        .expect("move_prefix should succeed");
+    assert_eq!(moved, files.len());
+    for file in files {
+        assert_eq!(
+            ctx.read(&format!("new-name/{}", file)).await,
+            file.as_bytes()
+        );
+        assert!(
+            !facade
+                .file_exists(&ctx.path(&format!("old-name/{}", file)))
+                .await
+                .unwrap()
+        );
+    }
+    assert!(
+        facade
+            .file_exists(&ctx.path("old-name-2/untouched.txt"))
+            .await
+            .unwrap()
+    );
+
+    // Moving a prefix into itself could overwrite sources before they're moved
+    let error = facade
+        .move_prefix(&ctx.path("new-name/"), &ctx.path("new-name/nested/"))
+        .await
+        .expect_err("overlapping prefixes should be refused");
+    assert!(matches!(&error, StorageError::InvalidKey { .. }));
+}
+
+#[tokio::test]
+async fn test_move_prefix_compares_normalized_prefixes() {
+    let facade = S3Facade::from_client(offline_client(), "mock-bucket", "Move prefix case test")
+        .with_case_sensitivity(CaseMode::LowerNormalized);
+
+    // Only overlapping once lowercased, and refused before any request is sent
+    let error = facade
+        .move_prefix("Data", "data/archive")
+        .await
+        .expect_err("prefixes overlapping once normalized should be refused");
+    assert!(matches!(&error, StorageError::InvalidKey { .. }));
+}
+
+#[tokio::test]
+async fn test_copy_file_replacing_tags() {
+    let ctx = S3TestContext::new("copy-replace-tags").await;
+    let facade = ctx.facade();
+
+    let source_path = ctx.path("tagged-source.txt");
+    let dest_path = ctx.path("tagged-dest.txt");
+
+    raw_client()
+        .await
+        .put_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(&source_path)
+        .body(s3::primitives::ByteStream::from_static(b"tagged content"))
+        .tagging("origin=source")
+        .send()
+        .await
+        .expect("put_object should succeed");
+
+    let new_tags = HashMap::from([
+        ("owner".to_string(), "tenant a".to_string()),
+        ("tier".to_string(), "cold".to_string()),
+    ]);
+
+    facade
+        .copy_file_with_options(
+            &source_path,
+            &dest_path,
+            CopyOptions {
+                tagging_directive: Some(s3::types::TaggingDirective::Replace),
+                tags: new_tags.clone(),
+                ..CopyOptions::default()
+            },
+        )
+        .await
+        .expect("copy_file_with_options should succeed");
+
+    assert_eq!(object_tags(&dest_path).await, new_tags);
+    assert_eq!(
+        object_tags(&source_path).await,
+        HashMap::from([("origin".to_string(), "source".to_string())]),
+        "Source tags should be unchanged"
+    );
+}
+
+#[tokio::test]
+async fn test_move_preserves_metadata() {
+    let ctx = S3TestContext::new("move-preserves-metadata").await;
+    let facade = ctx.facade();
+    let client = raw_client().await;
+    let source_path = ctx.path("report.json");
+    let moved_path = ctx.path("archive/report.json");
+
+    client
+        .put_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(&source_path)
+        .content_type("application/json")
+        .metadata("owner", "tenant-a")
+        .storage_class(s3::types::StorageClass::StandardIa)
+        .body(s3::primitives::ByteStream::from_static(b"{}"))
+        .send()
+        .await
+        .expect("put_object should succeed");
+
+    facade
+        .move_file(&source_path, &moved_path)
+        .await
+        .expect("move_file should succeed");
+
+    let head = client
+        .head_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(&moved_path)
+        .send()
+        .await
+        .expect("head_object should succeed");
+    assert_eq!(head.content_type(), Some("application/json"));
+    assert_eq!(
+        head.metadata()
+            .and_then(|m| m.get("owner"))
+            .map(String::as_str),
+        Some("tenant-a")
+    );
+    assert_eq!(
+        head.storage_class(),
+        Some(&s3::types::StorageClass::StandardIa),
+        "The move shouldn't downgrade the storage class"
+    );
+
+    // Replacing the metadata during a copy
+    let replaced_path = ctx.path("replaced.json");
+    facade
+        .copy_file_with_options(
+            &moved_path,
+            &replaced_path,
+            CopyOptions {
+                metadata_directive: Some(s3::types::MetadataDirective::Replace),
+                content_type: Some("text/plain".to_string()),
+                metadata: HashMap::from([("owner".to_string(), "tenant-b".to_string())]),
+                ..CopyOptions::default()
+            },
+        )
+        .await
+        .expect("copy_file_with_options should succeed");
+
+    let head = client
+        .head_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(&replaced_path)
+        .send()
+        .await
+        .expect("head_object should succeed");
+    assert_eq!(head.content_type(), Some("text/plain"));
+    assert_eq!(
+        head.metadata()
+            .and_then(|m| m.get("owner"))
+            .map(String::as_str),
+        Some("tenant-b")
+    );
+}
+
+#[tokio::test]
+async fn test_verify_bucket_policy() {
+    let ctx = S3TestContext::new("verify-bucket-policy").await;
+    let facade = ctx.facade();
+
+    // No expectations can't fail
+    facade
+        .verify_bucket_policy(BucketExpectations::default())
+        .await
+        .expect("Empty expectations should be met");
+
+    // Each setting is either on or off, so across these two opposite sets every expectation fails exactly once
+    let failures = |result: Result<(), StorageError>| match result {
+        Ok(()) => vec![],
+        Err(StorageError::ExpectationsNotMet(failures)) => failures,
+        Err(e) => panic!("Unexpected error verifying bucket: {}", e),
+    };
+    let all_on = facade
+        .verify_bucket_policy(BucketExpectations {
+            versioning: Some(true),
+            default_encryption: Some(true),
+            public_access_blocked: Some(true),
+        })
+        .await;
+    let all_off = facade
+        .verify_bucket_policy(BucketExpectations {
+            versioning: Some(false),
+            default_encryption: Some(false),
+            public_access_blocked: Some(false),
+        })
+        .await;
+
+    let mut itemized = failures(all_on);
+    itemized.extend(failures(all_off));
+    assert_eq!(
+        itemized.len(),
+        3,
+        "Each wrong expectation should be itemized: {:?}",
+        itemized
+    );
+}
+
+#[tokio::test]
+async fn test_versioning_status() {
+    let ctx = S3TestContext::new("versioning-status").await;
+    let facade = ctx.facade();
+
+    // The test bucket's versioning isn't toggled here, so check the status agrees with the policy check instead
+    let status = facade
+        .versioning_status()
+        .await
+        .expect("Failed to get versioning status");
+    let enabled = facade
+        .verify_bucket_policy(BucketExpectations {
+            versioning: Some(true),
+            ..BucketExpectations::default()
+        })
+        .await
+        .is_ok();
+    assert_eq!(status == VersioningStatus::Enabled, enabled);
+}
+
+#[cfg(feature = "tar")]
+#[tokio::test]
+async fn test_stream_prefix_as_tar() {
+    use futures::TryStreamExt;
+    use std::io::Read;
+
+    let ctx = S3TestContext::new("stream-tar").await;
+    let facade = ctx.facade();
+
+    let long_name = format!("nested/{}.txt", "n".repeat(120));
+    let files = HashMap::from([
+        ("a.txt".to_string(), b"alpha".to_vec()),
+        ("sub/b.bin".to_string(), vec![7u8; 1500]),
+        (long_name, b"long name".to_vec()),
+    ]);
+    for (file, content) in &files {
+        facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &ctx.path(file),
+                content,
+                None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+
+    let chunks: Vec<bytes::Bytes> = facade
+        .stream_prefix_as_tar(ctx.prefix())
+        .await
+        .expect("stream_prefix_as_tar should succeed")
+        .try_collect()
+        .await
+        .expect("tar stream should complete");
+    let archive_bytes = chunks.concat();
+
+    let mut unpacked = HashMap::new();
+    let mut archive = tar::Archive::new(archive_bytes.as_slice());
+    for entry in archive.entries().expect("Archive should be readable") {
+        let mut entry = entry.expect("Entry should be readable");
+        let path = entry.path().expect("Entry should have a path");
+        let path = path.to_string_lossy().to_string();
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .expect("Entry content should be readable");
+        unpacked.insert(path, content);
+    }
+
+    assert_eq!(unpacked, files);
+}
+
+/// XOR stage for pipeline tests (NOT secure, just for exercising the stage mechanism)
+struct XorStage(u8);
+
+impl Transform for XorStage {
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(data.iter().map(|b| b ^ self.0).collect())
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        self.encode(data)
+    }
+}
+
+/// Order sensitive stage, which prepends a marker byte on encode and insists on it when decoding
+struct FramingStage;
+
+impl Transform for FramingStage {
+    fn encode(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok([&[0xFF], data].concat())
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        match data.split_first() {
+            Some((0xFF, rest)) => Ok(rest.to_vec()),
+            _ => Err("missing frame marker".into()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline_round_trip() {
+    let ctx = S3TestContext::new("pipeline").await;
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Pipeline test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_pipeline(Pipeline::new().stage(FramingStage).stage(XorStage(0x42)));
+    let path = ctx.path("pipelined.txt");
+    let original_data = b"Data passing through two stages";
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            original_data,
+            None,
+        )
+        .await
+        .expect("write_data through pipeline should succeed");
+
+    // Stored bytes are framed after the XOR, as framing is the outermost read stage
+    let stored = ctx
+        .facade()
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path, None,
+        )
+        .await
+        .expect("raw read should succeed");
+    assert_ne!(
+        stored,
+        original_data.to_vec(),
+        "Stored bytes should be transformed"
+    );
+    assert_eq!(
+        stored[0], 0xFF,
+        "Outermost stage should be applied last on write"
+    );
+
+    let result = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path, None,
+        )
+        .await
+        .expect("read_data through pipeline should succeed");
+    assert_eq!(result, original_data.to_vec());
+}
+
+#[tokio::test]
+async fn test_presign_get_many() {
+    let ctx = S3TestContext::new("presign-many").await;
+    let facade = ctx.facade();
+
+    let keys: Vec<String> = ["one.png", "two.png", "three.png"]
+        .iter()
+        .map(|file| ctx.path(file))
+        .collect();
+
+    let urls = facade
+        .presign_get_many(&keys, Duration::from_secs(900))
+        .await
+        .expect("presign_get_many should succeed");
+
+    assert_eq!(urls.len(), keys.len(), "Every key should get a URL");
+    for key in &keys {
+        let url = urls.get(key).expect("URL should be present for key");
+        assert!(url.contains("X-Amz-Signature="), "URL should be signed");
+        assert!(
+            url.contains("X-Amz-Expires=900"),
+            "URL should carry the expiry"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_replication_status_without_replication() {
+    let ctx = S3TestContext::new("replication-status").await;
+    let facade = ctx.facade();
+    let path = ctx.path("unreplicated.txt");
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            b"not replicated",
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    let status = facade
+        .replication_status(&path)
+        .await
+        .expect("replication_status should succeed");
+    assert_eq!(status, None, "Test bucket has no replication configured");
+}
+
+/// Clock frozen at a chosen instant
+struct FixedClock(SystemTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+#[tokio::test]
+async fn test_find_older_than() {
+    let ctx = S3TestContext::new("find-older-than").await;
+    let facade = ctx.facade();
+    let old_path = ctx.path("old.log");
+    let new_path = ctx.path("new.log");
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &old_path, b"old", None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    // S3 timestamps have one second resolution, so leave a clear gap between the two objects
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &new_path, b"new", None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    let new_modified = match facade
+        .read_conditional(&new_path, ReadPrecondition::default())
+        .await
+        .expect("read_conditional should succeed")
+    {
+        ReadOutcome::Modified(_, metadata) => metadata
+            .last_modified
+            .expect("S3 should report last modified"),
+        ReadOutcome::NotModified => panic!("Unconditional read should return data"),
+    };
+
+    // Half a second after the newer write, only the older object is over a second old
+    let auditor = S3Facade::new(TEST_BUCKET_NAME, "Retention audit test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_clock(FixedClock(new_modified + Duration::from_millis(500)));
+
+    let stale = auditor
+        .find_older_than(ctx.prefix(), Duration::from_secs(1))
+        .await
+        .expect("find_older_than should succeed");
+
+    let stale_keys: Vec<&str> = stale.iter().map(|entry| entry.key.as_str()).collect();
+    assert_eq!(stale_keys, vec![old_path.as_str()]);
+}
+
+/// Creates an empty, uniquely named directory under the system temp directory.
+fn temp_dir(test_name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("fallible-{}-{}", test_name, Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).expect("Failed to create temp directory");
+    dir
+}
+
+#[tokio::test]
+async fn test_upload_dir() {
+    let ctx = S3TestContext::new("upload-dir").await;
+    let facade = ctx.facade();
+
+    let root = temp_dir("upload-dir");
+    let files = [
+        ("top.txt", "top level"),
+        ("nested/inner.txt", "one level down"),
+        ("nested/deeper/leaf.txt", "two levels down"),
+    ];
+    for (file, content) in &files {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("Failed to create directories");
+        std::fs::write(&path, content).expect("Failed to write local file");
+    }
+
+    let report = facade
+        .upload_dir(&root, &ctx.path("backup"), 4)
+        .await
+        .expect("upload_dir should succeed");
+
+    assert!(
+        report.failed.is_empty(),
+        "No uploads should fail: {:?}",
+        report.failed
+    );
+    assert_eq!(report.uploaded.len(), files.len());
+
+    for (file, content) in &files {
+        let key = ctx.path(&format!("backup/{}", file));
+        assert!(
+            report.uploaded.contains(&key),
+            "Report should include {}",
+            key
+        );
+
+        let result = facade
+            .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &key, None,
+            )
+            .await
+            .expect("read_data should succeed");
+        assert_eq!(result, content.as_bytes().to_vec());
+    }
+
+    std::fs::remove_dir_all(&root).expect("Failed to clean up temp directory");
+}
+
+#[tokio::test]
+async fn test_download_prefix() {
+    let ctx = S3TestContext::new("download-prefix").await;
+    let facade = ctx.facade();
+
+    let files = [
+        ("top.txt", "top level"),
+        ("nested/inner.txt", "one level down"),
+        ("nested/deeper/leaf.txt", "two levels down"),
+    ];
+    for (file, content) in &files {
+        facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &ctx.path(file),
+                content.as_bytes(),
+                None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+    // Directory marker, as created by the S3 console's "create folder"
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("empty/"),
+            b"",
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    let root = temp_dir("download-prefix");
+    let report = facade
+        .download_prefix(ctx.prefix(), &root, 4, false)
+        .await
+        .expect("download_prefix should succeed");
+
+    assert!(
+        report.failed.is_empty(),
+        "No downloads should fail: {:?}",
+        report.failed
+    );
+    assert_eq!(report.downloaded.len(), files.len() + 1);
+    for (file, content) in &files {
+        let local = std::fs::read_to_string(root.join(file)).expect("Local file should exist");
+        assert_eq!(local, *content);
+    }
+    assert!(
+        root.join("empty").is_dir(),
+        "Directory marker should become a directory"
+    );
+
+    // A second run without overwriting leaves existing files alone
+    let rerun = facade
+        .download_prefix(ctx.prefix(), &root, 4, false)
+        .await
+        .expect("download_prefix should succeed");
+    assert_eq!(rerun.skipped.len(), files.len() + 1);
+    assert!(rerun.downloaded.is_empty());
+
+    std::fs::remove_dir_all(&root).expect("Failed to clean up temp directory");
+}
+
+#[tokio::test]
+async fn test_publish_is_never_partial() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let ctx = S3TestContext::new("publish").await;
+    let facade = ctx.facade();
+    let path = ctx.path("config.json");
+
+    let old_content = vec![b'o'; 1024];
+    let new_content = vec![b'n'; 4 * 1024 * 1024];
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            &old_content,
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    // Keep reading while a large publish is in flight
+    let published = AtomicBool::new(false);
+    let publisher = async {
+        facade
+            .publish(&path, &new_content)
+            .await
+            .expect("publish should succeed");
+        published.store(true, Ordering::SeqCst);
+    };
+    let reader = async {
+        while !published.load(Ordering::SeqCst) {
+            let seen = facade
+                .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                    &path, None,
+                )
+                .await
+                .expect("read_data should succeed");
+            assert!(
+                seen == old_content || seen == new_content,
+                "Reader should never observe a partial object"
+            );
+        }
+    };
+    tokio::join!(publisher, reader);
+
+    let result = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path, None,
+        )
+        .await
+        .expect("read_data should succeed");
+    assert_eq!(result, new_content);
+
+    let listed = facade
+        .list_objects(ctx.prefix())
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(listed, vec![path], "Temporary object should be cleaned up");
+}
+
+#[tokio::test]
+async fn test_hashed_partitions_round_trip() {
+    let ctx = S3TestContext::new("hashed-partitions").await;
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Partitioning test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_hashed_partitions(true);
+    let logical_key = ctx.path("hot/object.txt");
+    let content = b"spread across prefixes";
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &logical_key,
+            content,
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    let result = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &logical_key,
+            None,
+        )
+        .await
+        .expect("read_data should succeed");
+    assert_eq!(result, content.to_vec());
+
+    // The object is physically stored under the partitioned key
+    let physical_key = facade.hashed_key(&logical_key);
+    assert!(physical_key.ends_with(&logical_key));
+    assert_ne!(physical_key, logical_key);
+    assert!(ctx.facade().file_exists(&physical_key).await.unwrap());
+    assert!(!ctx.facade().file_exists(&logical_key).await.unwrap());
+
+    // Listings map stored keys back to logical ones
+    let listed = facade
+        .list_objects(ctx.prefix())
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(listed, vec![logical_key]);
+}
+
+#[tokio::test]
+async fn test_object_lambda_arn_skips_bucket_check() {
+    let arn = "arn:aws:s3-object-lambda:us-east-1:123456789012:accesspoint/redact";
+
+    let facade = S3Facade::new(arn, "Object Lambda test")
+        .await
+        .expect("An Object Lambda ARN should be accepted without head_bucket");
+
+    assert_eq!(facade.metadata().name, arn);
+}
+
+#[tokio::test]
+async fn test_list_in_progress_uploads() {
+    let ctx = S3TestContext::new("in-progress-uploads").await;
+    let key = ctx.path("big.bin");
+    let client = raw_client().await;
+
+    let upload_id = client
+        .create_multipart_upload()
+        .bucket(TEST_BUCKET_NAME)
+        .key(&key)
+        .send()
+        .await
+        .expect("create_multipart_upload should succeed")
+        .upload_id()
+        .expect("S3 should assign an upload id")
+        .to_string();
+    client
+        .upload_part()
+        .bucket(TEST_BUCKET_NAME)
+        .key(&key)
+        .upload_id(&upload_id)
+        .part_number(1)
+        .body(s3::primitives::ByteStream::from(vec![7u8; 1024]))
+        .send()
+        .await
+        .expect("upload_part should succeed");
+
+    let uploads = ctx
+        .facade()
+        .list_in_progress_uploads(ctx.prefix())
+        .await
+        .expect("list_in_progress_uploads should succeed");
+
+    client
+        .abort_multipart_upload()
+        .bucket(TEST_BUCKET_NAME)
+        .key(&key)
+        .upload_id(&upload_id)
+        .send()
+        .await
+        .expect("abort_multipart_upload should succeed");
+
+    assert_eq!(uploads.len(), 1);
+    assert_eq!(uploads[0].key, key);
+    assert_eq!(uploads[0].upload_id, upload_id);
+    assert!(uploads[0].initiated.is_some());
+    assert_eq!(uploads[0].parts_completed, 1);
+    assert_eq!(uploads[0].bytes_uploaded, 1024);
+}
+
+#[tokio::test]
+async fn test_prefix_fingerprint_tracks_changes() {
+    let ctx = S3TestContext::new("prefix-fingerprint").await;
+    ctx.write("a.txt", b"first").await;
+    ctx.write("b.txt", b"second").await;
+
+    let original = ctx
+        .facade()
+        .prefix_fingerprint(ctx.prefix())
+        .await
+        .expect("prefix_fingerprint should succeed");
+    assert_eq!(original.len(), 64);
+
+    ctx.write("c.txt", b"third").await;
+    let added = ctx
+        .facade()
+        .prefix_fingerprint(ctx.prefix())
+        .await
+        .expect("prefix_fingerprint should succeed");
+    assert_ne!(added, original);
+
+    ctx.facade()
+        .delete_file(&ctx.path("c.txt"))
+        .await
+        .expect("delete_file should succeed");
+    let restored = ctx
+        .facade()
+        .prefix_fingerprint(ctx.prefix())
+        .await
+        .expect("prefix_fingerprint should succeed");
+    assert_eq!(restored, original);
+}
+
+#[tokio::test]
+async fn test_prefix_as_directory() {
+    let ctx = S3TestContext::new("prefix-as-directory").await;
+    ctx.write("logs/x", b"inside").await;
+    ctx.write("logs-archive/x", b"sibling").await;
+
+    let literal = ctx
+        .facade()
+        .list_objects(&ctx.path("logs"))
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(literal.len(), 2);
+
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Directory prefix test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_prefix_as_directory(true);
+    let listed = facade
+        .list_objects(&ctx.path("logs"))
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(listed, vec![ctx.path("logs/x")]);
+}
+
+#[tokio::test]
+async fn test_truncate_object() {
+    let ctx = S3TestContext::new("truncate-object").await;
+    let path = ctx.path("app.log");
+    let content: Vec<u8> = (0..8192u32).map(|i| (i % 251) as u8).collect();
+    ctx.write("app.log", &content).await;
+
+    ctx.facade()
+        .truncate_object(&path, 3000)
+        .await
+        .expect("truncate_object should succeed");
+
+    let result = ctx
+        .facade()
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path, None,
+        )
+        .await
+        .expect("read_data should succeed");
+    assert_eq!(result, content[..3000].to_vec());
+
+    // Longer than the object is a no-op, and zero empties it
+    ctx.facade()
+        .truncate_object(&path, 10_000)
+        .await
+        .expect("truncate_object should succeed");
+    ctx.facade()
+        .truncate_object(&path, 0)
+        .await
+        .expect("truncate_object should succeed");
+    let emptied = ctx
+        .facade()
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path, None,
+        )
+        .await
+        .expect("read_data should succeed");
+    assert!(emptied.is_empty());
+}
+
+#[tokio::test]
+async fn test_truncate_object_preserves_settings() {
+    let ctx = S3TestContext::new("truncate-preserves-settings").await;
+    let facade = ctx.facade();
+    let client = raw_client().await;
+
+    for (name, len) in [("report.json", 3), ("empty.json", 0)] {
+        let path = ctx.path(name);
+        client
+            .put_object()
+            .bucket(TEST_BUCKET_NAME)
+            .key(&path)
+            .content_type("application/json")
+            .storage_class(s3::types::StorageClass::StandardIa)
+            .body(s3::primitives::ByteStream::from_static(b"{\"a\": 1}"))
+            .send()
+            .await
+            .expect("put_object should succeed");
+
+        facade
+            .truncate_object(&path, len)
+            .await
+            .expect("truncate_object should succeed");
+
+        let head = client
+            .head_object()
+            .bucket(TEST_BUCKET_NAME)
+            .key(&path)
+            .send()
+            .await
+            .expect("head_object should succeed");
+        assert_eq!(head.content_length(), Some(len as i64));
+        assert_eq!(head.content_type(), Some("application/json"));
+        assert_eq!(
+            head.storage_class(),
+            Some(&s3::types::StorageClass::StandardIa)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_multi_region_access_point_uses_sigv4a() {
+    let arn = "arn:aws:s3::123456789012:accesspoint/mfzwi23gnjvgw.mrap";
+
+    let facade = S3Facade::new(arn, "Multi-Region Access Point test")
+        .await
+        .expect("An MRAP ARN should be accepted without head_bucket");
+    let urls = facade
+        .presign_get_many(&["reports/q1.csv".to_string()], Duration::from_secs(60))
+        .await
+        .expect("presigning against an MRAP should succeed");
+
+    // SigV4A signs with ECDSA, scoped to every region rather than one
+    let url = &urls["reports/q1.csv"];
+    assert!(
+        url.contains("X-Amz-Algorithm=AWS4-ECDSA-P256-SHA256"),
+        "{}",
+        url
+    );
+    assert!(url.contains("X-Amz-Region-Set=%2A"), "{}", url);
+    assert!(url.contains(".mrap.accesspoint.s3-global."), "{}", url);
+}
+
+#[tokio::test]
+async fn test_read_and_hash() {
+    use futures::TryStreamExt;
+    use sha2::{Digest, Sha256};
+
+    let ctx = S3TestContext::new("read-and-hash").await;
+    let content: Vec<u8> = (0..50_000u32).map(|i| (i % 199) as u8).collect();
+    ctx.write("data.bin", &content).await;
+    let expected: [u8; 32] = Sha256::digest(&content).into();
+
+    let (data, digest) = ctx
+        .facade()
+        .read_and_hash(&ctx.path("data.bin"))
+        .await
+        .expect("read_and_hash should succeed");
+    assert_eq!(data, content);
+    assert_eq!(digest, expected);
+
+    let (chunks, digest) = ctx
+        .facade()
+        .read_and_hash_stream(&ctx.path("data.bin"))
+        .await
+        .expect("read_and_hash_stream should succeed");
+    let streamed: Vec<bytes::Bytes> = chunks.try_collect().await.expect("stream should complete");
+    assert_eq!(streamed.concat(), content);
+    assert_eq!(digest.await.expect("digest should be sent"), expected);
+}
+
+#[tokio::test]
+async fn test_retry_classifier_retries_fatal_errors() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    ensure_bucket_exists().await;
+    let client = raw_client().await;
+    let bucket = format!("fallible-retry-{}", Uuid::new_v4());
+    client
+        .create_bucket()
+        .bucket(&bucket)
+        .send()
+        .await
+        .expect("create_bucket should succeed");
+
+    let calls = Arc::new(AtomicU32::new(0));
+    let counted = Arc::clone(&calls);
+    let facade = S3Facade::new(&bucket, "Retry classifier test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_listing_backoff(ListingBackoff {
+            page_delay: Duration::ZERO,
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(1),
+        })
+        .with_retry_classifier(move |error| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            // A missing bucket is fatal by default
+            assert!(!default_retry_classifier(error));
+            true
+        });
+
+    // Listing a deleted bucket fails the same way on every attempt
+    client
+        .delete_bucket()
+        .bucket(&bucket)
+        .send()
+        .await
+        .expect("delete_bucket should succeed");
+    let listed = facade.list_objects("").await;
+
+    assert!(listed.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_group_by_top_prefix() {
+    let ctx = S3TestContext::new("group-by-top-prefix").await;
+    ctx.write("acme/a.bin", &[0; 100]).await;
+    ctx.write("acme/nested/b.bin", &[0; 250]).await;
+    ctx.write("globex/c.bin", &[0; 40]).await;
+
+    let groups = ctx
+        .facade()
+        .group_by_top_prefix(ctx.prefix())
+        .await
+        .expect("group_by_top_prefix should succeed");
+
+    assert_eq!(groups.len(), 2);
+    let acme = PrefixStats {
+        objects: 2,
+        bytes: 350,
+    };
+    let globex = PrefixStats {
+        objects: 1,
+        bytes: 40,
+    };
+    assert_eq!(groups["acme"], acme);
+    assert_eq!(groups["globex"], globex);
+}
+
+#[tokio::test]
+async fn test_read_data_bounded() {
+    let ctx = S3TestContext::new("read-data-bounded").await;
+    ctx.write("tiny.csv", b"id").await;
+    ctx.write("normal.csv", b"id,name\n1,alice\n").await;
+
+    let error = ctx
+        .facade()
+        .read_data_bounded(&ctx.path("tiny.csv"), Some(10), Some(1024))
+        .await
+        .expect_err("An object below min should be rejected");
+    match &error {
+        StorageError::SizeOutOfRange { size, min, max } => {
+            assert_eq!(*size, 2);
+            assert_eq!(*min, Some(10));
+            assert_eq!(*max, Some(1024));
+        }
+        other => panic!("Expected SizeOutOfRange, got {:?}", other),
+    }
+
+    let data = ctx
+        .facade()
+        .read_data_bounded(&ctx.path("normal.csv"), Some(10), Some(1024))
+        .await
+        .expect("An object within range should be read");
+    assert_eq!(data, b"id,name\n1,alice\n".to_vec());
+}
+
+#[tokio::test]
+async fn test_backup_then_write() {
+    let ctx = S3TestContext::new("backup-then-write").await;
+    let path = ctx.path("config.toml");
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Backup test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_clock(FixedClock(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_768_210_200),
+        ));
+
+    let first = facade
+        .backup_then_write(&path, b"version = 1")
+        .await
+        .expect("backup_then_write should succeed");
+    assert_eq!(first, "");
+
+    let backup = facade
+        .backup_then_write(&path, b"version = 2")
+        .await
+        .expect("backup_then_write should succeed");
+    assert_eq!(backup, format!("{}.bak.2026-01-12T09:30:00Z", path));
+
+    assert_eq!(
+        ctx.read("config.toml.bak.2026-01-12T09:30:00Z").await,
+        b"version = 1"
+    );
+    assert_eq!(ctx.read("config.toml").await, b"version = 2");
+}
+
+#[tokio::test]
+async fn test_list_objects_if_changed() {
+    let ctx = S3TestContext::new("list-if-changed").await;
+    ctx.write("a.txt", b"first").await;
+
+    let (entries, fingerprint) = ctx
+        .facade()
+        .list_objects_if_changed(ctx.prefix(), "")
+        .await
+        .expect("list_objects_if_changed should succeed")
+        .expect("The first poll should return a listing");
+    assert_eq!(entries.len(), 1);
+
+    let unchanged = ctx
+        .facade()
+        .list_objects_if_changed(ctx.prefix(), &fingerprint)
+        .await
+        .expect("list_objects_if_changed should succeed");
+    assert!(unchanged.is_none());
+
+    ctx.write("b.txt", b"second").await;
+    let (entries, new_fingerprint) = ctx
+        .facade()
+        .list_objects_if_changed(ctx.prefix(), &fingerprint)
+        .await
+        .expect("list_objects_if_changed should succeed")
+        .expect("A changed prefix should return a listing");
+    let keys: Vec<String> = entries.into_iter().map(|entry| entry.key).collect();
+    assert_eq!(keys, vec![ctx.path("a.txt"), ctx.path("b.txt")]);
+    assert_ne!(new_fingerprint, fingerprint);
+    assert_eq!(
+        new_fingerprint,
+        ctx.facade()
+            .prefix_fingerprint(ctx.prefix())
+            .await
+            .expect("prefix_fingerprint should succeed")
+    );
+}
+
+/// Reads an object's ETag straight from S3, multipart uploads being the ones with a `-{parts}` suffix.
+async fn object_etag(key: &str) -> String {
+    raw_client()
+        .await
+        .head_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(key)
+        .send()
+        .await
+        .expect("head_object should succeed")
+        .e_tag()
+        .expect("S3 should return an ETag")
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_write_from_reader() {
+    let ctx = S3TestContext::new("write-from-reader").await;
+    let small = b"known length payload".to_vec();
+
+    ctx.facade()
+        .write_from_reader(
+            &ctx.path("small.txt"),
+            small.as_slice(),
+            Some(small.len() as u64),
+        )
+        .await
+        .expect("write_from_reader should succeed");
+    assert_eq!(ctx.read("small.txt").await, small);
+    assert!(!object_etag(&ctx.path("small.txt")).await.contains('-'));
+
+    // Over one part, with no length given, goes through a multipart upload
+    let large: Vec<u8> = (0..9 * 1024 * 1024u32).map(|i| (i % 241) as u8).collect();
+    ctx.facade()
+        .write_from_reader(&ctx.path("large.bin"), large.as_slice(), None)
+        .await
+        .expect("write_from_reader should succeed");
+    assert_eq!(ctx.read("large.bin").await, large);
+    assert!(object_etag(&ctx.path("large.bin")).await.ends_with("-2\""));
+
+    let short = ctx
+        .facade()
+        .write_from_reader(&ctx.path("short.txt"), small.as_slice(), Some(100))
+        .await;
+    assert!(short.is_err());
+    assert!(
+        !ctx.facade()
+            .file_exists(&ctx.path("short.txt"))
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_scan_and_quarantine() {
+    let ctx = S3TestContext::new("scan-and-quarantine").await;
+    ctx.write("uploads/good.json", b"{\"ok\": true}").await;
+    ctx.write("uploads/nested/bad.json", b"not json").await;
+    ctx.write("uploads/also-good.json", b"[]").await;
+
+    let report = ctx
+        .facade()
+        .scan_and_quarantine(
+            &ctx.path("uploads/"),
+            &ctx.path("quarantine/"),
+            |data| data.starts_with(b"{") || data.starts_with(b"["),
+            4,
+        )
+        .await
+        .expect("scan_and_quarantine should succeed");
+
+    assert_eq!(report.scanned, 3);
+    assert_eq!(
+        report.quarantined,
+        vec![ctx.path("uploads/nested/bad.json")]
+    );
+    assert!(report.failed.is_empty());
+
+    let quarantined = ctx
+        .facade()
+        .list_objects(&ctx.path("quarantine/"))
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(quarantined, vec![ctx.path("quarantine/nested/bad.json")]);
+    let remaining = ctx
+        .facade()
+        .list_objects(&ctx.path("uploads/"))
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(remaining.len(), 2);
+}
+
+#[tokio::test]
+async fn test_per_operation_timeouts() {
+    let ctx = S3TestContext::new("operation-timeouts").await;
+    ctx.write("large.bin", &vec![0; 4 * 1024 * 1024]).await;
+
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Timeout test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_timeouts(OperationTimeouts {
+            read: Some(Duration::from_millis(1)),
+            list: Some(Duration::from_secs(30)),
+            ..OperationTimeouts::default()
+        });
+
+    let error = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("large.bin"),
+            None,
+        )
+        .await
+        .expect_err("A 1ms read timeout should trigger");
+    assert!(matches!(&error, StorageError::TimedOut { operation, .. } if operation == "read"));
+
+    let listed = facade
+        .list_objects(ctx.prefix())
+        .await
+        .expect("The generous list timeout should allow the listing");
+    assert_eq!(listed, vec![ctx.path("large.bin")]);
+}
+
+#[tokio::test]
+async fn test_write_data_verified() {
+    let ctx = S3TestContext::new("write-verified").await;
+
+    ctx.facade()
+        .write_data_verified(&ctx.path("ledger.json"), b"{\"balance\": 10}", 3)
+        .await
+        .expect("write_data_verified should succeed");
+    assert_eq!(ctx.read("ledger.json").await, b"{\"balance\": 10}".to_vec());
+
+    // Empty objects can't satisfy a ranged read, but are still readable
+    ctx.facade()
+        .write_data_verified(&ctx.path("empty.json"), b"", 0)
+        .await
+        .expect("write_data_verified should succeed for an empty object");
+}
+
+#[tokio::test]
+async fn test_list_all_directories() {
+    let ctx = S3TestContext::new("list-all-directories").await;
+    ctx.write("a/b/c.txt", b"deep").await;
+    ctx.write("a/d.txt", b"shallow").await;
+    ctx.write("top.txt", b"root").await;
+
+    let directories = ctx
+        .facade()
+        .list_all_directories(ctx.prefix())
+        .await
+        .expect("list_all_directories should succeed");
+
+    assert_eq!(directories, vec![ctx.path("a/"), ctx.path("a/b/")]);
+}
+
+#[tokio::test]
+async fn test_delete_if_etag() {
+    let ctx = S3TestContext::new("delete-if-etag").await;
+    let path = ctx.path("lease.json");
+    ctx.write("lease.json", b"holder = a").await;
+    let stale_etag = object_etag(&path).await;
+
+    ctx.write("lease.json", b"holder = b").await;
+    let deleted = ctx
+        .facade()
+        .delete_if_etag(&path, &stale_etag)
+        .await
+        .expect("delete_if_etag should succeed");
+    assert!(!deleted);
+    assert!(ctx.facade().file_exists(&path).await.unwrap());
+
+    let current_etag = object_etag(&path).await;
+    let deleted = ctx
+        .facade()
+        .delete_if_etag(&path, &current_etag)
+        .await
+        .expect("delete_if_etag should succeed");
+    assert!(deleted);
+    assert!(!ctx.facade().file_exists(&path).await.unwrap());
+}
+
+#[cfg(feature = "text")]
+#[tokio::test]
+async fn test_read_text() {
+    let ctx = S3TestContext::new("read-text").await;
+    let text = "Grüße, 世界";
+
+    let mut utf16 = vec![0xFF, 0xFE];
+    utf16.extend(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+    ctx.write("utf16.txt", &utf16).await;
+    ctx.write("utf8.txt", text.as_bytes()).await;
+
+    for file in ["utf16.txt", "utf8.txt"] {
+        let decoded = ctx
+            .facade()
+            .read_text(&ctx.path(file))
+            .await
+            .expect("read_text should succeed");
+        assert_eq!(decoded, text, "{}", file);
+    }
+
+    ctx.write("latin1.txt", b"Caf\xe9 cr\xe8me br\xfbl\xe9e")
+        .await;
+    let decoded = ctx
+        .facade()
+        .read_text(&ctx.path("latin1.txt"))
+        .await
+        .expect("read_text should succeed");
+    assert_eq!(decoded, "Café crème brûlée");
+}
+
+/// Reads an object's storage class straight from S3, which omits it for `STANDARD`.
+async fn object_storage_class(key: &str) -> String {
+    raw_client()
+        .await
+        .head_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(key)
+        .send()
+        .await
+        .expect("head_object should succeed")
+        .storage_class()
+        .map_or("STANDARD".to_string(), |class| class.as_str().to_string())
+}
+
+#[tokio::test]
+async fn test_apply_tiering() {
+    use aws_sdk_s3::types::StorageClass;
+
+    let ctx = S3TestContext::new("apply-tiering").await;
+    ctx.write("oldest.log", b"oldest").await;
+    // S3 timestamps have one second resolution, so leave clear gaps between the objects
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    ctx.write("older.log", b"older").await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    ctx.write("new.log", b"new").await;
+
+    let new_modified = match ctx
+        .facade()
+        .read_conditional(&ctx.path("new.log"), ReadPrecondition::default())
+        .await
+        .expect("read_conditional should succeed")
+    {
+        ReadOutcome::Modified(_, metadata) => metadata
+            .last_modified
+            .expect("S3 should report last modified"),
+        ReadOutcome::NotModified => panic!("Unconditional read should return data"),
+    };
+
+    // Ages are roughly 4.5s, 2.5s and 0.5s
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Tiering test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_clock(FixedClock(new_modified + Duration::from_millis(500)));
+    let rules = [
+        TieringRule {
+            older_than: Duration::from_secs(1),
+            to_class: StorageClass::StandardIa,
+        },
+        TieringRule {
+            older_than: Duration::from_secs(3),
+            to_class: StorageClass::Glacier,
+        },
+    ];
+
+    let report = facade
+        .apply_tiering(ctx.prefix(), &rules)
+        .await
+        .expect("apply_tiering should succeed");
+
+    assert!(report.failed.is_empty(), "{:?}", report.failed);
+    assert_eq!(report.transitioned.len(), 2);
+    assert_eq!(report.unchanged, 1);
+    assert_eq!(
+        object_storage_class(&ctx.path("oldest.log")).await,
+        "GLACIER"
+    );
+    assert_eq!(
+        object_storage_class(&ctx.path("older.log")).await,
+        "STANDARD_IA"
+    );
+    assert_eq!(object_storage_class(&ctx.path("new.log")).await, "STANDARD");
+}
+
+#[tokio::test]
+async fn test_object_handle() {
+    let ctx = S3TestContext::new("object-handle").await;
+    let content = b"0123456789abcdef";
+    ctx.write("handle.txt", content).await;
+
+    let handle = ctx.facade().object(&ctx.path("handle.txt"));
+    assert_eq!(handle.path(), ctx.path("handle.txt"));
+
+    let read = handle.read().await.expect("read should succeed");
+    assert_eq!(read, ctx.read("handle.txt").await);
+    assert_eq!(
+        handle.size().await.expect("size should succeed"),
+        read.len() as u64
+    );
+
+    let metadata = handle.metadata().await.expect("metadata should succeed");
+    assert_eq!(
+        metadata.etag,
+        Some(object_etag(&ctx.path("handle.txt")).await)
+    );
+    assert_eq!(
+        handle
+            .read_range(4..10)
+            .await
+            .expect("read_range should succeed"),
+        b"456789".to_vec()
+    );
+
+    handle.delete().await.expect("delete should succeed");
+    assert!(
+        !ctx.facade()
+            .file_exists(&ctx.path("handle.txt"))
+            .await
+            .unwrap()
+    );
+    assert!(handle.size().await.is_err());
+}
+
+#[tokio::test]
+async fn test_stat_many() {
+    let ctx = S3TestContext::new("stat-many").await;
+    ctx.write("a.txt", b"first").await;
+    ctx.write("b.txt", b"second object").await;
+
+    let keys = vec![
+        ctx.path("a.txt"),
+        ctx.path("b.txt"),
+        ctx.path("missing.txt"),
+    ];
+    let stats = ctx
+        .facade()
+        .stat_many(&keys, 2)
+        .await
+        .expect("stat_many should succeed");
+
+    assert_eq!(stats.len(), 3);
+    let a = stats[&ctx.path("a.txt")].as_ref().expect("a.txt exists");
+    assert_eq!(a.size, 5);
+    assert_eq!(a.etag, Some(object_etag(&ctx.path("a.txt")).await));
+    assert!(a.last_modified.is_some());
+    let b = stats[&ctx.path("b.txt")].as_ref().expect("b.txt exists");
+    assert_eq!(b.size, 13);
+    assert!(stats[&ctx.path("missing.txt")].is_none());
+}
+
+#[tokio::test]
+async fn test_write_with_part_size() {
+    let ctx = S3TestContext::new("write-part-size").await;
+    let part_size = 5 * 1024 * 1024;
+    let data: Vec<u8> = (0..part_size * 2 + 1024).map(|i| (i % 251) as u8).collect();
+
+    let first = ctx
+        .facade()
+        .write_with_part_size(&ctx.path("first.bin"), &data, part_size)
+        .await
+        .expect("write_with_part_size should succeed");
+    let second = ctx
+        .facade()
+        .write_with_part_size(&ctx.path("second.bin"), &data, part_size)
+        .await
+        .expect("write_with_part_size should succeed");
+
+    assert_eq!(first, second);
+    assert!(first.ends_with("-3\""));
+    assert_eq!(first, multipart_etag(&data, part_size));
+    assert_eq!(object_etag(&ctx.path("first.bin")).await, first);
+    assert_eq!(ctx.read("first.bin").await, data);
+
+    let small = ctx
+        .facade()
+        .write_with_part_size(&ctx.path("small.bin"), b"tiny", 16)
+        .await
+        .expect("data within one part should be accepted below 5 MiB");
+    assert_eq!(small, multipart_etag(b"tiny", 16));
+    assert!(
+        ctx.facade()
+            .write_with_part_size(&ctx.path("rejected.bin"), &data, 1024)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_prune() {
+    let ctx = S3TestContext::new("prune").await;
+    ctx.write("work/a.tmp", b"scratch").await;
+    ctx.write("work/nested/b.tmp", b"scratch").await;
+    ctx.write("work/keep.csv", b"a,b").await;
+    ctx.write("work/nested/keep.tmp.csv", b"c,d").await;
+
+    let report = ctx
+        .facade()
+        .prune(&ctx.path("work/"), |entry| entry.key.ends_with(".tmp"))
+        .await
+        .expect("prune should succeed");
+
+    assert_eq!(report.scanned, 4);
+    assert_eq!(
+        report.deleted,
+        vec![ctx.path("work/a.tmp"), ctx.path("work/nested/b.tmp")]
+    );
+    assert!(report.failed.is_empty());
+
+    let remaining = ctx
+        .facade()
+        .list_objects(&ctx.path("work/"))
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(
+        remaining,
+        vec![
+            ctx.path("work/keep.csv"),
+            ctx.path("work/nested/keep.tmp.csv")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_key_policy() {
+    let ctx = S3TestContext::new("key-policy").await;
+    ctx.write("tenant-b/secret.txt", b"not yours").await;
+
+    let allowed = ctx.path("tenant-a/**");
+    let denied = ctx.path("tenant-a/locked/**");
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Key policy test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_key_policy(
+            KeyPolicy::new(&[allowed.as_str()], &[denied.as_str()])
+                .expect("patterns should compile"),
+        );
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("tenant-a/report.csv"),
+            b"a,b",
+            None,
+        )
+        .await
+        .expect("write within the allowed prefix should succeed");
+    assert_eq!(ctx.read("tenant-a/report.csv").await, b"a,b".to_vec());
+
+    for refused in ["tenant-b/secret.txt", "tenant-a/locked/keys.txt"] {
+        let error = facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &ctx.path(refused),
+                b"overwritten",
+                None,
+            )
+            .await
+            .expect_err("write outside the policy should be refused");
+        assert!(matches!(&error, StorageError::KeyNotAllowed(_)));
+    }
+    assert_eq!(ctx.read("tenant-b/secret.txt").await, b"not yours".to_vec());
+    let error = facade
+        .file_exists(&ctx.path("tenant-b/secret.txt"))
+        .await
+        .expect_err("existence checks outside the policy should be refused");
+    assert!(matches!(&error, StorageError::KeyNotAllowed(_)));
+
+    let listed = facade
+        .list_objects(&ctx.path(""))
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(listed, vec![ctx.path("tenant-a/report.csv")]);
+}
+
+#[tokio::test]
+async fn test_legal_hold() {
+    let ctx = S3TestContext::new("legal-hold").await;
+    ctx.write("unlocked.txt", b"no lock").await;
+    let error = ctx
+        .facade()
+        .set_legal_hold(&ctx.path("unlocked.txt"), true)
+        .await
+        .expect_err("a bucket without object lock should be refused");
+    assert!(matches!(&error, StorageError::ExpectationsNotMet(_)));
+
+    let client = raw_client().await;
+    let bucket = format!("fallible-lock-{}", Uuid::new_v4());
+    client
+        .create_bucket()
+        .bucket(&bucket)
+        .object_lock_enabled_for_bucket(true)
+        .send()
+        .await
+        .expect("create_bucket should succeed");
+    let facade = S3Facade::new(&bucket, "Legal hold test")
+        .await
+        .expect("Failed to create S3Facade for test");
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            "evidence.txt",
+            b"under investigation",
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    assert!(!facade.get_legal_hold("evidence.txt").await.unwrap());
+    facade
+        .set_legal_hold("evidence.txt", true)
+        .await
+        .expect("set_legal_hold should succeed");
+    assert!(facade.get_legal_hold("evidence.txt").await.unwrap());
+    facade
+        .set_legal_hold("evidence.txt", false)
+        .await
+        .expect("set_legal_hold should succeed");
+    assert!(!facade.get_legal_hold("evidence.txt").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_retention() {
+    use aws_sdk_s3::types::ObjectLockRetentionMode;
+
+    let ctx = S3TestContext::new("retention").await;
+    ctx.write("unlocked.txt", b"no lock").await;
+    let until = SystemTime::now() + Duration::from_secs(120);
+    let error = ctx
+        .facade()
+        .set_retention(
+            &ctx.path("unlocked.txt"),
+            ObjectLockRetentionMode::Governance,
+            until,
+        )
+        .await
+        .expect_err("a bucket without object lock should be refused");
+    assert!(matches!(&error, StorageError::ExpectationsNotMet(_)));
+
+    let client = raw_client().await;
+    let bucket = format!("fallible-lock-{}", Uuid::new_v4());
+    client
+        .create_bucket()
+        .bucket(&bucket)
+        .object_lock_enabled_for_bucket(true)
+        .send()
+        .await
+        .expect("create_bucket should succeed");
+    let facade = S3Facade::new(&bucket, "Retention test")
+        .await
+        .expect("Failed to create S3Facade for test");
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            "record.txt",
+            b"retained record",
+            None,
+        )
+        .await
+        .expect("write_data should succeed");
+
+    assert!(facade.get_retention("record.txt").await.unwrap().is_none());
+    // Governance mode, so the object can still be cleaned up by an account with bypass permissions
+    facade
+        .set_retention("record.txt", ObjectLockRetentionMode::Governance, until)
+        .await
+        .expect("set_retention should succeed");
+    let (mode, retained_until) = facade
+        .get_retention("record.txt")
+        .await
+        .unwrap()
+        .expect("the retention should be set");
+    assert_eq!(mode, ObjectLockRetentionMode::Governance);
+    let drift = retained_until
+        .duration_since(until)
+        .unwrap_or_else(|e| e.duration());
+    assert!(drift < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_estimate_storage_cost() {
+    use aws_sdk_s3::types::StorageClass;
+
+    let ctx = S3TestContext::new("storage-cost").await;
+    let mib = 1024 * 1024;
+    ctx.write("costs/hot-a.bin", &vec![0u8; 3 * mib]).await;
+    ctx.write("costs/hot-b.bin", &vec![0u8; mib]).await;
+    raw_client()
+        .await
+        .put_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(ctx.path("costs/cold.bin"))
+        .storage_class(StorageClass::Glacier)
+        .body(vec![0u8; 2 * mib].into())
+        .send()
+        .await
+        .expect("put_object should succeed");
+    ctx.write("costs/unpriced.bin", b"x").await;
+    raw_client()
+        .await
+        .copy_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(ctx.path("costs/unpriced.bin"))
+        .copy_source(format!(
+            "{}/{}",
+            TEST_BUCKET_NAME,
+            ctx.path("costs/unpriced.bin")
+        ))
+        .storage_class(StorageClass::OnezoneIa)
+        .send()
+        .await
+        .expect("copy_object should succeed");
+
+    // A GB-month rate of 1024 makes each MiB cost exactly one unit
+    let pricing = StoragePricing {
+        per_gb_month: HashMap::from([
+            (StorageClass::Standard, 1024.0),
+            (StorageClass::Glacier, 512.0),
+        ]),
+    };
+    let estimate = ctx
+        .facade()
+        .estimate_storage_cost(&ctx.path("costs/"), &pricing)
+        .await
+        .expect("estimate_storage_cost should succeed");
+
+    let standard = &estimate.by_class[&StorageClass::Standard];
+    assert_eq!(standard.objects, 2);
+    assert_eq!(standard.bytes, 4 * mib as u64);
+    assert_eq!(standard.monthly_cost, Some(4.0));
+    let glacier = &estimate.by_class[&StorageClass::Glacier];
+    assert_eq!(glacier.objects, 1);
+    assert_eq!(glacier.monthly_cost, Some(1.0));
+    let unpriced = &estimate.by_class[&StorageClass::OnezoneIa];
+    assert_eq!(unpriced.bytes, 1);
+    assert_eq!(unpriced.monthly_cost, None);
+    assert_eq!(estimate.total, 5.0);
+}
+
+#[tokio::test]
+async fn test_download_resumable() {
+    let ctx = S3TestContext::new("download-resumable").await;
+    let content: Vec<u8> = (0..64 * 1024).map(|i| (i % 241) as u8).collect();
+    ctx.write("large.bin", &content).await;
+
+    let dir = temp_dir("download-resumable");
+    let dest = dir.join("large.bin");
+    std::fs::write(&dest, &content[..10_000]).expect("Failed to write partial file");
+
+    ctx.facade()
+        .download_resumable(&ctx.path("large.bin"), &dest)
+        .await
+        .expect("download_resumable should succeed");
+    assert_eq!(
+        std::fs::read(&dest).expect("Failed to read download"),
+        content
+    );
+    assert!(!dir.join("large.bin.etag").exists());
+
+    // A partial file recorded against an older version of the object is restarted
+    std::fs::write(&dest, b"stale bytes").expect("Failed to write partial file");
+    std::fs::write(dir.join("large.bin.etag"), "\"outdated\"").expect("Failed to write etag");
+    ctx.facade()
+        .download_resumable(&ctx.path("large.bin"), &dest)
+        .await
+        .expect("download_resumable should succeed");
+    assert_eq!(
+        std::fs::read(&dest).expect("Failed to read download"),
+        content
+    );
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp directory");
+}
+
+#[tokio::test]
+async fn test_snapshot_at() {
+    ensure_bucket_exists().await;
+    let client = raw_client().await;
+    let bucket = format!("fallible-snapshot-{}", Uuid::new_v4());
+    client
+        .create_bucket()
+        .bucket(&bucket)
+        .send()
+        .await
+        .expect("create_bucket should succeed");
+    client
+        .put_bucket_versioning()
+        .bucket(&bucket)
+        .versioning_configuration(
+            s3::types::VersioningConfiguration::builder()
+                .status(s3::types::BucketVersioningStatus::Enabled)
+                .build(),
+        )
+        .send()
+        .await
+        .expect("put_bucket_versioning should succeed");
+    let facade = S3Facade::new(&bucket, "Snapshot test")
+        .await
+        .expect("Failed to create S3Facade for test");
+
+    // S3 timestamps versions to the second, so each checkpoint is kept over a second clear of every event
+    async fn checkpoint() -> SystemTime {
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let now = SystemTime::now();
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        now
+    }
+    let put = |data: &'static [u8]| {
+        let client = client.clone();
+        let bucket = bucket.clone();
+        async move {
+            client
+                .put_object()
+                .bucket(&bucket)
+                .key("audit/report.csv")
+                .body(data.to_vec().into())
+                .send()
+                .await
+                .expect("put_object should succeed")
+                .version_id()
+                .expect("versioned writes should return a version id")
+                .to_string()
+        }
+    };
+
+    let before_anything = checkpoint().await;
+    let first = put(b"v1").await;
+    let after_first = checkpoint().await;
+    let second = put(b"v2").await;
+    let after_second = checkpoint().await;
+    client
+        .delete_object()
+        .bucket(&bucket)
+        .key("audit/report.csv")
+        .send()
+        .await
+        .expect("delete_object should succeed");
+    let after_delete = checkpoint().await;
+
+    let snapshot = |at| facade.snapshot_at("audit/", at);
+    assert!(snapshot(before_anything).await.unwrap().is_empty());
+    assert_eq!(
+        snapshot(after_first).await.unwrap(),
+        vec![("audit/report.csv".to_string(), first)]
+    );
+    assert_eq!(
+        snapshot(after_second).await.unwrap(),
+        vec![("audit/report.csv".to_string(), second)]
+    );
+    assert!(snapshot(after_delete).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_key_limits() {
+    let ctx = S3TestContext::new("key-limits").await;
+    let base = ctx.path("");
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Key limits test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_key_limits(KeyLimits {
+            max_key_bytes: Some(base.len() + 20),
+            max_key_depth: Some(base.split('/').count() + 1),
+        });
+
+    facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("a/fits.txt"),
+            b"ok",
+            None,
+        )
+        .await
+        .expect("a conforming key should be written");
+
+    for (relative, limit) in [
+        ("a/this-name-is-far-too-long.txt", "byte"),
+        ("a/b/c.txt", "segment"),
+    ] {
+        let error = facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &ctx.path(relative),
+                b"refused",
+                None,
+            )
+            .await
+            .expect_err("a key over the limits should be refused");
+        match &error {
+            StorageError::InvalidKey { reason, .. } => {
+                assert!(reason.contains(limit), "unexpected reason: {}", reason)
+            }
+            other => panic!("expected InvalidKey, got {:?}", other),
+        }
+        assert!(!ctx.facade().file_exists(&ctx.path(relative)).await.unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_write_with_sidecar_checksum() {
+    let ctx = S3TestContext::new("sidecar-checksum").await;
+    let content = b"rows,of,data\n1,2,3\n";
+
+    ctx.facade()
+        .write_with_sidecar_checksum(&ctx.path("export.csv"), content)
+        .await
+        .expect("write_with_sidecar_checksum should succeed");
+
+    assert_eq!(ctx.read("export.csv").await, content.to_vec());
+    let sidecar =
+        String::from_utf8(ctx.read("export.csv.sha256").await).expect("sidecar should be text");
+    let (_, digest) = ctx
+        .facade()
+        .read_and_hash(&ctx.path("export.csv"))
+        .await
+        .expect("read_and_hash should succeed");
+    let expected: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    assert_eq!(sidecar, expected);
+}
+
+#[tokio::test]
+async fn test_list_objects_limited() {
+    let ctx = S3TestContext::new("list-limited").await;
+    for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"] {
+        ctx.write(&format!("page/{}", name), b"entry").await;
+    }
+
+    let (keys, truncated) = ctx
+        .facade()
+        .list_objects_limited(&ctx.path("page/"), 3)
+        .await
+        .expect("list_objects_limited should succeed");
+    assert_eq!(
+        keys,
+        vec![
+            ctx.path("page/a.txt"),
+            ctx.path("page/b.txt"),
+            ctx.path("page/c.txt")
+        ]
+    );
+    assert!(truncated);
+
+    let (keys, truncated) = ctx
+        .facade()
+        .list_objects_limited(&ctx.path("page/"), 5)
+        .await
+        .expect("list_objects_limited should succeed");
+    assert_eq!(keys.len(), 5);
+    assert!(!truncated);
+}
+
+#[tokio::test]
+async fn test_find_duplicates() {
+    let ctx = S3TestContext::new("find-duplicates").await;
+    ctx.write("media/original.jpg", b"the same bytes").await;
+    ctx.write("media/unique.jpg", b"different byte").await;
+    ctx.write("media/other-size.jpg", b"short").await;
+    // Uploaded with a SHA-256 checksum, so its hash can come from S3 rather than a download
+    raw_client()
+        .await
+        .put_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(ctx.path("media/nested/copy.jpg"))
+        .checksum_algorithm(s3::types::ChecksumAlgorithm::Sha256)
+        .body(b"the same bytes".to_vec().into())
+        .send()
+        .await
+        .expect("put_object should succeed");
+
+    let groups = ctx
+        .facade()
+        .find_duplicates(&ctx.path("media/"))
+        .await
+        .expect("find_duplicates should succeed");
+
+    assert_eq!(
+        groups,
+        vec![vec![
+            ctx.path("media/nested/copy.jpg"),
+            ctx.path("media/original.jpg")
+        ]]
+    );
+}
+
+#[tokio::test]
+async fn test_write_retries_encrypt_once() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    ensure_bucket_exists().await;
+    let client = raw_client().await;
+    let bucket = format!("fallible-write-retry-{}", Uuid::new_v4());
+    client
+        .create_bucket()
+        .bucket(&bucket)
+        .send()
+        .await
+        .expect("create_bucket should succeed");
+
+    let retries = Arc::new(AtomicU32::new(0));
+    let counted = Arc::clone(&retries);
+    let facade = S3Facade::new(&bucket, "Write retry test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_write_retries(WriteRetries {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+        })
+        .with_retry_classifier(move |_| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            true
+        });
+
+    // Writing to a deleted bucket fails the same way on every attempt
+    client
+        .delete_bucket()
+        .bucket(&bucket)
+        .send()
+        .await
+        .expect("delete_bucket should succeed");
+    let encryptions = AtomicU32::new(0);
+    let written = facade
+        .write_data(
+            "retried.txt",
+            b"encrypt me once",
+            Some(|data: &[u8]| {
+                encryptions.fetch_add(1, Ordering::SeqCst);
+                Ok(data.iter().rev().copied().collect())
+            }),
+        )
+        .await;
+
+    assert!(written.is_err());
+    assert_eq!(retries.load(Ordering::SeqCst), 2);
+    assert_eq!(encryptions.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_read_concatenated() {
+    use futures::TryStreamExt;
+
+    let ctx = S3TestContext::new("read-concatenated").await;
+    // Written out of order, to show the stream follows key order rather than write order
+    ctx.write("data/0002", b" chunks").await;
+    ctx.write("data/0000", b"three").await;
+    ctx.write("data/0001", b" ordered").await;
+
+    let stream = ctx
+        .facade()
+        .read_concatenated(&ctx.path("data/"))
+        .await
+        .expect("read_concatenated should succeed");
+    let chunks: Vec<bytes::Bytes> = stream
+        .try_collect()
+        .await
+        .expect("the stream should complete");
+
+    assert_eq!(chunks.concat(), b"three ordered chunks".to_vec());
+}
+
+#[tokio::test]
+async fn test_soft_delete_and_restore() {
+    let ctx = S3TestContext::new("soft-delete").await;
+    let facade = ctx.facade();
+    let path = ctx.path("invoice.pdf");
+    ctx.write("invoice.pdf", b"amount due").await;
+
+    facade
+        .soft_delete(&path, Duration::from_secs(86_400))
+        .await
+        .expect("soft_delete should succeed");
+    assert!(!facade.file_exists(&path).await.unwrap());
+    let trashed = format!(".trash/{}", path);
+    assert!(facade.file_exists(&trashed).await.unwrap());
+    assert!(
+        object_tags(&trashed)
+            .await
+            .contains_key("fallible-purge-after")
+    );
+
+    facade.restore(&path).await.expect("restore should succeed");
+    assert_eq!(ctx.read("invoice.pdf").await, b"amount due".to_vec());
+    assert!(!facade.file_exists(&trashed).await.unwrap());
+    assert!(object_tags(&path).await.is_empty());
+
+    // Trash is only purged once the clock passes its retention
+    ctx.write("expired.pdf", b"old").await;
+    ctx.write("recent.pdf", b"new").await;
+    facade
+        .soft_delete(&ctx.path("expired.pdf"), Duration::from_secs(60))
+        .await
+        .expect("soft_delete should succeed");
+    facade
+        .soft_delete(&ctx.path("recent.pdf"), Duration::from_secs(86_400))
+        .await
+        .expect("soft_delete should succeed");
+    let sweeper = S3Facade::new(TEST_BUCKET_NAME, "Trash sweeper test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_clock(FixedClock(SystemTime::now() + Duration::from_secs(3_600)));
+
+    let purged = sweeper
+        .empty_trash()
+        .await
+        .expect("empty_trash should succeed");
+    assert!(purged.contains(&ctx.path("expired.pdf")));
+    assert!(!purged.contains(&ctx.path("recent.pdf")));
+    assert!(
+        !facade
+            .file_exists(&format!(".trash/{}", ctx.path("expired.pdf")))
+            .await
+            .unwrap()
+    );
+    assert!(
+        facade
+            .file_exists(&format!(".trash/{}", ctx.path("recent.pdf")))
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_read_matching() {
+    let ctx = S3TestContext::new("read-matching").await;
+    let path = ctx.path("ledger.csv");
+    ctx.write("ledger.csv", b"balance,100").await;
+    let etag = object_etag(&path).await;
+
+    let data = ctx
+        .facade()
+        .read_matching(&path, &etag)
+        .await
+        .expect("read_matching should succeed with the current ETag");
+    assert_eq!(data, b"balance,100".to_vec());
+
+    ctx.write("ledger.csv", b"balance,250").await;
+    let error = ctx
+        .facade()
+        .read_matching(&path, &etag)
+        .await
+        .expect_err("A stale ETag should be rejected");
+    match &error {
+        StorageError::Conflict { expected_etag, .. } => assert_eq!(*expected_etag, etag),
+        other => panic!("Expected Conflict, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_write_data_if_match() {
+    let ctx = S3TestContext::new("write-if-match").await;
+    let path = ctx.path("counter.txt");
+    ctx.write("counter.txt", b"1").await;
+    let facade = ctx.facade();
+
+    let (data, etag) = facade
+        .read_with_etag(&path)
+        .await
+        .expect("read_with_etag should succeed");
+    assert_eq!(data, b"1");
+    assert_eq!(etag, object_etag(&path).await);
+
+    facade
+        .write_data_if_match(&path, b"2", &etag)
+        .await
+        .expect("write_data_if_match should succeed with the current ETag");
+    let (data, etag) = facade.read_with_etag(&path).await.unwrap();
+    assert_eq!(data, b"2");
+
+    // Another writer gets in between this read and write
+    ctx.write("counter.txt", b"10").await;
+    let error = facade
+        .write_data_if_match(&path, b"3", &etag)
+        .await
+        .expect_err("A stale ETag should be rejected");
+    match &error {
+        StorageError::Conflict { expected_etag, .. } => assert_eq!(*expected_etag, etag),
+        other => panic!("Expected Conflict, got {:?}", other),
+    }
+    assert_eq!(ctx.read("counter.txt").await, b"10");
+}
+
+/// Records the method and URI of every request the SDK sends, EG `PUT https://...`
+#[derive(Debug, Clone, Default)]
+struct RecordRequests(Arc<Mutex<Vec<String>>>);
+
+impl Intercept for RecordRequests {
+    fn name(&self) -> &'static str {
+        "RecordRequests"
+    }
+
+    fn read_before_transmit(
+        &self,
+        context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let request = context.request();
+        let recorded = format!("{} {}", request.method(), request.uri());
+        self.0.lock().unwrap().push(recorded);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_split_endpoints() {
+    let requests = RecordRequests::default();
+    // Neither endpoint resolves, so requests are recorded but never reach a server
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Split endpoint test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_split_endpoints("http://read.invalid", "http://write.invalid")
+        .with_interceptor(requests.clone());
+
+    let _ = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            "split/report.csv",
+            None,
+        )
+        .await;
+    let _ = facade.list_objects("split/").await;
+    let reads = std::mem::take(&mut *requests.0.lock().unwrap());
+    assert!(!reads.is_empty());
+    assert!(reads.iter().all(|request| request.contains("read.invalid")));
+
+    let _ = facade
+        .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            "split/report.csv",
+            b"id,total",
+            None,
+        )
+        .await;
+    let _ = facade.delete_file("split/report.csv").await;
+    let writes = std::mem::take(&mut *requests.0.lock().unwrap());
+    assert!(!writes.is_empty());
+    assert!(
+        writes
+            .iter()
+            .all(|request| request.contains("write.invalid"))
+    );
+}
+
+#[tokio::test]
+async fn test_count_matching() {
+    let ctx = S3TestContext::new("count-matching").await;
+    ctx.write("notes.txt", b"a").await;
+    ctx.write("todo.txt", b"b").await;
+    ctx.write("data.csv", b"c").await;
+    ctx.write("archive/old.txt", b"d").await;
+
+    let direct = ctx
+        .facade()
+        .count_matching(&ctx.path(""), "*.txt")
+        .await
+        .expect("count_matching should succeed");
+    assert_eq!(direct, 2);
+
+    let nested = ctx
+        .facade()
+        .count_matching(&ctx.path(""), "**/*.txt")
+        .await
+        .expect("count_matching should succeed");
+    assert_eq!(nested, 3);
+}
+
+#[tokio::test]
+async fn test_write_coalescing() {
+    let ctx = S3TestContext::new("write-coalescing").await;
+    let path = ctx.path("config.json");
+    let requests = RecordRequests::default();
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Write coalescing test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_write_coalescing(true)
+        .with_interceptor(requests.clone());
+
+    let writes = (0..10).map(|_| {
+        facade.write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            b"{\"replicas\": 3}",
+            None,
+        )
+    });
+    for result in futures::future::join_all(writes).await {
+        result.expect("Every coalesced write should succeed");
+    }
+
+    let puts = requests
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request.starts_with("PUT "))
+        .count();
+    assert_eq!(puts, 1);
+    assert_eq!(ctx.read("config.json").await, b"{\"replicas\": 3}".to_vec());
+}
+
+/// Fails every request before it's sent, so a test can exercise retries without a network
+#[derive(Debug)]
+struct RefuseRequests;
+
+impl Intercept for RefuseRequests {
+    fn name(&self) -> &'static str {
+        "RefuseRequests"
+    }
+
+    fn read_before_transmit(
+        &self,
+        _context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        Err("refused by test".into())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_read_data_deadline() {
+    let requests = RecordRequests::default();
+    // Every failure is retryable, so only the deadline stops the read
+    let facade = S3Facade::from_client(offline_client(), "mock-bucket", "Read deadline test")
+        .with_retry_classifier(|_| true)
+        .with_interceptor(requests.clone())
+        .with_interceptor(RefuseRequests);
+
+    // Time is paused, so only the backoff between attempts moves the clock
+    let started = tokio::time::Instant::now();
+    let deadline = started + Duration::from_secs(1);
+    let error = facade
+        .read_data_deadline("missing.bin", deadline)
+        .await
+        .expect_err("A failing read should run out the deadline");
+    assert!(matches!(&error, StorageError::TimedOut { operation, .. } if operation == "read"));
+    assert!(tokio::time::Instant::now() <= deadline);
+    assert!(started.elapsed() >= Duration::from_millis(750));
+    assert!(requests.0.lock().unwrap().len() > 1);
+
+    // A deadline already passed starts no attempts at all
+    requests.0.lock().unwrap().clear();
+    let error = facade
+        .read_data_deadline("missing.bin", tokio::time::Instant::now())
+        .await
+        .expect_err("A passed deadline should fail straight away");
+    assert!(matches!(&error, StorageError::TimedOut { .. }));
+    assert!(requests.0.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_list_pages() {
+    use futures::TryStreamExt;
+
+    let ctx = S3TestContext::new("list-pages").await;
+    for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"] {
+        ctx.write(name, b"page me").await;
+    }
+
+    let facade = ctx.facade();
+    let pages: Vec<ListingPage> = facade
+        .list_pages(ctx.prefix(), Some(2))
+        .try_collect()
+        .await
+        .expect("list_pages should succeed");
+
+    let keys: Vec<Vec<String>> = pages
+        .iter()
+        .map(|page| page.keys.iter().map(|entry| entry.key.clone()).collect())
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            vec![ctx.path("a.txt"), ctx.path("b.txt")],
+            vec![ctx.path("c.txt"), ctx.path("d.txt")],
+            vec![ctx.path("e.txt")],
+        ]
+    );
+    assert!(
+        pages[..2]
+            .iter()
+            .all(|page| page.is_truncated && page.next_token.is_some())
+    );
+    let last = pages.last().expect("There should be a final page");
+    assert!(!last.is_truncated);
+    assert_eq!(last.next_token, None);
+}
+
+#[tokio::test]
+async fn test_list_objects_stream() {
+    use futures::TryStreamExt;
+
+    let ctx = S3TestContext::new("list-stream").await;
+    let facade = ctx.facade();
+    // One more than S3's page size, so the stream has to fetch a second page
+    let entries: Vec<(String, Vec<u8>)> = (0..1001)
+        .map(|i| (ctx.path(&format!("key-{:04}.txt", i)), b"streamed".to_vec()))
+        .collect();
+    for (path, result) in facade.write_many(entries, DEFAULT_CONCURRENCY).await {
+        result.unwrap_or_else(|e| panic!("writing {} failed: {}", path, e));
+    }
+
+    let mut count = 0;
+    let mut keys = std::pin::pin!(facade.list_objects_stream(ctx.prefix()));
+    while let Some(key) = keys
+        .try_next()
+        .await
+        .expect("list_objects_stream should succeed")
+    {
+        assert!(key.starts_with(ctx.prefix()));
+        count += 1;
+    }
+    assert_eq!(count, 1001);
+}
+
+#[tokio::test]
+async fn test_directory_bucket_uses_session_auth() {
+    let name = "fallible-tests--use1-az4--x-s3";
+
+    let facade = S3Facade::new(name, "Directory bucket test")
+        .await
+        .expect("A directory bucket name should be accepted without head_bucket");
+    assert!(facade.is_directory_bucket());
+    assert!(
+        !S3Facade::new(TEST_BUCKET_NAME, "General purpose bucket test")
+            .await
+            .expect("Failed to create S3Facade for test")
+            .is_directory_bucket()
+    );
+
+    let error = facade
+        .list_object_versions("reports/q1.csv")
+        .await
+        .expect_err("Directory buckets have no versions to list");
+    assert!(matches!(&error, StorageError::Unsupported(_)));
+}
+
+#[tokio::test]
+async fn test_reorganize() {
+    let ctx = S3TestContext::new("reorganize").await;
+    ctx.write("a.txt", b"alpha").await;
+    ctx.write("b.txt", b"bravo").await;
+    let prefix = ctx.prefix().to_string();
+    let partitioned = |key: &str| format!("{}2024/{}", prefix, &key[prefix.len()..]);
+
+    let report = ctx
+        .facade()
+        .reorganize(ctx.prefix(), partitioned, true, 4)
+        .await
+        .expect("reorganize should succeed");
+    assert_eq!(
+        report.reorganized,
+        vec![
+            (ctx.path("a.txt"), ctx.path("2024/a.txt")),
+            (ctx.path("b.txt"), ctx.path("2024/b.txt")),
+        ]
+    );
+    assert!(report.failed.is_empty());
+
+    assert_eq!(ctx.read("2024/a.txt").await, b"alpha".to_vec());
+    assert_eq!(ctx.read("2024/b.txt").await, b"bravo".to_vec());
+    assert!(!ctx.facade().file_exists(&ctx.path("a.txt")).await.unwrap());
+    assert!(!ctx.facade().file_exists(&ctx.path("b.txt")).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_list_object_versions() {
+    ensure_bucket_exists().await;
+    let client = raw_client().await;
+    let bucket = format!("fallible-versions-{}", Uuid::new_v4());
+    client
+        .create_bucket()
+        .bucket(&bucket)
+        .send()
+        .await
+        .expect("create_bucket should succeed");
+    client
+        .put_bucket_versioning()
+        .bucket(&bucket)
+        .versioning_configuration(
+            s3::types::VersioningConfiguration::builder()
+                .status(s3::types::BucketVersioningStatus::Enabled)
+                .build(),
+        )
+        .send()
+        .await
+        .expect("put_bucket_versioning should succeed");
+    let facade = S3Facade::new(&bucket, "Versions test")
+        .await
+        .expect("Failed to create S3Facade for test");
+
+    for data in [b"v1", b"v2", b"v3"] {
+        facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                "ledger/balance.csv",
+                data,
+                None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+
+    let versions = facade
+        .list_object_versions("ledger/balance.csv")
+        .await
+        .expect("list_object_versions should succeed");
+    assert_eq!(versions.len(), 3);
+    assert!(
+        versions
+            .iter()
+            .all(|version| version.starts_with("ledger/balance.csv?versionId="))
+    );
+    let unique: std::collections::HashSet<&String> = versions.iter().collect();
+    assert_eq!(unique.len(), 3, "Each write should have its own version id");
+}
+
+#[tokio::test]
+async fn test_restore_and_delete_version() {
+    ensure_bucket_exists().await;
+    let client = raw_client().await;
+    let bucket = format!("fallible-restore-{}", Uuid::new_v4());
+    client
+        .create_bucket()
+        .bucket(&bucket)
+        .send()
+        .await
+        .expect("create_bucket should succeed");
+    client
+        .put_bucket_versioning()
+        .bucket(&bucket)
+        .versioning_configuration(
+            s3::types::VersioningConfiguration::builder()
+                .status(s3::types::BucketVersioningStatus::Enabled)
+                .build(),
+        )
+        .send()
+        .await
+        .expect("put_bucket_versioning should succeed");
+    let facade = S3Facade::new(&bucket, "Restore version test")
+        .await
+        .expect("Failed to create S3Facade for test");
+    let path = "ledger/balance.csv";
+
+    for data in [b"good", b"oops"] {
+        facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                path, data, None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+
+    let mut good_version = None;
+    for version in facade.list_object_versions(path).await.unwrap() {
+        let version_id = version
+            .strip_prefix("ledger/balance.csv?versionId=")
+            .expect("versions should be listed with their key")
+            .to_string();
+        if facade.read_version(path, &version_id).await.unwrap() == b"good" {
+            good_version = Some(version_id);
+        }
+    }
+    let good_version = good_version.expect("the first write should have a version");
+
+    // Versions are held to the read limit like current objects
+    let limited = S3Facade::new(&bucket, "Restore version test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_max_read_bytes(3);
+    assert!(matches!(
+        limited.read_version(path, &good_version).await,
+        Err(StorageError::TooLarge {
+            size: 4,
+            limit: 3,
+            ..
+        })
+    ));
+
+    // Restoring adds the old bytes as a new current version
+    facade
+        .restore_version(path, &good_version)
+        .await
+        .expect("restore_version should succeed");
+    assert_eq!(
+        facade
+            .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                path, None,
+            )
+            .await
+            .unwrap(),
+        b"good"
+    );
+    assert_eq!(facade.list_object_versions(path).await.unwrap().len(), 3);
+
+    facade
+        .delete_version(path, &good_version)
+        .await
+        .expect("delete_version should succeed");
+    let error = facade
+        .read_version(path, &good_version)
+        .await
+        .expect_err("a deleted version can't be read");
+    assert!(matches!(&error, StorageError::NotFound(_)));
+    assert_eq!(facade.list_object_versions(path).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_write_data_returning() {
+    let ctx = S3TestContext::new("write-returning").await;
+    let path = ctx.path("unversioned.txt");
+    let output = ctx
+        .facade()
+        .write_data_returning::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            b"first",
+            None,
+        )
+        .await
+        .expect("write_data_returning should succeed");
+    assert_eq!(output.etag, Some(object_etag(&path).await));
+
+    let client = raw_client().await;
+    let bucket = format!("fallible-returning-{}", Uuid::new_v4());
+    client
+        .create_bucket()
+        .bucket(&bucket)
+        .send()
+        .await
+        .expect("create_bucket should succeed");
+    client
+        .put_bucket_versioning()
+        .bucket(&bucket)
+        .versioning_configuration(
+            s3::types::VersioningConfiguration::builder()
+                .status(s3::types::BucketVersioningStatus::Enabled)
+                .build(),
+        )
+        .send()
+        .await
+        .expect("put_bucket_versioning should succeed");
+    let facade = S3Facade::new(&bucket, "Write returning test")
+        .await
+        .expect("Failed to create S3Facade for test");
+
+    let mut version_ids = Vec::new();
+    for data in [b"v1", b"v2"] {
+        let output = facade
+            .write_data_returning::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                "ledger.csv",
+                data,
+                None,
+            )
+            .await
+            .expect("write_data_returning should succeed");
+        version_ids.push(
+            output
+                .version_id
+                .expect("a versioned bucket should return a version id"),
+        );
+    }
+    assert_ne!(version_ids[0], version_ids[1]);
+    assert_eq!(
+        facade
+            .read_version("ledger.csv", &version_ids[0])
+            .await
+            .unwrap(),
+        b"v1"
+    );
+}
+
+#[tokio::test]
+async fn test_read_rate_limit() {
+    let ctx = S3TestContext::new("read-rate-limit").await;
+    let data: Vec<u8> = (0..128 * 1024).map(|i| (i % 251) as u8).collect();
+    ctx.write("throttled.bin", &data).await;
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Read rate limit test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_read_rate_limit(64 * 1024);
+
+    // 128KiB at 64KiB a second can't arrive in under two seconds
+    let started = std::time::Instant::now();
+    let read = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("throttled.bin"),
+            None,
+        )
+        .await
+        .expect("read_data should succeed");
+    assert!(started.elapsed() >= Duration::from_secs(2));
+    assert_eq!(read, data);
+}
+
+#[tokio::test]
+async fn test_get_file_metadata() {
+    let ctx = S3TestContext::new("get-file-metadata").await;
+    let data = b"twenty-two bytes long!";
+    ctx.write("sized.txt", data).await;
+    let facade = ctx.facade();
+
+    let metadata = facade
+        .get_file_metadata(&ctx.path("sized.txt"))
+        .await
+        .expect("get_file_metadata should succeed");
+    assert_eq!(metadata.size, data.len() as u64);
+    assert!(metadata.last_modified.is_some());
+    assert!(metadata.etag.is_some());
+
+    assert!(
+        facade
+            .get_file_metadata(&ctx.path("missing.txt"))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_last_modified() {
+    let ctx = S3TestContext::new("last-modified").await;
+    ctx.write("page.html", b"<h1>hello</h1>").await;
+    let facade = ctx.facade();
+
+    let modified = facade
+        .last_modified(&ctx.path("page.html"))
+        .await
+        .expect("last_modified should succeed");
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_else(|e| e.duration());
+    assert!(age < Duration::from_secs(30));
+
+    let error = facade
+        .last_modified(&ctx.path("missing.html"))
+        .await
+        .expect_err("a missing object has no modification time");
+    assert!(matches!(&error, StorageError::NotFound(_)));
+}
+
+#[tokio::test]
+async fn test_capabilities() {
+    let ctx = S3TestContext::new("capabilities").await;
+    let capabilities = ctx.facade().capabilities();
+    assert!(capabilities.versioning);
+    assert!(capabilities.server_side_copy);
+    assert!(capabilities.range_reads);
+}
+
+#[tokio::test]
+async fn test_read_stream() {
+    let ctx = S3TestContext::new("read-stream").await;
+    let data: Vec<u8> = (0..10 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    ctx.write("large.bin", &data).await;
+    let facade = ctx.facade();
+
+    let mut reader = facade
+        .read_stream(&ctx.path("large.bin"))
+        .await
+        .expect("read_stream should succeed");
+    let mut streamed = Vec::new();
+    reader
+        .read_to_end(&mut streamed)
+        .await
+        .expect("stream should read to the end");
+
+    let buffered = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("large.bin"),
+            None,
+        )
+        .await
+        .expect("read_data should succeed");
+    assert_eq!(streamed.len(), data.len());
+    assert_eq!(streamed, buffered);
+
+    assert!(facade.read_stream(&ctx.path("missing.bin")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_write_stream() {
+    let ctx = S3TestContext::new("write-stream").await;
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Write stream test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_part_size(5 * 1024 * 1024);
+
+    // Just over two parts, so the upload has a short third part to stitch on the end
+    let data: Vec<u8> = (0..11 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    facade
+        .write_stream(&ctx.path("parts.bin"), data.as_slice())
+        .await
+        .expect("write_stream should succeed");
+
+    assert!(object_etag(&ctx.path("parts.bin")).await.ends_with("-3\""));
+    assert_eq!(ctx.read("parts.bin").await, data);
+}
+
+#[tokio::test]
+async fn test_migrate_between_backends() {
+    let ctx = S3TestContext::new("migrate").await;
+    let root = std::env::temp_dir().join(format!("fallible-migrate-{}", Uuid::new_v4()));
+    tokio::fs::create_dir_all(&root).await.unwrap();
+    let local = LocalFacade::new(&root, "Migrate test")
+        .await
+        .expect("Failed to create LocalFacade for test");
+    ctx.write("from-s3.txt", b"bucket to disk").await;
+
+    migrate(
+        ctx.facade(),
+        &ctx.path("from-s3.txt"),
+        &local,
+        "from-s3.txt",
+    )
+    .await
+    .expect("S3 to local migrate should succeed");
+    assert_eq!(
+        std::fs::read(root.join("from-s3.txt")).unwrap(),
+        b"bucket to disk"
+    );
+
+    migrate(
+        &local,
+        "from-s3.txt",
+        ctx.facade(),
+        &ctx.path("back-again.txt"),
+    )
+    .await
+    .expect("local to S3 migrate should succeed");
+    assert_eq!(ctx.read("back-again.txt").await, b"bucket to disk");
+
+    // Same bucket copies stay server side
+    migrate(
+        ctx.facade(),
+        &ctx.path("back-again.txt"),
+        ctx.facade(),
+        &ctx.path("copied.txt"),
+    )
+    .await
+    .expect("same bucket migrate should succeed");
+    assert_eq!(ctx.read("copied.txt").await, b"bucket to disk");
+
+    assert!(
+        migrate(
+            ctx.facade(),
+            &ctx.path("missing.txt"),
+            &local,
+            "missing.txt"
+        )
+        .await
+        .is_err()
+    );
+    assert!(!local.file_exists("missing.txt").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_new_with_config_uses_endpoint() {
+    // The existence check goes to the overridden endpoint, which doesn't resolve, rather than AWS
+    let result = S3Facade::new_with_config(
+        TEST_BUCKET_NAME,
+        "Custom endpoint test",
+        Some("http://minio.invalid:9000".to_string()),
+        Some("us-east-1".to_string()),
+        true,
+    )
+    .await;
+    assert!(result.is_err());
+
+    S3Facade::new_with_config(TEST_BUCKET_NAME, "Default config test", None, None, false)
+        .await
+        .expect("defaults should behave like S3Facade::new");
+}
+
+/// A client against an endpoint which doesn't resolve, with static credentials, so tests using it don't need AWS
+fn offline_client() -> s3::Client {
+    let config = s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(s3::config::Region::new("eu-west-2"))
+        .endpoint_url("http://mock.invalid")
+        .credentials_provider(s3::config::Credentials::new(
+            "test", "test", None, None, "static",
+        ))
+        .build();
+    s3::Client::from_conf(config)
+}
+
+#[tokio::test]
+async fn test_from_client_sends_no_requests() {
+    let facade = S3Facade::from_client(offline_client(), "mock-bucket", "Injected client test");
+
+    assert!(matches!(
+        &facade.metadata().id,
+        DataStoreId::S3(arn) if arn == "arn:aws:s3:::mock-bucket"
+    ));
+    assert_eq!(facade.metadata().name, "mock-bucket");
+
+    // Requests go to the injected client's endpoint, and failing to reach it isn't mistaken for a missing file
+    assert!(facade.file_exists("anything.txt").await.is_err());
+}
+
+#[tokio::test]
+async fn test_builder_applies_config() {
+    let requests = RecordRequests::default();
+    // The bucket check is skipped and the endpoint doesn't resolve, so nothing reaches a server
+    let facade = S3Facade::builder("mock-bucket", "Builder test")
+        .endpoint_url("http://builder.invalid")
+        .region("eu-west-2")
+        .force_path_style(true)
+        .operation_timeout(Duration::from_secs(5))
+        .max_read_bytes(1024)
+        .skip_bucket_check(true)
+        .build()
+        .await
+        .expect("build should succeed without sending requests")
+        .with_interceptor(requests.clone());
+
+    assert!(matches!(
+        &facade.metadata().id,
+        DataStoreId::S3(arn) if arn == "arn:aws:s3:::mock-bucket"
+    ));
+    assert!(requests.0.lock().unwrap().is_empty());
+
+    let _ = facade.file_exists("reports/q1.pdf").await;
+    let sent = requests.0.lock().unwrap();
+    assert!(!sent.is_empty());
+    assert!(
+        sent.iter()
+            .all(|request| request.contains("http://builder.invalid/mock-bucket/reports/q1.pdf"))
+    );
+}
+
+#[tokio::test]
+async fn test_facades_share_config() {
+    // Loaded once and shared, so neither facade reads the environment
+    let config = aws::SdkConfig::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(s3::config::Region::new("eu-west-2"))
+        .endpoint_url("http://shared.invalid")
+        .credentials_provider(s3::config::SharedCredentialsProvider::new(
+            s3::config::Credentials::new("test", "test", None, None, "static"),
+        ))
+        .build();
+
+    for bucket in ["bucket-a", "bucket-b"] {
+        let requests = RecordRequests::default();
+        let facade = S3Facade::builder(bucket, "Shared config test")
+            .sdk_config(&config)
+            .force_path_style(true)
+            .skip_bucket_check(true)
+            .build()
+            .await
+            .expect("build should succeed without sending requests")
+            .with_interceptor(requests.clone());
+
+        let _ = facade.file_exists("reports/q1.pdf").await;
+        let sent = requests.0.lock().unwrap();
+        assert!(!sent.is_empty());
+        let expected = format!("http://shared.invalid/{}/reports/q1.pdf", bucket);
+        assert!(sent.iter().all(|request| request.contains(&expected)));
+    }
+
+    // The bucket check goes to the shared configuration's endpoint, which can't be reached
+    assert!(
+        S3Facade::new_with_shared_config(&config, "bucket-a", "Shared config test")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_client_config_sets_attempts() {
+    let requests = RecordRequests::default();
+    let facade = S3Facade::from_client(offline_client(), "mock-bucket", "Client config test")
+        .with_client_config(ClientConfig {
+            max_attempts: 2,
+            retry_mode: s3::config::retry::RetryMode::Standard,
+            operation_timeout: Some(Duration::from_secs(10)),
+        })
+        .with_interceptor(requests.clone());
+
+    // The endpoint can't be reached, which the SDK retries as a transient failure
+    assert!(facade.file_exists("anything.txt").await.is_err());
+    assert_eq!(requests.0.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_set_and_get_tags() {
+    let ctx = S3TestContext::new("object-tags").await;
+    ctx.write("invoice.pdf", b"invoice").await;
+    let facade = ctx.facade();
+    let path = ctx.path("invoice.pdf");
+
+    assert!(facade.get_tags(&path).await.unwrap().is_empty());
+
+    let tags = HashMap::from([
+        ("cost-centre".to_string(), "finance".to_string()),
+        ("retention".to_string(), "7y".to_string()),
+    ]);
+    facade
+        .set_tags(&path, tags.clone())
+        .await
+        .expect("set_tags should succeed");
+    assert_eq!(facade.get_tags(&path).await.unwrap(), tags);
+    assert_eq!(object_tags(&path).await, tags);
+}
+
+#[tokio::test]
+async fn test_set_tags_checks_limits() {
+    let requests = RecordRequests::default();
+    let facade = S3Facade::from_client(offline_client(), "mock-bucket", "Tag limits test")
+        .with_interceptor(requests.clone());
+
+    let too_many: HashMap<String, String> = (0..11)
+        .map(|i| (format!("key-{}", i), "value".to_string()))
+        .collect();
+    let long_key = HashMap::from([("k".repeat(129), "value".to_string())]);
+    let long_value = HashMap::from([("key".to_string(), "v".repeat(257))]);
+
+    for tags in [too_many, long_key, long_value] {
+        let error = facade.set_tags("tagged.txt", tags).await.unwrap_err();
+        assert!(error.to_string().contains("limit") || error.to_string().contains("at most"));
+    }
+
+    // Invalid tags are refused before anything is sent
+    assert!(requests.0.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_read_range() {
+    let ctx = S3TestContext::new("read-range").await;
+    let data: Vec<u8> = (0..64).collect();
+    ctx.write("header.bin", &data).await;
+    let facade = ctx.facade();
+    let path = ctx.path("header.bin");
+
+    let range = facade
+        .read_range(&path, 10, Some(20))
+        .await
+        .expect("read_range should succeed");
+    assert_eq!(range, data[10..20]);
+
+    let tail = facade
+        .read_range(&path, 60, None)
+        .await
+        .expect("an open ended range should read to the end");
+    assert_eq!(tail, data[60..]);
+
+    assert!(facade.read_range(&path, 20, Some(10)).await.is_err());
+    assert!(facade.read_range(&path, 64, None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_delete_files() {
+    let ctx = S3TestContext::new("delete-files").await;
+    let facade = ctx.facade();
+    let paths: Vec<String> = (0..5).map(|i| ctx.path(&format!("{}.txt", i))).collect();
+    for i in 0..5 {
+        ctx.write(&format!("{}.txt", i), b"batch").await;
+    }
+
+    let failed = facade
+        .delete_files(&paths[..4])
+        .await
+        .expect("delete_files should succeed");
+    assert!(failed.is_empty(), "unexpected failures: {:?}", failed);
+
+    for path in &paths[..4] {
+        assert!(!facade.file_exists(path).await.unwrap());
+    }
+    assert!(facade.file_exists(&paths[4]).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_delete_prefix() {
+    let ctx = S3TestContext::new("delete-prefix").await;
+    let facade = ctx.facade();
+    for relative in [
+        "tenant-1/a.txt",
+        "tenant-1/docs/b.txt",
+        "tenant-1/docs/deep/c.txt",
+        "tenant-10/keep.txt",
+    ] {
+        ctx.write(relative, b"tenant data").await;
+    }
+
+    facade
+        .delete_prefix(&ctx.path("tenant-1"))
+        .await
+        .expect("delete_prefix should succeed");
+    assert!(
+        facade
+            .list_objects(&ctx.path("tenant-1/"))
+            .await
+            .expect("list_objects should succeed")
+            .is_empty()
+    );
+    assert!(
+        facade
+            .file_exists(&ctx.path("tenant-10/keep.txt"))
+            .await
+            .unwrap()
+    );
+
+    // Deleting it again finds nothing to delete
+    facade
+        .delete_prefix(&ctx.path("tenant-1"))
+        .await
+        .expect("deleting an empty prefix should succeed");
+}
+
+#[tokio::test]
+async fn test_prefix_exists() {
+    let ctx = S3TestContext::new("prefix-exists").await;
+    let facade = ctx.facade();
+    ctx.write("parent/child/file.txt", b"nested").await;
+
+    assert!(facade.prefix_exists(&ctx.path("parent")).await.unwrap());
+    assert!(
+        facade
+            .prefix_exists(&ctx.path("parent/child/"))
+            .await
+            .unwrap()
+    );
+    assert!(!facade.prefix_exists(&ctx.path("sibling")).await.unwrap());
+    // Only whole directory names match, though `par` is a prefix of the key
+    assert!(!facade.prefix_exists(&ctx.path("par")).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_presign_get_and_put() {
+    // Presigning is local, so an offline client is enough
+    let facade = S3Facade::from_client(offline_client(), "mock-bucket", "Presign test");
+
+    let get = facade
+        .presign_get("reports/q1.pdf", Duration::from_secs(3600))
+        .await
+        .expect("presign_get should succeed");
+    assert!(get.contains("reports/q1.pdf"));
+    assert!(get.contains("X-Amz-Signature="));
+    assert!(get.contains("X-Amz-Expires=3600"));
+
+    let put = facade
+        .presign_put("uploads/avatar.png", Duration::from_secs(300))
+        .await
+        .expect("presign_put should succeed");
+    assert!(put.contains("uploads/avatar.png"));
+    assert!(put.contains("X-Amz-Expires=300"));
+
+    let week = Duration::from_secs(7 * 24 * 60 * 60);
+    assert!(facade.presign_get("reports/q1.pdf", week).await.is_ok());
+    assert!(
+        facade
+            .presign_put("uploads/avatar.png", week + Duration::from_secs(1))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_list_directory() {
+    let ctx = S3TestContext::new("list-directory").await;
+    let facade = ctx.facade();
+    for relative in ["a.txt", "b.txt", "sub/c.txt", "sub/deep/d.txt"] {
+        ctx.write(relative, b"listed").await;
+    }
+
+    let listing = facade
+        .list_directory(&ctx.path(""))
+        .await
+        .expect("list_directory should succeed");
+    assert_eq!(listing.files, vec![ctx.path("a.txt"), ctx.path("b.txt")]);
+    assert_eq!(listing.directories, vec![ctx.path("sub/")]);
+    assert!(!listing.files.contains(&ctx.path("sub/c.txt")));
+
+    // The trailing slash is optional
+    let listing = facade
+        .list_directory(&ctx.path("sub"))
+        .await
+        .expect("list_directory should succeed");
+    assert_eq!(listing.files, vec![ctx.path("sub/c.txt")]);
+    assert_eq!(listing.directories, vec![ctx.path("sub/deep/")]);
+}
+
+#[tokio::test]
+async fn test_list_objects_detailed() {
+    let ctx = S3TestContext::new("list-detailed").await;
+    let facade = ctx.facade();
+    ctx.write("b.txt", b"four").await;
+    ctx.write("a.txt", b"one").await;
+
+    let entries = facade
+        .list_objects_detailed(&ctx.path(""))
+        .await
+        .expect("list_objects_detailed should succeed");
+    let keys: Vec<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+    assert_eq!(keys, vec![ctx.path("a.txt"), ctx.path("b.txt")]);
+    assert_eq!(entries[0].size, 3);
+    assert_eq!(entries[1].size, 4);
+    assert!(entries.iter().all(|entry| entry.last_modified.is_some()));
+    assert_eq!(entries[0].storage_class.as_deref(), Some("STANDARD"));
+}
+
+#[tokio::test]
+async fn test_read_archived_object_starts_restore() {
+    use aws_sdk_s3::types::StorageClass;
+
+    let ctx = S3TestContext::new("restore").await;
+    let facade = ctx.facade();
+    ctx.write("hot.txt", b"ready").await;
+    raw_client()
+        .await
+        .put_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(ctx.path("cold.txt"))
+        .storage_class(StorageClass::Glacier)
+        .body(b"archived".to_vec().into())
+        .send()
+        .await
+        .expect("put_object should succeed");
+
+    facade
+        .ensure_retrievable(&ctx.path("hot.txt"))
+        .await
+        .expect("objects outside archive classes are retrievable");
+
+    for _ in 0..2 {
+        // The first read starts the restore, and the second finds it already running
+        let error = facade
+            .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &ctx.path("cold.txt"),
+                None,
+            )
+            .await
+            .expect_err("archived objects can't be read before a restore");
+        assert!(matches!(
+            &error,
+            StorageError::RestoreInProgress(key) if *key == ctx.path("cold.txt")
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_write_data_with_class() {
+    use aws_sdk_s3::types::StorageClass;
+
+    let ctx = S3TestContext::new("write-class").await;
+    let facade = ctx.facade();
+    facade
+        .write_data_with_class(
+            &ctx.path("infrequent.txt"),
+            b"rarely read",
+            StorageClass::StandardIa,
+        )
+        .await
+        .expect("write_data_with_class should succeed");
+    ctx.write("default.txt", b"often read").await;
+
+    let metadata = facade
+        .get_file_metadata(&ctx.path("infrequent.txt"))
+        .await
+        .expect("get_file_metadata should succeed");
+    assert_eq!(metadata.storage_class.as_deref(), Some("STANDARD_IA"));
+    assert_eq!(ctx.read("infrequent.txt").await, b"rarely read");
+
+    // S3 omits the class from head responses for Standard objects
+    let metadata = facade
+        .get_file_metadata(&ctx.path("default.txt"))
+        .await
+        .expect("get_file_metadata should succeed");
+    assert!(matches!(
+        metadata.storage_class.as_deref(),
+        None | Some("STANDARD")
+    ));
+}
+
+#[tokio::test]
+async fn test_write_data_with_options() {
+    let ctx = S3TestContext::new("write-with-options").await;
+    let facade = ctx.facade();
+    let path = ctx.path("config.json");
+
+    facade
+        .write_data_with_options::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path,
+            br#"{"enabled": true}"#,
+            None,
+            WriteOptions {
+                content_type: Some("application/json".to_string()),
+                metadata: HashMap::from([("origin".to_string(), "importer".to_string())]),
+                ..WriteOptions::default()
+            },
+        )
+        .await
+        .expect("write_data_with_options should succeed");
+
+    let metadata = facade
+        .get_file_metadata(&path)
+        .await
+        .expect("get_file_metadata should succeed");
+    assert_eq!(metadata.content_type.as_deref(), Some("application/json"));
+    assert_eq!(
+        metadata.user_metadata,
+        HashMap::from([("origin".to_string(), "importer".to_string())])
+    );
+    assert_eq!(ctx.read("config.json").await, br#"{"enabled": true}"#);
+}
+
+#[tokio::test]
+async fn test_read_into_reuses_buffer() {
+    let ctx = S3TestContext::new("read-into").await;
+    ctx.write("large.txt", &[b'x'; 4096]).await;
+    ctx.write("medium.txt", b"a medium sized object").await;
+    ctx.write("small.txt", b"tiny").await;
+    let facade = ctx.facade();
+
+    let mut buf = Vec::new();
+    facade
+        .read_into(&ctx.path("large.txt"), &mut buf)
+        .await
+        .expect("read_into should succeed");
+    assert_eq!(buf, vec![b'x'; 4096]);
+    let capacity = buf.capacity();
+
+    // Each read replaces the previous contents, rather than appending to them
+    facade
+        .read_into(&ctx.path("medium.txt"), &mut buf)
+        .await
+        .expect("read_into should succeed");
+    assert_eq!(buf, b"a medium sized object");
+    facade
+        .read_into(&ctx.path("small.txt"), &mut buf)
+        .await
+        .expect("read_into should succeed");
+    assert_eq!(buf, b"tiny");
+    assert_eq!(buf.capacity(), capacity);
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn test_write_and_read_compressed() {
+    let ctx = S3TestContext::new("compressed").await;
+    let facade = ctx.facade();
+    let path = ctx.path("report.csv");
+    let data = "id,name,status\n1,widget,active\n"
+        .repeat(1000)
+        .into_bytes();
+
+    facade
+        .write_compressed::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path, &data, None,
+        )
+        .await
+        .expect("write_compressed should succeed");
+
+    let stored = ctx.read("report.csv").await;
+    assert!(stored.len() < data.len() / 10);
+    let head = raw_client()
+        .await
+        .head_object()
+        .bucket(TEST_BUCKET_NAME)
+        .key(&path)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(head.content_encoding(), Some("gzip"));
+
+    let read = facade
+        .read_compressed::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &path, None,
+        )
+        .await
+        .expect("read_compressed should succeed");
+    assert_eq!(read, data);
+
+    // Compression happens before encryption, so the encrypted object is still small
+    let xor = |bytes: &[u8]| -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(bytes.iter().map(|byte| byte ^ 0x5A).collect())
+    };
+    let encrypted_path = ctx.path("report.csv.enc");
+    facade
+        .write_compressed(&encrypted_path, &data, Some(xor))
+        .await
+        .expect("write_compressed should succeed");
+    assert!(ctx.read("report.csv.enc").await.len() < data.len() / 10);
+    assert_eq!(
+        facade
+            .read_compressed(&encrypted_path, Some(xor))
+            .await
+            .unwrap(),
+        data
+    );
+}
+
+#[tokio::test]
+async fn test_content_type_guessing() {
+    let ctx = S3TestContext::new("guess-content-type").await;
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Content type guessing test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_content_type_guessing(true);
+    let content_type = |path: String| {
+        let facade = &facade;
+        async move {
+            facade
+                .get_file_metadata(&path)
+                .await
+                .expect("get_file_metadata should succeed")
+                .content_type
+        }
+    };
+
+    for name in ["index.html", "logo.PNG", "notes"] {
+        facade
+            .write_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+                &ctx.path(name),
+                b"guess my type",
+                None,
+            )
+            .await
+            .expect("write_data should succeed");
+    }
+    assert_eq!(
+        content_type(ctx.path("index.html")).await.as_deref(),
+        Some("text/html")
+    );
+    assert_eq!(
+        content_type(ctx.path("logo.PNG")).await.as_deref(),
+        Some("image/png")
+    );
+    assert_ne!(
+        content_type(ctx.path("notes")).await.as_deref(),
+        Some("text/html")
+    );
+
+    // An explicit content type wins over the guess
+    facade
+        .write_data_with_options::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("template.html"),
+            b"{{ title }}",
+            None,
+            WriteOptions {
+                content_type: Some("text/plain".to_string()),
+                ..WriteOptions::default()
+            },
+        )
+        .await
+        .expect("write_data_with_options should succeed");
+    assert_eq!(
+        content_type(ctx.path("template.html")).await.as_deref(),
+        Some("text/plain")
+    );
+}
+
+#[tokio::test]
+async fn test_errors_are_typed() {
+    let ctx = S3TestContext::new("typed-errors").await;
+    let facade = ctx.facade();
+
+    let error = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("missing.txt"),
+            None,
+        )
+        .await
+        .expect_err("reading a missing object should fail");
+    assert!(matches!(&error, StorageError::NotFound(key) if *key == ctx.path("missing.txt")));
+    let error = facade
+        .get_file_metadata(&ctx.path("missing.txt"))
+        .await
+        .expect_err("a missing object has no metadata");
+    assert!(matches!(&error, StorageError::NotFound(_)));
+    // Methods which call the SDK themselves report missing objects the same way
+    let error = facade
+        .read_and_hash(&ctx.path("missing.txt"))
+        .await
+        .expect_err("a missing object can't be hashed");
+    assert!(matches!(&error, StorageError::NotFound(_)));
+    let error = facade
+        .read_matching(&ctx.path("missing.txt"), "\"etag\"")
+        .await
+        .expect_err("a missing object can't match an ETag");
+    assert!(matches!(&error, StorageError::NotFound(_)));
+
+    ctx.write("plain.txt", b"not encrypted").await;
+    let error = facade
+        .read_data(
+            &ctx.path("plain.txt"),
+            Some(
+                |_: &[u8]| -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+                    Err("bad key".into())
+                },
+            ),
+        )
+        .await
+        .expect_err("a failing decrypt function should fail the read");
+    assert!(matches!(&error, StorageError::Encryption(_)));
+}
+
+#[tokio::test]
+async fn test_write_data_if_absent() {
+    let ctx = S3TestContext::new("if-absent").await;
+    let facade = ctx.facade();
+
+    facade
+        .write_data_if_absent::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("job.lock"),
+            b"first",
+            None,
+        )
+        .await
+        .expect("write_data_if_absent should succeed for a new key");
+    let error = facade
+        .write_data_if_absent::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("job.lock"),
+            b"second",
+            None,
+        )
+        .await
+        .expect_err("an existing object should be left alone");
+    assert!(matches!(&error, StorageError::AlreadyExists(key) if *key == ctx.path("job.lock")));
+    assert_eq!(ctx.read("job.lock").await, b"first");
+}
+
+#[tokio::test]
+async fn test_upload_from_and_download_to() {
+    let ctx = S3TestContext::new("file-transfer").await;
+    let facade = ctx.facade();
+    let dir = temp_dir("file-transfer");
+    // Larger than the default part size, so the upload is multipart
+    let content: Vec<u8> = (0..9 * 1024 * 1024).map(|i| (i % 241) as u8).collect();
+    let src = dir.join("source.bin");
+    std::fs::write(&src, &content).expect("Failed to write source file");
+
+    facade
+        .upload_from(&ctx.path("large.bin"), &src)
+        .await
+        .expect("upload_from should succeed");
+    let dest = dir.join("nested").join("copy.bin");
+    facade
+        .download_to::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("large.bin"),
+            &dest,
+            None,
+        )
+        .await
+        .expect("download_to should succeed");
+    assert_eq!(
+        std::fs::read(&dest).expect("Failed to read download"),
+        content
+    );
+
+    // A decrypt function is applied on the way to disk
+    let reverse = |data: &[u8]| -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(data.iter().rev().copied().collect())
+    };
+    facade
+        .download_to(&ctx.path("large.bin"), &dest, Some(reverse))
+        .await
+        .expect("download_to should succeed");
+    assert_eq!(
+        std::fs::read(&dest).expect("Failed to read download"),
+        reverse(&content).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_copy_to_bucket() {
+    let ctx = S3TestContext::new("copy-to-bucket").await;
+    let facade = ctx.facade();
+    // Spaces and symbols must survive the CopySource header
+    ctx.write("reports/q1 summary+final.txt", b"quarterly")
+        .await;
+
+    facade
+        .copy_to_bucket(
+            &ctx.path("reports/q1 summary+final.txt"),
+            TEST_BUCKET_NAME,
+            &ctx.path("copies/q1 summary+final.txt"),
+        )
+        .await
+        .expect("copy_to_bucket should succeed");
+    assert_eq!(ctx.read("copies/q1 summary+final.txt").await, b"quarterly");
+    assert!(
+        facade
+            .file_exists(&ctx.path("reports/q1 summary+final.txt"))
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_write_many() {
+    let ctx = S3TestContext::new("write-many").await;
+    let entries: Vec<(String, Vec<u8>)> = (0..50)
+        .map(|i| {
+            (
+                ctx.path(&format!("{:02}.txt", i)),
+                i.to_string().into_bytes(),
+            )
+        })
+        .collect();
+
+    let results = ctx.facade().write_many(entries, DEFAULT_CONCURRENCY).await;
+    assert_eq!(results.len(), 50);
+    for (path, result) in &results {
+        assert!(result.is_ok(), "{} should have been written", path);
+    }
+    assert_eq!(
+        ctx.facade().list_objects(ctx.prefix()).await.unwrap().len(),
+        50
+    );
+    assert_eq!(ctx.read("07.txt").await, b"7");
+}
+
+#[tokio::test]
+async fn test_max_read_bytes() {
+    let ctx = S3TestContext::new("max-read-bytes").await;
+    ctx.write("small.bin", &[1; 1024]).await;
+    ctx.write("large.bin", &[1; 1025]).await;
+    let facade = S3Facade::new(TEST_BUCKET_NAME, "Read limit test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_max_read_bytes(1024);
+
+    let read = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("small.bin"),
+            None,
+        )
+        .await
+        .expect("an object at the limit should be read");
+    assert_eq!(read.len(), 1024);
+
+    let error = facade
+        .read_data::<fn(&[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>(
+            &ctx.path("large.bin"),
+            None,
+        )
+        .await
+        .expect_err("an object over the limit should be refused");
+    assert!(matches!(
+        &error,
+        StorageError::TooLarge {
+            size: 1025,
+            limit: 1024,
+            ..
+        }
+    ));
+
+    // Methods which send their own GET are limited too
+    let large = ctx.path("large.bin");
+    assert!(matches!(
+        facade.read_and_hash(&large).await,
+        Err(StorageError::TooLarge { .. })
+    ));
+    assert!(matches!(
+        facade
+            .read_conditional(&large, ReadPrecondition::default())
+            .await,
+        Err(StorageError::TooLarge { .. })
+    ));
+    let etag = facade
+        .get_file_metadata(&large)
+        .await
+        .unwrap()
+        .etag
+        .unwrap();
+    assert!(matches!(
+        facade.read_matching(&large, &etag).await,
+        Err(StorageError::TooLarge { .. })
+    ));
+
+    // Streams aren't limited
+    let mut streamed = Vec::new();
+    facade
+        .read_stream(&ctx.path("large.bin"))
+        .await
+        .expect("read_stream should succeed")
+        .read_to_end(&mut streamed)
+        .await
+        .unwrap();
+    assert_eq!(streamed.len(), 1025);
+}
+
+#[tokio::test]
+async fn test_health_check() {
+    let ctx = S3TestContext::new("health-check").await;
+    ctx.facade()
+        .health_check()
+        .await
+        .expect("the test bucket should be healthy");
+
+    let missing = S3Facade::from_client(
+        raw_client().await,
+        &format!("fallible-missing-{}", Uuid::new_v4()),
+        "Missing bucket",
+    );
+    assert!(matches!(
+        missing.health_check().await,
+        Err(StorageError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_append() {
+    let ctx = S3TestContext::new("append").await;
+    let path = ctx.path("logs/app.log");
+
+    ctx.facade()
+        .append(&path, b"first\n")
+        .await
+        .expect("appending to a missing object should create it");
+    ctx.facade()
+        .append(&path, b"second\n")
+        .await
+        .expect("append should succeed");
+
+    assert_eq!(ctx.read("logs/app.log").await, b"first\nsecond\n");
+
+    // The existing object is read whole, so it's held to the read limit
+    let limited = S3Facade::new(TEST_BUCKET_NAME, "Append limit test")
+        .await
+        .expect("Failed to create S3Facade for test")
+        .with_max_read_bytes(8);
+    assert!(matches!(
+        limited.append(&path, b"third\n").await,
+        Err(StorageError::TooLarge {
+            size: 13,
+            limit: 8,
+            ..
+        })
+    ));
+    assert_eq!(ctx.read("logs/app.log").await, b"first\nsecond\n");
+}
+
+#[tokio::test]
+async fn test_list_objects_relative() {
+    let ctx = S3TestContext::new("list-relative").await;
+    // A directory marker, as the console creates for an empty folder
+    ctx.write("docs/", b"").await;
+    ctx.write("docs/a.txt", b"a").await;
+    ctx.write("docs/sub/c.txt", b"c").await;
+
+    assert_eq!(
+        ctx.facade()
+            .list_objects_relative(&ctx.path("docs/"))
+            .await
+            .expect("list_objects_relative should succeed"),
+        vec!["a.txt", "sub/c.txt"]
+    );
+}
@@ -0,0 +1,102 @@
+//! Tests for the backend agnostic helpers in storage_facade
+//!
+//! These tests run entirely in memory, so need no backend or credentials.
+
+use fallible::storage_facade::{
+    DataStoreId, S3Arn, StorageError, parse_s3_arn, verify_crypto_roundtrip,
+};
+use std::error::Error;
+use std::path::PathBuf;
+
+/// XORs every byte with a key, which is its own inverse
+fn xor_with(key: u8) -> impl Fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    move |data| Ok(data.iter().map(|byte| byte ^ key).collect())
+}
+
+#[test]
+fn test_verify_crypto_roundtrip() {
+    verify_crypto_roundtrip(&xor_with(0x5a), &xor_with(0x5a))
+        .expect("A matching XOR pair should round trip");
+
+    let error = verify_crypto_roundtrip(&xor_with(0x5a), &xor_with(0x3c))
+        .expect_err("XOR pairs with different keys should be caught");
+    match error {
+        StorageError::Crypto(reason) => assert!(reason.contains("text"), "{}", reason),
+        other => panic!("Expected Crypto, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_s3_arn() {
+    assert_eq!(
+        parse_s3_arn("arn:aws:s3:::my-bucket"),
+        Some(S3Arn {
+            partition: "aws",
+            service: "s3",
+            region: "",
+            account_id: "",
+            resource: "my-bucket",
+        })
+    );
+
+    let gov = DataStoreId::S3("arn:aws-us-gov:s3:::records".to_string());
+    assert_eq!(gov.partition(), Some("aws-us-gov"));
+    assert_eq!(gov.bucket_name(), Some("records"));
+
+    let express =
+        DataStoreId::S3("arn:aws:s3express:eu-west-2::bucket/logs--euw2-az1--x-s3".to_string());
+    assert_eq!(express.bucket_name(), Some("logs--euw2-az1--x-s3"));
+
+    // Access points are S3 ARNs, but don't name a bucket
+    let access_point = "arn:aws:s3::123456789012:accesspoint/mfzwi23gnjvgw.mrap";
+    assert_eq!(parse_s3_arn(access_point).unwrap().bucket_name(), None);
+}
+
+#[test]
+fn test_parse_s3_arn_rejects_malformed() {
+    for arn in [
+        "",
+        "my-bucket",
+        "arn:aws:s3::",
+        "arn:aws:s3:::",
+        "arn:aws:dynamodb:eu-west-2:123456789012:table/users",
+        "urn:aws:s3:::my-bucket",
+        "arn::s3:::my-bucket",
+    ] {
+        assert_eq!(parse_s3_arn(arn), None, "{} should be rejected", arn);
+        assert_eq!(DataStoreId::S3(arn.to_string()).bucket_name(), None);
+    }
+
+    let local = DataStoreId::Local(PathBuf::from("/srv/store"));
+    assert_eq!(local.bucket_name(), None);
+    assert_eq!(local.partition(), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_store_metadata_serde_round_trip() {
+    use fallible::storage_facade::{DataStoreId, StoreMetadata};
+
+    let metadata = StoreMetadata {
+        id: DataStoreId::S3("arn:aws:s3:::my-bucket".to_string()),
+        name: "my-bucket".to_string(),
+        description: "Serde round trip".to_string(),
+    };
+
+    let json = serde_json::to_value(&metadata).expect("metadata should serialize");
+    assert_eq!(
+        json["id"],
+        serde_json::json!({"S3": "arn:aws:s3:::my-bucket"})
+    );
+    let restored: StoreMetadata =
+        serde_json::from_value(json).expect("metadata should deserialize");
+    assert_eq!(restored, metadata);
+
+    let local = DataStoreId::Local("/srv/store".into());
+    let json = serde_json::to_string(&local).expect("id should serialize");
+    assert_eq!(json, r#"{"Local":"/srv/store"}"#);
+    assert_eq!(
+        serde_json::from_str::<DataStoreId>(&json).expect("id should deserialize"),
+        local
+    );
+}
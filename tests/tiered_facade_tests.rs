@@ -0,0 +1,113 @@
+//! Tests for TieredFacade
+//!
+//! These tests run entirely in memory, layering two `MockFacade`s so the
+//! precedence rules can be checked without any backend.
+//!
+//! Only built with the `testing` feature, EG `cargo test --features testing`.
+#![cfg(feature = "testing")]
+
+use fallible::mock_facade::MockFacade;
+use fallible::storage_facade::{StorageError, StorageFacade};
+use fallible::tiered_facade::TieredFacade;
+use std::error::Error;
+
+type NoTransform = fn(&[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+
+#[tokio::test]
+async fn test_read_falls_back_to_secondary() {
+    let primary = MockFacade::new("hot", "Primary tier");
+    let secondary =
+        MockFacade::new("cold", "Secondary tier").with_object("archive/report.csv", b"old data");
+    let tiered = TieredFacade::new(primary, secondary, false);
+
+    let data = tiered
+        .read_data::<NoTransform>("archive/report.csv", None)
+        .await
+        .expect("read_data should fall back to the secondary");
+
+    assert_eq!(data, b"old data".to_vec());
+    assert!(
+        !tiered
+            .primary()
+            .snapshot()
+            .contains_key("archive/report.csv")
+    );
+}
+
+#[tokio::test]
+async fn test_read_promotes_to_primary() {
+    let primary = MockFacade::new("hot", "Primary tier");
+    let secondary =
+        MockFacade::new("cold", "Secondary tier").with_object("archive/report.csv", b"old data");
+    let tiered = TieredFacade::new(primary, secondary, true);
+
+    let data = tiered
+        .read_data::<NoTransform>("archive/report.csv", None)
+        .await
+        .expect("read_data should fall back to the secondary");
+
+    assert_eq!(data, b"old data".to_vec());
+    assert_eq!(
+        tiered.primary().snapshot()["archive/report.csv"],
+        b"old data"
+    );
+}
+
+#[tokio::test]
+async fn test_primary_takes_precedence() {
+    let primary = MockFacade::new("hot", "Primary tier").with_object("shared.txt", b"hot copy");
+    let secondary = MockFacade::new("cold", "Secondary tier")
+        .with_object("shared.txt", b"cold copy")
+        .with_object("cold-only.txt", b"cold");
+    let tiered = TieredFacade::new(primary, secondary, false);
+
+    let data = tiered
+        .read_data::<NoTransform>("shared.txt", None)
+        .await
+        .expect("read_data should succeed");
+    assert_eq!(data, b"hot copy".to_vec());
+
+    let listed = tiered
+        .list_objects("")
+        .await
+        .expect("list_objects should succeed");
+    assert_eq!(listed, vec!["cold-only.txt", "shared.txt"]);
+
+    tiered
+        .delete_file("shared.txt")
+        .await
+        .expect("delete_file should succeed");
+    assert!(!tiered.file_exists("shared.txt").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_write_if_absent_checks_both_tiers() {
+    let primary = MockFacade::new("hot", "Primary tier");
+    let secondary = MockFacade::new("cold", "Secondary tier").with_object("cold-only.txt", b"cold");
+    let tiered = TieredFacade::new(primary, secondary, false);
+
+    tiered
+        .write_data_if_absent::<NoTransform>("new.txt", b"first", None)
+        .await
+        .expect("write_data_if_absent should succeed for a new file");
+    for path in ["new.txt", "cold-only.txt"] {
+        let error = tiered
+            .write_data_if_absent::<NoTransform>(path, b"second", None)
+            .await
+            .expect_err("files in either tier should be left alone");
+        assert!(matches!(&error, StorageError::AlreadyExists(_)));
+    }
+    assert_eq!(tiered.primary().snapshot()["new.txt"], b"first");
+    assert!(!tiered.primary().snapshot().contains_key("cold-only.txt"));
+}
+
+#[tokio::test]
+async fn test_append_copies_up_from_secondary() {
+    let primary = MockFacade::new("hot", "Primary tier");
+    let secondary = MockFacade::new("cold", "Secondary tier").with_object("app.log", b"old\n");
+    let tiered = TieredFacade::new(primary, secondary, false);
+
+    tiered.append("app.log", b"new\n").await.unwrap();
+    assert_eq!(tiered.primary().snapshot()["app.log"], b"old\nnew\n");
+    assert_eq!(tiered.secondary().snapshot()["app.log"], b"old\n");
+}